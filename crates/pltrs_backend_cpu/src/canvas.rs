@@ -0,0 +1,342 @@
+//! A multisampled RGBA target filled the way the GPU fills its own.
+//!
+//! Samples sit at the standard positions for their count and are stored as
+//! 8-bit sRGB, blended in linear light and averaged into pixels at the end,
+//! so shapes come out with the same coverage and colours as the GPU draws
+//! them.
+
+use pltrs_core::Color;
+
+/// A scissor rectangle in device pixels: x, y of the top-left corner,
+/// width, height.
+pub(crate) type Scissor = (u32, u32, u32, u32);
+
+/// Offsets of the samples within a pixel, y down, for each sample count.
+fn sample_offsets(samples: u32) -> &'static [[f32; 2]] {
+    match samples {
+        4 => &[
+            [0.375, 0.125],
+            [0.875, 0.375],
+            [0.125, 0.625],
+            [0.625, 0.875],
+        ],
+        8 => &[
+            [0.5625, 0.3125],
+            [0.4375, 0.6875],
+            [0.8125, 0.5625],
+            [0.3125, 0.1875],
+            [0.1875, 0.8125],
+            [0.0625, 0.4375],
+            [0.6875, 0.9375],
+            [0.9375, 0.0625],
+        ],
+        _ => &[[0.5, 0.5]],
+    }
+}
+
+pub(crate) struct Canvas {
+    width: u32,
+    height: u32,
+    offsets: &'static [[f32; 2]],
+    /// `offsets.len()` samples per pixel, rows top to bottom.
+    samples: Vec<[u8; 4]>,
+    /// Linear value of each 8-bit sRGB level.
+    linear: [f32; 256],
+}
+
+impl Canvas {
+    /// A canvas of `width` by `height` pixels with `samples` samples each;
+    /// counts other than 4 and 8 draw a single sample.
+    pub(crate) fn new(width: u32, height: u32, samples: u32) -> Self {
+        let linear = std::array::from_fn(|level| {
+            let c = level as f32 / 255.0;
+            if c <= 0.040_45 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        });
+        let offsets = sample_offsets(samples);
+        Self {
+            width,
+            height,
+            offsets,
+            samples: vec![[0; 4]; width as usize * height as usize * offsets.len()],
+            linear,
+        }
+    }
+
+    pub(crate) fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub(crate) fn sample_count(&self) -> u32 {
+        self.offsets.len() as u32
+    }
+
+    pub(crate) fn clear(&mut self, color: Color) {
+        self.samples.fill(color.to_srgb8());
+    }
+
+    /// Fill the triangle with `corners` in device pixels, y down. Samples
+    /// on an edge belong to the triangle when the edge is a top or left
+    /// one, so triangles sharing an edge never both cover a sample. `shade`
+    /// gets the weights of the corners at the pixel centre and gives the
+    /// colour blended into the covered samples, or `None` to leave them.
+    pub(crate) fn triangle(
+        &mut self,
+        corners: [[f32; 2]; 3],
+        scissor: Scissor,
+        shade: impl Fn([f32; 3]) -> Option<Color>,
+    ) {
+        let [a, mut b, mut c] = corners;
+        let mut area = cross(a, b, c);
+        let swapped = area < 0.0;
+        if swapped {
+            std::mem::swap(&mut b, &mut c);
+            area = -area;
+        }
+        if area == 0.0 || area.is_nan() {
+            return;
+        }
+
+        // Edge functions, positive inside, each opposite one corner.
+        let edges = [Edge::new(b, c), Edge::new(c, a), Edge::new(a, b)];
+        let min = [a[0].min(b[0]).min(c[0]), a[1].min(b[1]).min(c[1])];
+        let max = [a[0].max(b[0]).max(c[0]), a[1].max(b[1]).max(c[1])];
+        let Some((x0, y0, x1, y1)) = self.bounds(min, max, scissor) else {
+            return;
+        };
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut mask = 0_u8;
+                for (i, [ox, oy]) in self.offsets.iter().enumerate() {
+                    let p = [x as f32 + ox, y as f32 + oy];
+                    if edges.iter().all(|edge| edge.covers(p)) {
+                        mask |= 1 << i;
+                    }
+                }
+                if mask == 0 {
+                    continue;
+                }
+                let centre = [x as f32 + 0.5, y as f32 + 0.5];
+                let [wa, mut wb, mut wc] = [0, 1, 2].map(|i| edges[i].value(centre) / area);
+                if swapped {
+                    std::mem::swap(&mut wb, &mut wc);
+                }
+                if let Some(color) = shade([wa, wb, wc]) {
+                    self.blend(x, y, mask, color);
+                }
+            }
+        }
+    }
+
+    /// Fill the axis-aligned rectangle from `min` to `max` in device
+    /// pixels, as two triangles would. `shade` gets the pixel centre.
+    pub(crate) fn rect(
+        &mut self,
+        min: [f32; 2],
+        max: [f32; 2],
+        scissor: Scissor,
+        shade: impl Fn([f32; 2]) -> Option<Color>,
+    ) {
+        let Some((x0, y0, x1, y1)) = self.bounds(min, max, scissor) else {
+            return;
+        };
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut mask = 0_u8;
+                for (i, [ox, oy]) in self.offsets.iter().enumerate() {
+                    let [px, py] = [x as f32 + ox, y as f32 + oy];
+                    if min[0] <= px && px < max[0] && min[1] <= py && py < max[1] {
+                        mask |= 1 << i;
+                    }
+                }
+                if mask == 0 {
+                    continue;
+                }
+                if let Some(color) = shade([x as f32 + 0.5, y as f32 + 0.5]) {
+                    self.blend(x, y, mask, color);
+                }
+            }
+        }
+    }
+
+    /// Pixels from `min` to `max` within the scissor and the canvas, as
+    /// half-open ranges.
+    fn bounds(
+        &self,
+        min: [f32; 2],
+        max: [f32; 2],
+        scissor: Scissor,
+    ) -> Option<(u32, u32, u32, u32)> {
+        if !min.iter().chain(&max).all(|v| v.is_finite()) {
+            return None;
+        }
+        let (sx, sy, sw, sh) = scissor;
+        let x0 = (min[0].floor().max(0.0) as u32).max(sx);
+        let y0 = (min[1].floor().max(0.0) as u32).max(sy);
+        let x1 = (max[0].ceil().max(0.0) as u32).min(sx + sw).min(self.width);
+        let y1 = (max[1].ceil().max(0.0) as u32)
+            .min(sy + sh)
+            .min(self.height);
+        (x0 < x1 && y0 < y1).then_some((x0, y0, x1, y1))
+    }
+
+    /// Blend `color` over the samples of pixel `x`, `y` set in `mask`.
+    fn blend(&mut self, x: u32, y: u32, mask: u8, color: Color) {
+        let count = self.offsets.len();
+        let start = (y as usize * self.width as usize + x as usize) * count;
+        let mut last: Option<([u8; 4], [u8; 4])> = None;
+        for i in 0..count {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+            let dst = self.samples[start + i];
+            let out = match last {
+                Some((seen, out)) if seen == dst => out,
+                _ => {
+                    let out = over(color, dst, &self.linear);
+                    last = Some((dst, out));
+                    out
+                }
+            };
+            self.samples[start + i] = out;
+        }
+    }
+
+    /// Average the samples of every pixel into 8-bit sRGB RGBA rows.
+    pub(crate) fn resolve(&self) -> Vec<u8> {
+        let count = self.offsets.len();
+        if count == 1 {
+            return self.samples.iter().flatten().copied().collect();
+        }
+        let mut pixels = Vec::with_capacity(self.samples.len() / count * 4);
+        for samples in self.samples.chunks_exact(count) {
+            let mut sum = [0.0_f32; 4];
+            for sample in samples {
+                for channel in 0..3 {
+                    sum[channel] += self.linear[sample[channel] as usize];
+                }
+                sum[3] += sample[3] as f32 / 255.0;
+            }
+            let n = count as f32;
+            let color = Color {
+                r: sum[0] / n,
+                g: sum[1] / n,
+                b: sum[2] / n,
+                a: sum[3] / n,
+            };
+            pixels.extend_from_slice(&color.to_srgb8());
+        }
+        pixels
+    }
+
+    /// Blend `color` over pixel `x`, `y` of resolved `pixels`, as text is
+    /// drawn over the resolved image on the GPU.
+    pub(crate) fn blend_pixel(&self, pixels: &mut [u8], x: u32, y: u32, color: Color) {
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        let Some(pixel) = pixels.get_mut(start..start + 4) else {
+            return;
+        };
+        let dst = [pixel[0], pixel[1], pixel[2], pixel[3]];
+        pixel.copy_from_slice(&over(color, dst, &self.linear));
+    }
+}
+
+/// `color` blended over the 8-bit sRGB `dst` with straight alpha.
+fn over(color: Color, dst: [u8; 4], linear: &[f32; 256]) -> [u8; 4] {
+    let a = color.a.clamp(0.0, 1.0);
+    let mix = |src: f32, dst: u8| src * a + linear[dst as usize] * (1.0 - a);
+    Color {
+        r: mix(color.r, dst[0]),
+        g: mix(color.g, dst[1]),
+        b: mix(color.b, dst[2]),
+        a: a + dst[3] as f32 / 255.0 * (1.0 - a),
+    }
+    .to_srgb8()
+}
+
+/// Twice the signed area of `a`, `b`, `c`; positive when clockwise on
+/// screen.
+fn cross(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// The edge from `from` to `to` of a triangle lying to its right on
+/// screen.
+struct Edge {
+    from: [f32; 2],
+    to: [f32; 2],
+    /// Whether points exactly on the edge are inside.
+    top_left: bool,
+}
+
+impl Edge {
+    fn new(from: [f32; 2], to: [f32; 2]) -> Self {
+        let [dx, dy] = [to[0] - from[0], to[1] - from[1]];
+        Self {
+            from,
+            to,
+            top_left: dy < 0.0 || (dy == 0.0 && dx > 0.0),
+        }
+    }
+
+    fn value(&self, p: [f32; 2]) -> f32 {
+        cross(self.from, self.to, p)
+    }
+
+    fn covers(&self, p: [f32; 2]) -> bool {
+        let value = self.value(p);
+        value > 0.0 || (value == 0.0 && self.top_left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL: Scissor = (0, 0, 4, 4);
+
+    #[test]
+    fn triangles_sharing_an_edge_cover_each_sample_once() {
+        let half_red = Color {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.5,
+        };
+        let mut canvas = Canvas::new(4, 4, 4);
+        canvas.clear(Color::BLACK);
+        // A square split along its diagonal, wound both ways.
+        canvas.triangle([[0.0, 0.0], [4.0, 0.0], [4.0, 4.0]], FULL, |_| {
+            Some(half_red)
+        });
+        canvas.triangle([[0.0, 0.0], [0.0, 4.0], [4.0, 4.0]], FULL, |_| {
+            Some(half_red)
+        });
+        let once = over(half_red, Color::BLACK.to_srgb8(), &canvas.linear);
+        assert!(canvas.samples.iter().all(|&sample| sample == once));
+    }
+
+    #[test]
+    fn edges_resolve_to_partial_coverage() {
+        let mut canvas = Canvas::new(2, 1, 4);
+        canvas.clear(Color::BLACK);
+        canvas.rect([0.0, 0.0], [1.5, 1.0], FULL, |_| Some(Color::WHITE));
+        let pixels = canvas.resolve();
+        assert_eq!(&pixels[..4], &[255, 255, 255, 255]);
+        // Two of the four samples are covered: half the light.
+        assert_eq!(
+            &pixels[4..],
+            &Color {
+                r: 0.5,
+                g: 0.5,
+                b: 0.5,
+                a: 1.0
+            }
+            .to_srgb8()
+        );
+    }
+}
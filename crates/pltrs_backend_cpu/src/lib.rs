@@ -0,0 +1,340 @@
+//! A software renderer for machines without a GPU.
+//!
+//! Figures are drawn from the same batches, in the same order and with the
+//! same geometry as the GPU backend: filled triangles and stroked lines go
+//! through the line shader's dash test, markers through the scatter
+//! shader's distance functions, and text is laid out and rasterized as the
+//! GPU text renderer does it. Shapes are multisampled at the GPU's sample
+//! positions, so PNGs from either backend match to within rounding.
+
+use anyhow::anyhow;
+use canvas::{Canvas, Scissor};
+use pltrs_core::{
+    build_axes_batches, check_png_path, Batches, Color, Figure, Image, LineBatch, MarkerBatch,
+    Rect, RenderBackend, SolidBatch, TextBatch,
+};
+use pltrs_geom::{stroke_polyline, StrokeStyle};
+use pltrs_text::TextRasterizer;
+use std::path::Path;
+
+mod canvas;
+mod marker;
+
+/// Draws figures into an RGBA image in memory.
+pub struct CpuBackend {
+    canvas: Canvas,
+    text: TextRasterizer,
+    /// Device pixels per logical pixel of the figure being drawn.
+    dpi: f32,
+    /// Text of the frame, drawn over the shapes when it ends.
+    texts: Vec<TextBatch>,
+    /// The last finished frame as 8-bit sRGB RGBA rows.
+    pixels: Vec<u8>,
+}
+
+impl CpuBackend {
+    /// A backend drawing `width` by `height` device pixels with `samples`
+    /// samples per pixel (1, 4 or 8).
+    pub fn new(width: u32, height: u32, samples: u32) -> anyhow::Result<Self> {
+        if !matches!(samples, 1 | 4 | 8) {
+            return Err(anyhow!("samples must be 1, 4 or 8, got {samples}"));
+        }
+        Ok(Self {
+            canvas: Canvas::new(width, height, samples),
+            text: TextRasterizer::new()?,
+            dpi: 1.0,
+            texts: Vec::new(),
+            pixels: Vec::new(),
+        })
+    }
+
+    /// The last finished frame as 8-bit sRGB RGBA rows, top to bottom.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Width and height in device pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.canvas.size()
+    }
+
+    fn add_axes(&mut self, batches: Batches) {
+        // Filled shapes go under lines, so grid lines show over bars and
+        // legend swatches over the legend box.
+        for solid in &batches.solids {
+            self.solid(solid);
+        }
+        for line in &batches.lines {
+            self.line(line);
+        }
+        for markers in &batches.markers {
+            self.markers(markers);
+        }
+        self.texts.extend(batches.texts);
+    }
+
+    /// Figure coordinates to device pixels, y down.
+    fn pixel(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let (width, height) = self.canvas.size();
+        [x * width as f32, (1.0 - y) * height as f32]
+    }
+
+    fn scissor(&self, clip: Rect) -> Scissor {
+        let (width, height) = self.canvas.size();
+        scissor_rect_for_axes(clip, width, height).unwrap_or((0, 0, width, height))
+    }
+
+    fn solid(&mut self, solid: &SolidBatch) {
+        let scissor = self.scissor(solid.clip);
+        for triangle in solid.vertices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| self.pixel(triangle[i]));
+            self.canvas
+                .triangle(corners, scissor, |_| Some(solid.color));
+        }
+    }
+
+    /// Stroke `line` in logical pixels, as the GPU backend does, and fill
+    /// the dashes of the resulting triangles.
+    fn line(&mut self, line: &LineBatch) {
+        if line.vertices.len() < 2 {
+            return;
+        }
+        let (width, height) = self.canvas.size();
+        let dpi = self.dpi;
+        let points: Vec<[f32; 2]> = line
+            .vertices
+            .iter()
+            .map(|[x, y]| [x * width as f32 / dpi, y * height as f32 / dpi])
+            .collect();
+        let stroke = StrokeStyle {
+            width: line.width.max(1.0),
+            join: line.join,
+            cap: line.cap,
+            miter_limit: line.miter_limit,
+            period: line.dashes.iter().sum(),
+        };
+        let mesh = stroke_polyline(&points, &stroke);
+        let scissor = self.scissor(line.clip);
+        let dashes = &line.dashes[..line.dashes.len().min(pltrs_core::LineStyle::MAX_DASHES)];
+        let dash_total: f32 = dashes.iter().sum();
+        for triangle in mesh.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| {
+                let [x, y] = triangle[i].position;
+                [x * dpi, height as f32 - y * dpi]
+            });
+            let distances = [0, 1, 2].map(|i| triangle[i].distance);
+            self.canvas.triangle(corners, scissor, |weights| {
+                let distance: f32 = (0..3).map(|i| weights[i] * distances[i]).sum();
+                in_dash(distance, dashes, dash_total).then_some(line.color)
+            });
+        }
+    }
+
+    fn markers(&mut self, batch: &MarkerBatch) {
+        let scissor = self.scissor(batch.clip);
+        let dpi = self.dpi;
+        for (i, &position) in batch.positions.iter().enumerate() {
+            let color = batch
+                .colors
+                .as_ref()
+                .and_then(|colors| colors.get(i))
+                .copied()
+                .unwrap_or(batch.color);
+            let size = batch
+                .sizes
+                .as_ref()
+                .and_then(|sizes| sizes.get(i))
+                .copied()
+                .unwrap_or(batch.size);
+            let [cx, cy] = self.pixel(position);
+            let extent = size * dpi * 0.5;
+            let (edge_color, edge_width) = (batch.edge_color, batch.edge_width);
+            self.canvas.rect(
+                [cx - extent, cy - extent],
+                [cx + extent, cy + extent],
+                scissor,
+                |[px, py]| {
+                    // Work in logical pixels so edges stay one pixel soft
+                    // at every marker size, as in the scatter shader.
+                    let half_size = size * 0.5;
+                    let p = [(px - cx) / dpi, (cy - py) / dpi];
+                    let d = marker::marker_sdf(p, batch.marker, half_size - 0.5);
+                    let coverage = (0.5 - d).clamp(0.0, 1.0);
+                    if coverage <= 0.0 {
+                        return None;
+                    }
+                    let mut color = color;
+                    if edge_width > 0.0 {
+                        let fill = (0.5 - (d + edge_width)).clamp(0.0, 1.0);
+                        color = mix(edge_color, color, fill);
+                    }
+                    Some(Color {
+                        a: color.a * coverage,
+                        ..color
+                    })
+                },
+            );
+        }
+    }
+}
+
+impl RenderBackend for CpuBackend {
+    fn begin_frame(&mut self, clear: Color) {
+        self.canvas.clear(clear);
+        self.texts.clear();
+    }
+
+    fn draw_scene(&mut self, fig: &Figure) {
+        self.dpi = fig.size.dpi;
+        for axes in &fig.axes {
+            self.add_axes(build_axes_batches(axes));
+        }
+    }
+
+    fn end_frame(&mut self) {
+        let mut pixels = self.canvas.resolve();
+        let (width, height) = self.canvas.size();
+        let canvas = &self.canvas;
+        self.text.rasterize(
+            width,
+            height,
+            self.dpi,
+            &self.texts,
+            |x, y, coverage, color| {
+                let color = Color {
+                    a: color.a * coverage,
+                    ..color
+                };
+                canvas.blend_pixel(&mut pixels, x, y, color);
+            },
+        );
+        self.texts.clear();
+        self.pixels = pixels;
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        if (width, height) != self.canvas.size() {
+            self.canvas = Canvas::new(width, height, self.canvas.sample_count());
+        }
+    }
+}
+
+/// Render `fig` with `samples` samples per pixel (1, 4 or 8) into an image
+/// of `fig.size.pixels()`.
+pub fn render_image(fig: &Figure, samples: u32) -> anyhow::Result<Image> {
+    if fig.size.width == 0 || fig.size.height == 0 {
+        return Err(anyhow!("figure size must be non-zero for rendering"));
+    }
+    if !(fig.size.dpi.is_finite() && fig.size.dpi > 0.0) {
+        return Err(anyhow!("figure dpi must be positive, got {}", fig.size.dpi));
+    }
+
+    let (width, height) = fig.size.pixels();
+    let mut backend = CpuBackend::new(width, height, samples)?;
+    backend.begin_frame(fig.clear_color);
+    backend.draw_scene(fig);
+    backend.end_frame();
+    Ok(Image {
+        width,
+        height,
+        pixels: backend.pixels,
+    })
+}
+
+/// Render `fig` with `samples` samples per pixel (1, 4 or 8) and save it
+/// as a PNG of `fig.size.pixels()`.
+pub fn save_figure_png(fig: &Figure, path: impl AsRef<Path>, samples: u32) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    check_png_path(path)?;
+    Ok(render_image(fig, samples)?.save_png(path)?)
+}
+
+/// Whether the line shader keeps a fragment `distance` pixels along a
+/// line with the on/off `dashes`.
+fn in_dash(distance: f32, dashes: &[f32], total: f32) -> bool {
+    if dashes.is_empty() {
+        return true;
+    }
+    let mut t = distance % total;
+    for (i, &len) in dashes.iter().enumerate() {
+        if t < len {
+            return i % 2 == 0;
+        }
+        t -= len;
+    }
+    true
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    Color {
+        r: lerp(a.r, b.r),
+        g: lerp(a.g, b.g),
+        b: lerp(a.b, b.b),
+        a: lerp(a.a, b.a),
+    }
+}
+
+/// The scissor the GPU backend clips `rect` with on a target of `width` by
+/// `height` pixels, or `None` when it is empty.
+fn scissor_rect_for_axes(rect: Rect, width: u32, height: u32) -> Option<Scissor> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let x = (rect.x.clamp(0.0, 1.0) * width as f32).floor() as u32;
+    let y_top = ((1.0 - (rect.y + rect.h).clamp(0.0, 1.0)) * height as f32).floor() as u32;
+    let x_end = ((rect.x + rect.w).clamp(0.0, 1.0) * width as f32).ceil() as u32;
+    let y_end = ((1.0 - rect.y.clamp(0.0, 1.0)) * height as f32).ceil() as u32;
+
+    let w = x_end.saturating_sub(x);
+    let h = y_end.saturating_sub(y_top);
+    if w == 0 || h == 0 {
+        None
+    } else {
+        Some((x, y_top, w, h))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pltrs_core::{Axes, Node, Patch, Scale, Size};
+
+    #[test]
+    fn figures_render_at_their_device_pixel_size() {
+        let mut fig = Figure::new(Size {
+            width: 20,
+            height: 10,
+            dpi: 2.0,
+        });
+        let unit = Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 1.0,
+            h: 1.0,
+        };
+        let mut axes = Axes::new(
+            unit,
+            Scale::linear((0.0, 1.0), (0.0, 1.0)),
+            Scale::linear((0.0, 1.0), (0.0, 1.0)),
+        );
+        // The left half black.
+        axes.add(Node::Patch(Patch {
+            x0: 0.0,
+            y0: 0.0,
+            x1: 0.5,
+            y1: 1.0,
+            color: Color::BLACK,
+        }));
+        fig.add_axes(axes);
+
+        let image = render_image(&fig, 4).unwrap();
+        assert_eq!((image.width, image.height), (40, 20));
+        assert_eq!(image.pixels.len(), 40 * 20 * 4);
+        assert_eq!(&image.pixels[..4], &[0, 0, 0, 255]);
+        assert_eq!(&image.pixels[39 * 4..40 * 4], &[255, 255, 255, 255]);
+
+        assert!(save_figure_png(&fig, "plot.jpg", 4).is_err());
+    }
+}
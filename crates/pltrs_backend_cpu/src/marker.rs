@@ -0,0 +1,136 @@
+//! The marker shapes of scatter_shader.wgsl, evaluated per pixel.
+//!
+//! Signed distance functions, negative inside. `r` is the radius of the
+//! circle the shape fits in; all shapes point up.
+
+use pltrs_core::Marker;
+
+type Vec2 = [f32; 2];
+
+fn length([x, y]: Vec2) -> f32 {
+    x.hypot(y)
+}
+
+fn dot(a: Vec2, b: Vec2) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+/// WGSL's `sign`, which is zero at zero.
+fn sign(v: f32) -> f32 {
+    if v == 0.0 {
+        0.0
+    } else {
+        v.signum()
+    }
+}
+
+fn sd_box(p: Vec2, b: Vec2) -> f32 {
+    let d = [p[0].abs() - b[0], p[1].abs() - b[1]];
+    length([d[0].max(0.0), d[1].max(0.0)]) + d[0].max(d[1]).min(0.0)
+}
+
+fn sd_triangle(p_in: Vec2, r: f32) -> f32 {
+    // Equilateral triangle with half side `h`, centred on its centroid.
+    let k = 3.0_f32.sqrt();
+    let h = r * k * 0.5;
+    let mut p = [p_in[0].abs() - h, p_in[1] + h / k];
+    if p[0] + k * p[1] > 0.0 {
+        p = [(p[0] - k * p[1]) / 2.0, (-k * p[0] - p[1]) / 2.0];
+    }
+    p[0] -= p[0].clamp(-2.0 * h, 0.0);
+    -length(p) * sign(p[1])
+}
+
+fn sd_plus(p: Vec2, r: f32) -> f32 {
+    let arm = r * 0.3;
+    sd_box(p, [r, arm]).min(sd_box(p, [arm, r]))
+}
+
+fn sd_star(p_in: Vec2, r: f32) -> f32 {
+    let rf = 0.45;
+    let k1 = [0.809_017, -0.587_785_24];
+    let k2 = [-k1[0], k1[1]];
+    let mut p = [p_in[0].abs(), p_in[1]];
+    let t = 2.0 * dot(k1, p).max(0.0);
+    p = [p[0] - t * k1[0], p[1] - t * k1[1]];
+    let t = 2.0 * dot(k2, p).max(0.0);
+    p = [p[0] - t * k2[0], p[1] - t * k2[1]];
+    p[0] = p[0].abs();
+    p[1] -= r;
+    let ba = [rf * -k1[1], rf * k1[0] - 1.0];
+    let h = (dot(p, ba) / dot(ba, ba)).clamp(0.0, r);
+    length([p[0] - ba[0] * h, p[1] - ba[1] * h]) * sign(p[1] * ba[0] - p[0] * ba[1])
+}
+
+fn sd_hexagon(p_in: Vec2, r: f32) -> f32 {
+    let k = [-0.866_025_4, 0.5, 0.577_350_26];
+    let inner = r * 0.866_025_4;
+    let mut p = [p_in[0].abs(), p_in[1].abs()];
+    let t = 2.0 * dot([k[0], k[1]], p).min(0.0);
+    p = [p[0] - t * k[0], p[1] - t * k[1]];
+    p = [p[0] - p[0].clamp(-k[2] * inner, k[2] * inner), p[1] - inner];
+    length(p) * sign(p[1])
+}
+
+fn sd_pentagon(p_in: Vec2, r: f32) -> f32 {
+    let k = [0.809_017, 0.587_785_24, 0.726_542_5];
+    let inner = r * k[0];
+    let mut p = [p_in[0].abs(), -p_in[1]];
+    for n in [[-k[0], k[1]], [k[0], k[1]]] {
+        let t = 2.0 * dot(n, p).min(0.0);
+        p = [p[0] - t * n[0], p[1] - t * n[1]];
+    }
+    p = [p[0] - p[0].clamp(-inner * k[2], inner * k[2]), p[1] - inner];
+    length(p) * sign(p[1])
+}
+
+fn rotate_45([x, y]: Vec2) -> Vec2 {
+    [(x + y) * 0.707_106_77, (y - x) * 0.707_106_77]
+}
+
+pub(crate) fn marker_sdf(p: Vec2, marker: Marker, r: f32) -> f32 {
+    match marker {
+        Marker::Circle => length(p) - r,
+        Marker::Square => sd_box(p, [r, r]),
+        Marker::TriangleUp => sd_triangle(p, r),
+        Marker::TriangleDown => sd_triangle([p[0], -p[1]], r),
+        Marker::TriangleLeft => sd_triangle([p[1], -p[0]], r),
+        Marker::TriangleRight => sd_triangle([-p[1], p[0]], r),
+        Marker::Diamond => sd_box(rotate_45(p), [r * 0.707_106_77; 2]),
+        Marker::Plus => sd_plus(p, r),
+        Marker::Cross => sd_plus(rotate_45(p), r),
+        Marker::Star => sd_star(p, r),
+        Marker::Hexagon => sd_hexagon(p, r),
+        Marker::Pentagon => sd_pentagon(p, r),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outline_corners_lie_on_the_shape_edges() {
+        let markers = [
+            Marker::Circle,
+            Marker::Square,
+            Marker::TriangleUp,
+            Marker::TriangleDown,
+            Marker::TriangleLeft,
+            Marker::TriangleRight,
+            Marker::Diamond,
+            Marker::Plus,
+            Marker::Cross,
+            Marker::Star,
+            Marker::Hexagon,
+            Marker::Pentagon,
+        ];
+        for marker in markers {
+            assert!(marker_sdf([0.0, 0.0], marker, 10.0) < 0.0, "{marker:?}");
+            for corner in marker.outline(10.0) {
+                let d = marker_sdf(corner, marker, 10.0);
+                assert!(d.abs() < 0.05, "{marker:?} at {corner:?}: {d}");
+            }
+        }
+    }
+}
@@ -0,0 +1,653 @@
+//! Vector PDF output for figures.
+//!
+//! Every figure becomes one page, drawn from the same batches and in the same
+//! order as the GPU backend draws them: every axes adds its fills, lines and
+//! markers in turn, and all text goes on top. Text is set in the figure font
+//! (the bundled Noto Sans, or `PLTRS_FONT_PATH`), embedded with only the
+//! glyphs the pages use. Nothing here needs a GPU.
+
+use anyhow::{anyhow, Context};
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use pdf_writer::types::{
+    CidFontType, FontFlags, LineCapStyle, LineJoinStyle, SystemInfo, UnicodeCmap,
+};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect as PdfRect, Ref, Str};
+use pltrs_core::{
+    build_axes_batches, Batches, Color, Figure, LineBatch, LineCap, LineJoin, Marker, MarkerBatch,
+    Rect, SolidBatch, TextBatch,
+};
+use pltrs_geom::inset_polygon;
+use pltrs_text::FontMetrics;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Name of the figure font in page resources.
+const FONT: Name<'static> = Name(b"F0");
+
+/// Character collection of fonts whose character ids are glyph ids.
+const IDENTITY: SystemInfo<'static> = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+/// Render `figs` as a PDF document with one page per figure, each sized in
+/// its logical pixels (one pixel to a point).
+pub fn render_pdf(figs: &[Figure]) -> anyhow::Result<Vec<u8>> {
+    anyhow::ensure!(!figs.is_empty(), "no figures to write to the PDF");
+    let data = pltrs_text::font_data()?;
+    let mut font = Font::new(&data)?;
+    let mut pdf = Pdf::new();
+    let mut next = Ref::new(1);
+    let catalog = next.bump();
+    let page_tree = next.bump();
+    let font_id = next.bump();
+
+    // One graphics state per opacity, shared by the pages using it.
+    let mut opacities: BTreeMap<u8, Ref> = BTreeMap::new();
+    let mut pages = Vec::with_capacity(figs.len());
+    for fig in figs {
+        let page = Page::draw(fig, &mut font);
+        let (page_id, content_id) = (next.bump(), next.bump());
+        pages.push(page_id);
+
+        let mut writer = pdf.page(page_id);
+        writer
+            .parent(page_tree)
+            .media_box(PdfRect::new(0.0, 0.0, page.width, page.height))
+            .contents(content_id);
+        let mut resources = writer.resources();
+        if page.has_text {
+            resources.fonts().pair(FONT, font_id);
+        }
+        let mut states = resources.ext_g_states();
+        for &alpha in &page.opacities {
+            let id = *opacities.entry(alpha).or_insert_with(|| next.bump());
+            states.pair(Name(opacity_name(alpha).as_bytes()), id);
+        }
+        states.finish();
+        resources.finish();
+        writer.finish();
+
+        let content = compress_to_vec_zlib(&page.content.finish(), 6);
+        pdf.stream(content_id, &content).filter(Filter::FlateDecode);
+    }
+    pdf.catalog(catalog).pages(page_tree);
+    pdf.pages(page_tree)
+        .kids(pages.iter().copied())
+        .count(pages.len() as i32);
+
+    for (alpha, id) in opacities {
+        let alpha = alpha as f32 / 255.0;
+        pdf.ext_graphics(id)
+            .non_stroking_alpha(alpha)
+            .stroking_alpha(alpha);
+    }
+    if !font.glyphs.is_empty() {
+        font.write(&mut pdf, font_id, &mut next)?;
+    }
+    Ok(pdf.finish())
+}
+
+/// Render `fig` as a single-page PDF and write it to `path`.
+pub fn save_figure_pdf(fig: &Figure, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    save_figures_pdf(std::slice::from_ref(fig), path)
+}
+
+/// Render `figs` as a PDF with one page per figure and write it to `path`.
+pub fn save_figures_pdf(figs: &[Figure], path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let pdf = render_pdf(figs)?;
+    std::fs::write(path, pdf).with_context(|| format!("failed to write PDF to {:?}", path))
+}
+
+fn opacity_name(alpha: u8) -> String {
+    format!("A{alpha}")
+}
+
+/// The figure font and the glyphs the pages have used so far.
+struct Font<'a> {
+    data: &'a [u8],
+    face: ttf_parser::Face<'a>,
+    metrics: FontMetrics,
+    /// Glyphs used, with the character each stands for.
+    glyphs: BTreeMap<u16, char>,
+}
+
+impl<'a> Font<'a> {
+    fn new(data: &'a [u8]) -> anyhow::Result<Self> {
+        let face = ttf_parser::Face::parse(data, 0)
+            .map_err(|err| anyhow!("failed to parse font: {err}"))?;
+        Ok(Self {
+            data,
+            face,
+            metrics: FontMetrics::new(data)?,
+            glyphs: BTreeMap::new(),
+        })
+    }
+
+    /// `text` as two-byte glyph ids, the encoding of the embedded font.
+    fn encode(&mut self, text: &str) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(text.len() * 2);
+        for c in text.chars() {
+            let glyph = self.face.glyph_index(c).map_or(0, |id| id.0);
+            self.glyphs.entry(glyph).or_insert(c);
+            encoded.extend_from_slice(&glyph.to_be_bytes());
+        }
+        encoded
+    }
+
+    /// A length in font units, in thousandths of the font size.
+    fn scaled(&self, units: impl Into<f32>) -> f32 {
+        units.into() * 1000.0 / self.face.units_per_em() as f32
+    }
+
+    fn write(&self, pdf: &mut Pdf, type0: Ref, next: &mut Ref) -> anyhow::Result<()> {
+        let (cid_font, descriptor, file, cmap) =
+            (next.bump(), next.bump(), next.bump(), next.bump());
+        let glyphs: Vec<u16> = self.glyphs.keys().copied().collect();
+        let subset = subsetter::subset(self.data, 0, subsetter::Profile::pdf(&glyphs))
+            .map_err(|err| anyhow!("failed to subset font: {err}"))?;
+        let name = format!("{}+{}", subset_tag(&glyphs), self.postscript_name());
+        let name = Name(name.as_bytes());
+        let cff = self.face.tables().cff.is_some() || self.face.tables().cff2.is_some();
+
+        pdf.type0_font(type0)
+            .base_font(name)
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_font)
+            .to_unicode(cmap);
+
+        let mut cid = pdf.cid_font(cid_font);
+        cid.subtype(if cff {
+            CidFontType::Type0
+        } else {
+            CidFontType::Type2
+        })
+        .base_font(name)
+        .system_info(IDENTITY)
+        .font_descriptor(descriptor)
+        .default_width(0.0);
+        if !cff {
+            cid.cid_to_gid_map_predefined(Name(b"Identity"));
+        }
+        let mut widths = cid.widths();
+        for &glyph in &glyphs {
+            let advance = self
+                .face
+                .glyph_hor_advance(ttf_parser::GlyphId(glyph))
+                .unwrap_or(0);
+            widths.consecutive(glyph, [self.scaled(advance)]);
+        }
+        widths.finish();
+        cid.finish();
+
+        let bbox = self.face.global_bounding_box();
+        let mut flags = FontFlags::NON_SYMBOLIC;
+        if self.face.is_monospaced() {
+            flags |= FontFlags::FIXED_PITCH;
+        }
+        if self.face.is_italic() {
+            flags |= FontFlags::ITALIC;
+        }
+        let mut desc = pdf.font_descriptor(descriptor);
+        desc.name(name)
+            .flags(flags)
+            .bbox(PdfRect::new(
+                self.scaled(bbox.x_min),
+                self.scaled(bbox.y_min),
+                self.scaled(bbox.x_max),
+                self.scaled(bbox.y_max),
+            ))
+            .italic_angle(self.face.italic_angle())
+            .ascent(self.scaled(self.face.ascender()))
+            .descent(self.scaled(self.face.descender()))
+            .cap_height(self.scaled(self.face.capital_height().unwrap_or(self.face.ascender())))
+            .stem_v(80.0);
+        if cff {
+            desc.font_file3(file);
+        } else {
+            desc.font_file2(file);
+        }
+        desc.finish();
+
+        let compressed = compress_to_vec_zlib(&subset, 6);
+        let mut stream = pdf.stream(file, &compressed);
+        stream.filter(Filter::FlateDecode);
+        if cff {
+            stream.pair(Name(b"Subtype"), Name(b"OpenType"));
+        }
+        stream.finish();
+
+        let mut unicode = UnicodeCmap::new(Name(b"Custom"), IDENTITY);
+        for (&glyph, &c) in &self.glyphs {
+            unicode.pair(glyph, c);
+        }
+        pdf.cmap(cmap, &unicode.finish());
+        Ok(())
+    }
+
+    /// The font's PostScript name, which names it in the PDF.
+    fn postscript_name(&self) -> String {
+        let name = self
+            .face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .find_map(|name| name.to_string())
+            .unwrap_or_else(|| self.metrics.family.clone());
+        name.chars()
+            .filter(|c| c.is_ascii_graphic() && !"[](){}<>/%#".contains(*c))
+            .collect()
+    }
+}
+
+/// The six capital letters that mark a font as a subset, derived from the
+/// glyphs it keeps so that the same text gives the same file.
+fn subset_tag(glyphs: &[u16]) -> String {
+    let mut hash: u32 = 0x811c_9dc5;
+    for &glyph in glyphs {
+        for byte in glyph.to_be_bytes() {
+            hash = (hash ^ byte as u32).wrapping_mul(0x0100_0193);
+        }
+    }
+    (0..6)
+        .map(|_| {
+            let letter = (b'A' + (hash % 26) as u8) as char;
+            hash /= 26;
+            letter
+        })
+        .collect()
+}
+
+/// The content stream of one page.
+struct Page {
+    content: Content,
+    width: f32,
+    height: f32,
+    /// Opacities the page switches to.
+    opacities: BTreeSet<u8>,
+    has_text: bool,
+    /// The clip rectangle of the open `q`/`Q` group, if any, in points.
+    clip: Option<[f32; 4]>,
+    /// The opacity in effect.
+    alpha: u8,
+}
+
+impl Page {
+    fn draw(fig: &Figure, font: &mut Font) -> Self {
+        let mut page = Page {
+            content: Content::new(),
+            width: fig.size.width as f32,
+            height: fig.size.height as f32,
+            opacities: BTreeSet::new(),
+            has_text: false,
+            clip: None,
+            alpha: 255,
+        };
+        page.set_fill(fig.clear_color);
+        page.content
+            .rect(0.0, 0.0, page.width, page.height)
+            .fill_nonzero();
+
+        let mut texts = Vec::new();
+        for axes in &fig.axes {
+            let batches: Batches = build_axes_batches(axes);
+            for solid in &batches.solids {
+                page.solid(solid);
+            }
+            for line in &batches.lines {
+                page.line(line);
+            }
+            for markers in &batches.markers {
+                page.markers(markers);
+            }
+            texts.extend(batches.texts);
+        }
+        page.end_clip();
+
+        for text in &texts {
+            page.text(text, font);
+        }
+        page
+    }
+
+    /// Figure coordinates to points. Both have y up.
+    fn point(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [x * self.width, y * self.height]
+    }
+
+    /// Draw what follows clipped to `rect`, reusing the open group when it
+    /// has the same clip.
+    fn set_clip(&mut self, rect: Rect) {
+        let [x, y] = self.point([rect.x, rect.y]);
+        let clip = [x, y, rect.w * self.width, rect.h * self.height];
+        if self.clip == Some(clip) {
+            return;
+        }
+        self.end_clip();
+        let [x, y, w, h] = clip;
+        self.content
+            .save_state()
+            .rect(x, y, w, h)
+            .clip_nonzero()
+            .end_path();
+        self.clip = Some(clip);
+    }
+
+    fn end_clip(&mut self) {
+        if self.clip.take().is_some() {
+            self.content.restore_state();
+            // Restoring the state also restores full opacity.
+            self.alpha = 255;
+        }
+    }
+
+    fn set_alpha(&mut self, alpha: u8) {
+        if alpha == self.alpha {
+            return;
+        }
+        self.opacities.insert(alpha);
+        self.content
+            .set_parameters(Name(opacity_name(alpha).as_bytes()));
+        self.alpha = alpha;
+    }
+
+    fn set_fill(&mut self, color: Color) {
+        let [r, g, b, a] = color.to_srgb8();
+        self.set_alpha(a);
+        self.content
+            .set_fill_rgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    }
+
+    fn set_stroke(&mut self, color: Color) {
+        let [r, g, b, a] = color.to_srgb8();
+        self.set_alpha(a);
+        self.content
+            .set_stroke_rgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    }
+
+    fn solid(&mut self, solid: &SolidBatch) {
+        let mut polygons: Vec<Vec<[f32; 2]>> = Vec::new();
+        for triangles in solid.vertices.chunks(6) {
+            // Rectangles come as two triangles sharing a diagonal; drawing
+            // them whole avoids a hairline seam along it.
+            let corners: &[[f32; 2]] = match triangles {
+                [a, b, c, a2, c2, d] if a == a2 && c == c2 => &[*a, *b, *c, *d],
+                _ => triangles,
+            };
+            let corners: Vec<&[[f32; 2]]> = if corners.len() == 4 {
+                vec![corners]
+            } else {
+                corners.chunks_exact(3).collect()
+            };
+            polygons.extend(
+                corners
+                    .into_iter()
+                    .filter(|corners| corners.iter().flatten().all(|v| v.is_finite()))
+                    .map(|corners| corners.iter().map(|&c| self.point(c)).collect()),
+            );
+        }
+        if polygons.is_empty() {
+            return;
+        }
+        self.set_clip(solid.clip);
+        self.set_fill(solid.color);
+        for polygon in &polygons {
+            self.polygon(polygon);
+        }
+        self.content.fill_nonzero();
+    }
+
+    /// Add a closed path through `points`, already in points, to the
+    /// current path.
+    fn polygon(&mut self, points: &[[f32; 2]]) {
+        for (i, &[x, y]) in points.iter().enumerate() {
+            if i == 0 {
+                self.content.move_to(x, y);
+            } else {
+                self.content.line_to(x, y);
+            }
+        }
+        self.content.close_path();
+    }
+
+    fn line(&mut self, line: &LineBatch) {
+        let runs: Vec<&[[f32; 2]]> = line
+            .vertices
+            .split(|[x, y]| !(x.is_finite() && y.is_finite()))
+            .filter(|run| run.len() >= 2)
+            .collect();
+        if runs.is_empty() {
+            return;
+        }
+
+        self.set_clip(line.clip);
+        self.set_stroke(line.color);
+        self.content
+            .set_line_width(line.width.max(1.0))
+            .set_line_join(match line.join {
+                LineJoin::Miter => LineJoinStyle::MiterJoin,
+                LineJoin::Round => LineJoinStyle::RoundJoin,
+                LineJoin::Bevel => LineJoinStyle::BevelJoin,
+            })
+            .set_line_cap(match line.cap {
+                LineCap::Butt => LineCapStyle::ButtCap,
+                LineCap::Round => LineCapStyle::RoundCap,
+                LineCap::Square => LineCapStyle::ProjectingSquareCap,
+            })
+            .set_miter_limit(line.miter_limit)
+            .set_dash_pattern(line.dashes.iter().copied(), 0.0);
+        for run in runs {
+            for (i, &vertex) in run.iter().enumerate() {
+                let [x, y] = self.point(vertex);
+                if i == 0 {
+                    self.content.move_to(x, y);
+                } else {
+                    self.content.line_to(x, y);
+                }
+            }
+        }
+        self.content.stroke();
+    }
+
+    fn markers(&mut self, batch: &MarkerBatch) {
+        if batch.positions.is_empty() {
+            return;
+        }
+        self.set_clip(batch.clip);
+        for (i, &position) in batch.positions.iter().enumerate() {
+            let color = batch
+                .colors
+                .as_ref()
+                .and_then(|colors| colors.get(i))
+                .copied()
+                .unwrap_or(batch.color);
+            let size = batch
+                .sizes
+                .as_ref()
+                .and_then(|sizes| sizes.get(i))
+                .copied()
+                .unwrap_or(batch.size);
+            if size <= 0.0 || !position.iter().all(|v| v.is_finite()) {
+                continue;
+            }
+            self.marker(self.point(position), size * 0.5, color, batch);
+        }
+    }
+
+    fn marker(&mut self, [x, y]: [f32; 2], radius: f32, color: Color, batch: &MarkerBatch) {
+        let edge = batch.edge_width.max(0.0);
+        // Circles are drawn as curves; other shapes use their outline.
+        let shape = |page: &mut Page, inset: f32| -> bool {
+            if batch.marker == Marker::Circle {
+                if radius <= inset {
+                    return false;
+                }
+                page.circle([x, y], radius - inset);
+                return true;
+            }
+            let outline = batch.marker.outline(radius);
+            let points = if inset > 0.0 {
+                inset_polygon(&outline, inset)
+            } else {
+                outline
+            };
+            if area(&points) <= 0.0 {
+                return false;
+            }
+            let placed: Vec<[f32; 2]> = points.iter().map(|[px, py]| [x + px, y + py]).collect();
+            page.polygon(&placed);
+            true
+        };
+
+        if edge > 0.0 {
+            // A ring from the outline to the inset fill, drawn inside the
+            // marker as on the GPU.
+            self.set_fill(batch.edge_color);
+            shape(self, 0.0);
+            shape(self, edge);
+            self.content.fill_even_odd();
+        }
+        self.set_fill(color);
+        if shape(self, edge) {
+            self.content.fill_nonzero();
+        }
+    }
+
+    /// Add a circle, as four cubic curves, to the current path.
+    fn circle(&mut self, [x, y]: [f32; 2], r: f32) {
+        // Control point distance for a quarter circle.
+        let k = 0.552_284_8 * r;
+        self.content
+            .move_to(x + r, y)
+            .cubic_to(x + r, y + k, x + k, y + r, x, y + r)
+            .cubic_to(x - k, y + r, x - r, y + k, x - r, y)
+            .cubic_to(x - r, y - k, x - k, y - r, x, y - r)
+            .cubic_to(x + k, y - r, x + r, y - k, x + r, y)
+            .close_path();
+    }
+
+    fn text(&mut self, text: &TextBatch, font: &mut Font) {
+        if text.content.is_empty() {
+            return;
+        }
+        // The GPU text renderer places the top of the line at the position
+        // and scales the font so ascent to descent spans the text size.
+        let [x, top] = self.point(text.position);
+        let encoded = font.encode(&text.content);
+        self.has_text = true;
+        self.set_fill(text.color);
+        self.content
+            .begin_text()
+            .set_font(FONT, font.metrics.em * text.size)
+            .next_line(x, top - font.metrics.ascent * text.size)
+            .show(Str(&encoded))
+            .end_text();
+    }
+}
+
+/// Twice the signed area of `points`; positive when counter-clockwise.
+fn area(points: &[[f32; 2]]) -> f32 {
+    (0..points.len())
+        .map(|i| {
+            let ([x0, y0], [x1, y1]) = (points[i], points[(i + 1) % points.len()]);
+            x0 * y1 - x1 * y0
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pltrs_core::{Axes, Line, LineStyle, Node, Scale, Scatter, Size, Text};
+
+    fn figure(width: u32) -> Figure {
+        let mut axes = Axes::new(
+            Rect {
+                x: 0.1,
+                y: 0.1,
+                w: 0.8,
+                h: 0.8,
+            },
+            Scale::linear((0.0, 1.0), (0.0, 1.0)),
+            Scale::linear((0.0, 1.0), (0.0, 1.0)),
+        );
+        axes.add(Node::Line(Line {
+            xs: vec![0.0, 0.5, f64::NAN, 0.7, 1.0],
+            ys: vec![0.0, 1.0, 0.0, 0.2, 0.4],
+            color: Color::BLACK,
+            width: 2.0,
+            style: LineStyle::Dashed,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 2.0,
+            label: None,
+        }));
+        axes.add(Node::Scatter(Scatter {
+            xs: vec![0.25, 0.75],
+            ys: vec![0.5, 0.5],
+            color: Color {
+                a: 0.5,
+                ..Color::GREEN
+            },
+            colors: None,
+            size: 10.0,
+            sizes: None,
+            marker: Marker::Star,
+            edge_color: Color::BLACK,
+            edge_width: 1.0,
+            label: None,
+        }));
+        axes.add(Node::Text(Text {
+            content: "Ab".to_string(),
+            x: 0.5,
+            y: 0.5,
+            color: Color::BLACK,
+            size: 16.0,
+        }));
+        let mut fig = Figure::new(Size {
+            width,
+            height: 100,
+            dpi: 1.0,
+        });
+        fig.add_axes(axes);
+        fig
+    }
+
+    #[test]
+    fn pages_clip_stroke_fill_and_set_text() {
+        let data = pltrs_text::font_data().unwrap();
+        let mut font = Font::new(&data).unwrap();
+        let page = Page::draw(&figure(200), &mut font);
+        assert!(page.has_text);
+        assert_eq!(page.opacities, BTreeSet::from([128, 255]));
+
+        let content = String::from_utf8_lossy(&page.content.finish()).into_owned();
+        assert!(content.contains("q\n20 10 160 80 re\nW\nn\n"));
+        assert!(content.contains("[7.4 3.2] 0 d"), "{content}");
+        assert!(content.contains("/A128 gs"));
+        assert!(content.contains("/F0 "));
+        assert!(content.trim_end().ends_with("ET"));
+
+        let glyphs: Vec<char> = font.glyphs.values().copied().collect();
+        assert!(glyphs.contains(&'A') && glyphs.contains(&'b'));
+    }
+
+    #[test]
+    fn figures_become_pages_of_one_document() {
+        let pdf = render_pdf(&[figure(200), figure(300)]).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/MediaBox [0 0 200 100]"));
+        assert!(text.contains("/MediaBox [0 0 300 100]"));
+        assert!(text.contains("/Encoding /Identity-H"));
+        assert!(text.contains("/FontFile2"));
+        assert!(text.contains("/ToUnicode"));
+
+        // The subset is much smaller than the whole font.
+        assert!(pdf.len() < pltrs_text::font_data().unwrap().len() / 4);
+        assert!(render_pdf(&[]).is_err());
+    }
+}
@@ -0,0 +1,485 @@
+//! Vector SVG output for figures.
+//!
+//! Figures are drawn from the same batches, in the same order, as the GPU
+//! backend draws them: every axes adds its fills, lines and markers in turn,
+//! and all text goes on top. Nothing here needs a GPU.
+
+use anyhow::Context;
+use pltrs_core::{
+    build_axes_batches, Batches, Color, Figure, LineBatch, LineCap, LineJoin, Marker, MarkerBatch,
+    Rect, SolidBatch, TextBatch,
+};
+use pltrs_geom::inset_polygon;
+use pltrs_text::FontMetrics;
+use std::fmt::{self, Display, Write};
+use std::path::Path;
+
+/// Render `fig` as an SVG document sized in its logical pixels.
+pub fn render_svg(fig: &Figure) -> anyhow::Result<String> {
+    let font = FontMetrics::new(&pltrs_text::font_data()?)?;
+    let mut svg = Svg {
+        out: String::new(),
+        width: fig.size.width as f32,
+        height: fig.size.height as f32,
+        font,
+        clips: Vec::new(),
+        clip: None,
+    };
+    svg.figure(fig).context("failed to write SVG")?;
+    Ok(svg.out)
+}
+
+/// Render `fig` as an SVG document and write it to `path`.
+pub fn save_figure_svg(fig: &Figure, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let svg = render_svg(fig)?;
+    std::fs::write(path, svg).with_context(|| format!("failed to write SVG to {:?}", path))
+}
+
+/// A clip rectangle in pixels: x, y of the top-left corner, width, height.
+type ClipRect = [f32; 4];
+
+struct Svg {
+    out: String,
+    width: f32,
+    height: f32,
+    font: FontMetrics,
+    /// Clip rectangles defined so far; their index is their id.
+    clips: Vec<ClipRect>,
+    /// The clip of the open group, if any.
+    clip: Option<ClipRect>,
+}
+
+impl Svg {
+    fn figure(&mut self, fig: &Figure) -> fmt::Result {
+        let (width, height) = (Num(self.width), Num(self.height));
+        writeln!(self.out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            self.out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )?;
+        writeln!(
+            self.out,
+            r#"<rect width="{width}" height="{height}"{}/>"#,
+            Paint("fill", fig.clear_color)
+        )?;
+
+        let mut texts = Vec::new();
+        for axes in &fig.axes {
+            let batches: Batches = build_axes_batches(axes);
+            for solid in &batches.solids {
+                self.solid(solid)?;
+            }
+            for line in &batches.lines {
+                self.line(line)?;
+            }
+            for markers in &batches.markers {
+                self.markers(markers)?;
+            }
+            texts.extend(batches.texts);
+        }
+        self.end_clip()?;
+
+        if !texts.is_empty() {
+            writeln!(
+                self.out,
+                r#"<g font-family="{}, sans-serif">"#,
+                Escaped(&format!("'{}'", self.font.family))
+            )?;
+            for text in &texts {
+                self.text(text)?;
+            }
+            writeln!(self.out, "</g>")?;
+        }
+        writeln!(self.out, "</svg>")
+    }
+
+    /// Figure coordinates to pixels, y down.
+    fn point(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [x * self.width, (1.0 - y) * self.height]
+    }
+
+    /// Draw what follows clipped to `rect`, reusing the open group when it
+    /// has the same clip.
+    fn set_clip(&mut self, rect: Rect) -> fmt::Result {
+        let [x, y] = self.point([rect.x, rect.y + rect.h]);
+        let clip = [x, y, rect.w * self.width, rect.h * self.height];
+        if self.clip == Some(clip) {
+            return Ok(());
+        }
+        self.end_clip()?;
+
+        let id = match self.clips.iter().position(|&known| known == clip) {
+            Some(id) => id,
+            None => {
+                let [x, y, w, h] = clip.map(Num);
+                let id = self.clips.len();
+                writeln!(
+                    self.out,
+                    r#"<clipPath id="clip{id}"><rect x="{x}" y="{y}" width="{w}" height="{h}"/></clipPath>"#
+                )?;
+                self.clips.push(clip);
+                id
+            }
+        };
+        writeln!(self.out, r#"<g clip-path="url(#clip{id})">"#)?;
+        self.clip = Some(clip);
+        Ok(())
+    }
+
+    fn end_clip(&mut self) -> fmt::Result {
+        if self.clip.take().is_some() {
+            writeln!(self.out, "</g>")?;
+        }
+        Ok(())
+    }
+
+    fn solid(&mut self, solid: &SolidBatch) -> fmt::Result {
+        let mut d = String::new();
+        for triangles in solid.vertices.chunks(6) {
+            // Rectangles come as two triangles sharing a diagonal; drawing
+            // them whole avoids a hairline seam along it.
+            let corners: &[[f32; 2]] = match triangles {
+                [a, b, c, a2, c2, d] if a == a2 && c == c2 => &[*a, *b, *c, *d],
+                _ => triangles,
+            };
+            if corners.len() == 4 {
+                self.polygon(&mut d, corners)?;
+            } else {
+                for triangle in corners.chunks_exact(3) {
+                    self.polygon(&mut d, triangle)?;
+                }
+            }
+        }
+        if d.is_empty() {
+            return Ok(());
+        }
+        self.set_clip(solid.clip)?;
+        writeln!(self.out, r#"<path d="{d}"{}/>"#, Paint("fill", solid.color))
+    }
+
+    fn polygon(&self, d: &mut String, corners: &[[f32; 2]]) -> fmt::Result {
+        if !corners.iter().flatten().all(|v| v.is_finite()) {
+            return Ok(());
+        }
+        for (i, &corner) in corners.iter().enumerate() {
+            let [x, y] = self.point(corner).map(Num);
+            write!(d, "{}{x} {y}", if i == 0 { "M" } else { "L" })?;
+        }
+        write!(d, "Z")
+    }
+
+    fn line(&mut self, line: &LineBatch) -> fmt::Result {
+        let mut d = String::new();
+        for run in line
+            .vertices
+            .split(|[x, y]| !(x.is_finite() && y.is_finite()))
+        {
+            if run.len() < 2 {
+                continue;
+            }
+            for (i, &vertex) in run.iter().enumerate() {
+                let [x, y] = self.point(vertex).map(Num);
+                write!(d, "{}{x} {y}", if i == 0 { "M" } else { "L" })?;
+            }
+        }
+        if d.is_empty() {
+            return Ok(());
+        }
+
+        self.set_clip(line.clip)?;
+        let join = match line.join {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        };
+        let cap = match line.cap {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        };
+        write!(
+            self.out,
+            r#"<path d="{d}" fill="none"{} stroke-width="{}" stroke-linejoin="{join}" stroke-linecap="{cap}" stroke-miterlimit="{}""#,
+            Paint("stroke", line.color),
+            Num(line.width.max(1.0)),
+            Num(line.miter_limit),
+        )?;
+        if !line.dashes.is_empty() {
+            let dashes: Vec<String> = line
+                .dashes
+                .iter()
+                .map(|&len| Num(len).to_string())
+                .collect();
+            write!(self.out, r#" stroke-dasharray="{}""#, dashes.join(" "))?;
+        }
+        writeln!(self.out, "/>")
+    }
+
+    fn markers(&mut self, batch: &MarkerBatch) -> fmt::Result {
+        if batch.positions.is_empty() {
+            return Ok(());
+        }
+        self.set_clip(batch.clip)?;
+        for (i, &position) in batch.positions.iter().enumerate() {
+            let color = batch
+                .colors
+                .as_ref()
+                .and_then(|colors| colors.get(i))
+                .copied()
+                .unwrap_or(batch.color);
+            let size = batch
+                .sizes
+                .as_ref()
+                .and_then(|sizes| sizes.get(i))
+                .copied()
+                .unwrap_or(batch.size);
+            if size <= 0.0 {
+                continue;
+            }
+            let center = self.point(position);
+            if batch.marker == Marker::Circle {
+                self.circle(center, size * 0.5, color, batch)?;
+            } else {
+                self.shape(center, size * 0.5, color, batch)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn circle(
+        &mut self,
+        [x, y]: [f32; 2],
+        radius: f32,
+        color: Color,
+        batch: &MarkerBatch,
+    ) -> fmt::Result {
+        let (cx, cy) = (Num(x), Num(y));
+        let edge = batch.edge_width.min(radius);
+        if edge > 0.0 {
+            // The outline is drawn inside the marker, as on the GPU.
+            writeln!(
+                self.out,
+                r#"<circle cx="{cx}" cy="{cy}" r="{}" fill="none"{} stroke-width="{}"/>"#,
+                Num(radius - edge * 0.5),
+                Paint("stroke", batch.edge_color),
+                Num(edge),
+            )?;
+        }
+        if radius > edge {
+            writeln!(
+                self.out,
+                r#"<circle cx="{cx}" cy="{cy}" r="{}"{}/>"#,
+                Num(radius - edge),
+                Paint("fill", color)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn shape(
+        &mut self,
+        [x, y]: [f32; 2],
+        radius: f32,
+        color: Color,
+        batch: &MarkerBatch,
+    ) -> fmt::Result {
+        let outline = batch.marker.outline(radius);
+        let inner = if batch.edge_width > 0.0 {
+            Some(inset_polygon(&outline, batch.edge_width)).filter(|inner| area(inner) > 0.0)
+        } else {
+            Some(outline.clone())
+        };
+        let place = |points: &[[f32; 2]]| -> Vec<[f32; 2]> {
+            points.iter().map(|[px, py]| [x + px, y - py]).collect()
+        };
+
+        if batch.edge_width > 0.0 {
+            // A ring from the outline to the inset fill, drawn inside the
+            // marker as on the GPU.
+            let mut d = pixel_polygon(&place(&outline))?;
+            if let Some(inner) = &inner {
+                d.push_str(&pixel_polygon(&place(inner))?);
+            }
+            writeln!(
+                self.out,
+                r#"<path d="{d}" fill-rule="evenodd"{}/>"#,
+                Paint("fill", batch.edge_color)
+            )?;
+        }
+        if let Some(inner) = inner {
+            writeln!(
+                self.out,
+                r#"<path d="{}"{}/>"#,
+                pixel_polygon(&place(&inner))?,
+                Paint("fill", color)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, text: &TextBatch) -> fmt::Result {
+        // The GPU text renderer places the top of the line at the position
+        // and scales the font so ascent to descent spans the text size.
+        let [x, top] = self.point(text.position);
+        writeln!(
+            self.out,
+            r#"<text x="{}" y="{}" font-size="{}"{}>{}</text>"#,
+            Num(x),
+            Num(top + self.font.ascent * text.size),
+            Num(self.font.em * text.size),
+            Paint("fill", text.color),
+            Escaped(&text.content)
+        )
+    }
+}
+
+/// A closed path through `points`, already in pixels.
+fn pixel_polygon(points: &[[f32; 2]]) -> Result<String, fmt::Error> {
+    let mut d = String::new();
+    for (i, &[x, y]) in points.iter().enumerate() {
+        write!(d, "{}{} {}", if i == 0 { "M" } else { "L" }, Num(x), Num(y))?;
+    }
+    write!(d, "Z")?;
+    Ok(d)
+}
+
+/// Twice the signed area of `points`; positive when counter-clockwise.
+fn area(points: &[[f32; 2]]) -> f32 {
+    (0..points.len())
+        .map(|i| {
+            let ([x0, y0], [x1, y1]) = (points[i], points[(i + 1) % points.len()]);
+            x0 * y1 - x1 * y0
+        })
+        .sum()
+}
+
+/// A number with at most two decimals and no trailing zeros.
+struct Num(f32);
+
+impl Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = format!("{:.2}", self.0);
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        f.write_str(if text == "-0" { "0" } else { text })
+    }
+}
+
+/// A colour attribute, with its opacity when not opaque.
+struct Paint(&'static str, Color);
+
+impl Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Paint(attribute, color) = *self;
+        let [r, g, b, a] = color.to_srgb8();
+        write!(f, r##" {attribute}="#{r:02x}{g:02x}{b:02x}""##)?;
+        if a < 255 {
+            write!(f, r#" {attribute}-opacity="{}""#, Num(a as f32 / 255.0))?;
+        }
+        Ok(())
+    }
+}
+
+/// Text with the characters XML reserves escaped.
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pltrs_core::{Axes, Line, LineStyle, Node, Scale, Scatter, Size, Text};
+
+    fn figure() -> Figure {
+        let mut axes = Axes::new(
+            Rect {
+                x: 0.1,
+                y: 0.1,
+                w: 0.8,
+                h: 0.8,
+            },
+            Scale::linear((0.0, 1.0), (0.0, 1.0)),
+            Scale::linear((0.0, 1.0), (0.0, 1.0)),
+        );
+        axes.add(Node::Line(Line {
+            xs: vec![0.0, 0.5, f64::NAN, 0.7, 1.0],
+            ys: vec![0.0, 1.0, 0.0, 0.2, 0.4],
+            color: Color::BLACK,
+            width: 2.0,
+            style: LineStyle::Dashed,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 2.0,
+            label: None,
+        }));
+        axes.add(Node::Scatter(Scatter {
+            xs: vec![0.25, 0.75],
+            ys: vec![0.5, 0.5],
+            color: Color::GREEN,
+            colors: None,
+            size: 10.0,
+            sizes: None,
+            marker: Marker::Star,
+            edge_color: Color::BLACK,
+            edge_width: 1.0,
+            label: None,
+        }));
+        axes.add(Node::Text(Text {
+            content: "a < b & c".to_string(),
+            x: 0.5,
+            y: 0.5,
+            color: Color::BLACK,
+            size: 16.0,
+        }));
+        let mut fig = Figure::new(Size {
+            width: 200,
+            height: 100,
+            dpi: 1.0,
+        });
+        fig.add_axes(axes);
+        fig
+    }
+
+    #[test]
+    fn figures_become_paths_markers_and_text() {
+        let svg = render_svg(&figure()).unwrap();
+        assert!(svg.contains(r#"width="200" height="100" viewBox="0 0 200 100""#));
+        assert!(
+            svg.contains(r#"<clipPath id="clip0"><rect x="20" y="10" width="160" height="80"/>"#)
+        );
+        // The missing sample splits the line into two runs.
+        let line = svg
+            .lines()
+            .find(|l| l.contains("stroke-dasharray"))
+            .unwrap();
+        assert_eq!(line.matches('M').count(), 2);
+        assert!(line.contains(r##"stroke="#000000" stroke-width="2" stroke-linejoin="miter""##));
+        assert!(line.contains(r#"stroke-miterlimit="2""#));
+        // Each star is an outline ring and an inset fill.
+        assert_eq!(svg.matches(r#"fill-rule="evenodd""#).count(), 2);
+        assert_eq!(svg.matches(r##"fill="#00ff00""##).count(), 2);
+        assert!(svg.contains(">a &lt; b &amp; c</text>"));
+        assert!(svg.contains("font-family=\"'Noto Sans', sans-serif\""));
+    }
+
+    #[test]
+    fn numbers_drop_trailing_zeros() {
+        assert_eq!(Num(1.0).to_string(), "1");
+        assert_eq!(Num(0.126).to_string(), "0.13");
+        assert_eq!(Num(-0.001).to_string(), "0");
+        assert_eq!(Num(12.5).to_string(), "12.5");
+    }
+}
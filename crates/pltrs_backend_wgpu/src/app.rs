@@ -1,10 +1,10 @@
 use crate::{backend::WgpuBackend, KEYBOARD_INTERRUPT_ERROR};
-use pltrs_core::{Color, Figure, PlotDefinition, PlotView, RenderBackend};
+use pltrs_core::{Color, Figure, PlotDefinition, PlotGrid, PlotView, RenderBackend, Size};
 use pyo3::{ffi, Python};
 use std::{env, sync::Arc, time::Instant};
 use winit::{
     application::ApplicationHandler,
-    dpi::{PhysicalPosition, PhysicalSize},
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event::{KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::ActiveEventLoop,
     keyboard::{KeyCode, PhysicalKey},
@@ -13,10 +13,11 @@ use winit::{
 
 enum AppContent {
     Static(Option<Figure>),
-    Interactive(PlotDefinition),
+    Interactive(Box<PlotGrid>),
 }
 
 struct DragState {
+    plot: usize,
     last_cursor: PhysicalPosition<f64>,
 }
 
@@ -25,16 +26,20 @@ pub struct App {
     pub clear: Color,
     pub init_error: Option<String>,
     content: AppContent,
-    interactive_view: Option<PlotView>,
+    interactive_views: Vec<PlotView>,
     cursor_position: Option<PhysicalPosition<f64>>,
     middle_drag: Option<DragState>,
     window_size: PhysicalSize<u32>,
+    /// The content's own dpi, before the window's scale factor.
+    dpi: f32,
+    /// Samples per pixel asked of the backend.
+    samples: u32,
     benchmark_start: Option<Instant>,
     benchmark_oneshot: bool,
 }
 
 impl App {
-    pub fn new(figure: Option<Figure>) -> Self {
+    pub fn new(figure: Option<Figure>, samples: u32) -> Self {
         let clear = figure
             .as_ref()
             .map(|fig| fig.clear_color)
@@ -43,80 +48,99 @@ impl App {
             .as_ref()
             .map(|fig| PhysicalSize::new(fig.size.width, fig.size.height))
             .unwrap_or_else(|| PhysicalSize::new(800, 600));
+        let dpi = figure.as_ref().map_or(1.0, |fig| fig.size.dpi);
 
         Self {
             backend: None,
             clear,
             init_error: None,
             content: AppContent::Static(figure),
-            interactive_view: None,
+            interactive_views: Vec::new(),
             cursor_position: None,
             middle_drag: None,
             window_size,
+            dpi,
+            samples,
             benchmark_start: benchmark_enabled().then(Instant::now),
             benchmark_oneshot: benchmark_enabled(),
         }
     }
 
-    pub fn new_interactive(plot: PlotDefinition) -> Self {
-        let clear = plot.clear_color;
-        let window_size = PhysicalSize::new(plot.size.width, plot.size.height);
-        let interactive_view = Some(plot.initial_view());
+    pub fn new_interactive(plot: PlotDefinition, samples: u32) -> Self {
+        Self::new_grid(PlotGrid::single(plot), samples)
+    }
+
+    pub fn new_grid(grid: PlotGrid, samples: u32) -> Self {
+        let clear = grid.clear_color;
+        let window_size = PhysicalSize::new(grid.size.width, grid.size.height);
+        let interactive_views = grid.initial_views();
+        let dpi = grid.size.dpi;
 
         Self {
             backend: None,
             clear,
             init_error: None,
-            content: AppContent::Interactive(plot),
-            interactive_view,
+            content: AppContent::Interactive(Box::new(grid)),
+            interactive_views,
             cursor_position: None,
             middle_drag: None,
             window_size,
+            dpi,
+            samples,
             benchmark_start: benchmark_enabled().then(Instant::now),
             benchmark_oneshot: benchmark_enabled(),
         }
     }
 
+    /// The window is sized in logical pixels, so the figure keeps its size
+    /// on screen however dense the display is.
     fn window_attributes(&self) -> WindowAttributes {
         let mut attrs = Window::default_attributes().with_title("pltrs plot");
         match &self.content {
             AppContent::Static(Some(fig)) => {
-                attrs = attrs.with_inner_size(PhysicalSize::new(fig.size.width, fig.size.height));
+                attrs = attrs.with_inner_size(self.logical_size(fig.size));
             }
-            AppContent::Interactive(plot) => {
-                attrs = attrs.with_inner_size(PhysicalSize::new(plot.size.width, plot.size.height));
+            AppContent::Interactive(grid) => {
+                attrs = attrs.with_inner_size(self.logical_size(grid.size));
             }
             AppContent::Static(None) => {}
         }
         attrs
     }
 
-    fn current_figure(&self) -> Option<Figure> {
-        match (&self.content, self.interactive_view) {
-            (AppContent::Static(fig), _) => fig.clone(),
-            (AppContent::Interactive(plot), Some(view)) => Some(plot.build_figure(&view)),
-            (AppContent::Interactive(_), None) => None,
+    fn logical_size(&self, size: Size) -> LogicalSize<f64> {
+        let dpi = self.dpi as f64;
+        LogicalSize::new(size.width as f64 * dpi, size.height as f64 * dpi)
+    }
+
+    /// Draw the content at its own dpi times the window's `scale_factor`.
+    fn set_scale_factor(&mut self, scale_factor: f64) {
+        let dpi = self.dpi * scale_factor as f32;
+        match &mut self.content {
+            AppContent::Static(Some(fig)) => fig.size.dpi = dpi,
+            AppContent::Static(None) => {}
+            AppContent::Interactive(grid) => grid.set_dpi(dpi),
         }
     }
 
-    fn interactive_plot(&self) -> Option<&PlotDefinition> {
+    fn interactive_grid(&self) -> Option<&PlotGrid> {
         match &self.content {
-            AppContent::Interactive(plot) => Some(plot),
+            AppContent::Interactive(grid) => Some(grid),
             AppContent::Static(_) => None,
         }
     }
 
-    fn handle_zoom(&mut self, delta: MouseScrollDelta) -> bool {
-        let Some(plot) = self.interactive_plot() else {
-            return false;
-        };
-        let Some(cursor) = self.cursor_position else {
-            return false;
-        };
-        let Some(plot_pos) = plot.plot_normalized_position(
+    /// The plot under the cursor and the cursor position within it.
+    fn plot_under_cursor(&self) -> Option<(usize, (f64, f64))> {
+        let cursor = self.cursor_position?;
+        self.interactive_grid()?.plot_at(
             (cursor.x, cursor.y),
             (self.window_size.width, self.window_size.height),
-        ) else {
+        )
+    }
+
+    fn handle_zoom(&mut self, delta: MouseScrollDelta) -> bool {
+        let Some((plot, plot_pos)) = self.plot_under_cursor() else {
             return false;
         };
 
@@ -128,36 +152,38 @@ impl App {
             return false;
         }
 
-        if let Some(view) = &mut self.interactive_view {
-            view.zoom_at(plot_pos, 0.9_f64.powf(amount));
-            return true;
-        }
-
-        false
+        let AppContent::Interactive(grid) = &self.content else {
+            return false;
+        };
+        grid.zoom_at(
+            &mut self.interactive_views,
+            plot,
+            plot_pos,
+            0.9_f64.powf(amount),
+        );
+        true
     }
 
     fn start_drag(&mut self) {
-        let Some(plot) = self.interactive_plot() else {
-            return;
-        };
-        let Some(cursor) = self.cursor_position else {
+        let (Some(cursor), Some((plot, _))) = (self.cursor_position, self.plot_under_cursor())
+        else {
             return;
         };
-        if plot
-            .plot_normalized_position(
-                (cursor.x, cursor.y),
-                (self.window_size.width, self.window_size.height),
-            )
-            .is_some()
-        {
-            self.middle_drag = Some(DragState {
-                last_cursor: cursor,
-            });
-        }
+        self.middle_drag = Some(DragState {
+            plot,
+            last_cursor: cursor,
+        });
     }
 
     fn update_drag(&mut self, position: PhysicalPosition<f64>) -> bool {
-        let Some(plot_rect) = self.interactive_plot().map(|plot| plot.plot_rect) else {
+        let Some(plot) = self.middle_drag.as_ref().map(|drag| drag.plot) else {
+            return false;
+        };
+        let Some(plot_rect) = self
+            .interactive_grid()
+            .and_then(|grid| grid.plots.get(plot))
+            .map(|plot| plot.plot_rect)
+        else {
             return false;
         };
         let Some(drag) = &mut self.middle_drag else {
@@ -171,19 +197,37 @@ impl App {
         let dy = -(position.y - drag.last_cursor.y) / self.window_size.height as f64;
         drag.last_cursor = position;
 
-        if let Some(view) = &mut self.interactive_view {
-            view.pan_by((dx / plot_rect.w as f64, dy / plot_rect.h as f64));
-            return true;
-        }
+        let AppContent::Interactive(grid) = &self.content else {
+            return false;
+        };
+        grid.pan_by(
+            &mut self.interactive_views,
+            plot,
+            (dx / plot_rect.w as f64, dy / plot_rect.h as f64),
+        );
+        true
+    }
 
-        false
+    /// Show or hide the series whose legend entry was clicked.
+    fn toggle_legend_entry(&mut self) -> bool {
+        let Some(cursor) = self.cursor_position else {
+            return false;
+        };
+        let AppContent::Interactive(grid) = &self.content else {
+            return false;
+        };
+        grid.toggle_legend_entry(
+            &mut self.interactive_views,
+            (cursor.x, cursor.y),
+            (self.window_size.width, self.window_size.height),
+        )
     }
 
     fn reset_view(&mut self) -> bool {
-        let Some(plot) = self.interactive_plot() else {
+        let Some(grid) = self.interactive_grid() else {
             return false;
         };
-        self.interactive_view = Some(plot.initial_view());
+        self.interactive_views = grid.initial_views();
         self.middle_drag = None;
         true
     }
@@ -200,7 +244,9 @@ impl App {
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         let window = Arc::new(event_loop.create_window(self.window_attributes()).unwrap());
-        match pollster::block_on(WgpuBackend::new(window.clone())) {
+        self.window_size = window.inner_size();
+        self.set_scale_factor(window.scale_factor());
+        match pollster::block_on(WgpuBackend::new(window.clone(), self.samples)) {
             Ok(backend) => {
                 self.backend = Some(backend);
                 window.request_redraw();
@@ -212,17 +258,23 @@ impl ApplicationHandler for App {
         }
     }
 
+    #[allow(clippy::collapsible_if, clippy::collapsible_match)]
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::RedrawRequested => {
-                let fig = self.current_figure();
                 let Some(state) = &mut self.backend else {
                     return;
                 };
                 state.begin_frame(self.clear);
-                if let Some(fig) = fig {
-                    state.draw_scene(&fig);
+                match &self.content {
+                    AppContent::Static(Some(fig)) => state.draw_scene(fig),
+                    AppContent::Static(None) => {}
+                    // Series stay uploaded; a new view only moves them.
+                    AppContent::Interactive(grid) => {
+                        let layers = grid.build_layers(&self.interactive_views);
+                        state.draw_layers(grid, &layers);
+                    }
                 }
                 state.end_frame();
                 if self.benchmark_oneshot {
@@ -242,6 +294,12 @@ impl ApplicationHandler for App {
                 self.window_size = size;
                 state.resize(size.width, size.height);
             }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.set_scale_factor(scale_factor);
+                if let Some(state) = &self.backend {
+                    state.request_redraw();
+                }
+            }
             WindowEvent::CursorMoved { position, .. } => {
                 self.cursor_position = Some(position);
                 if self.update_drag(position) {
@@ -257,6 +315,15 @@ impl ApplicationHandler for App {
                     }
                 }
             }
+            WindowEvent::MouseInput {
+                state: button_state,
+                button: MouseButton::Left,
+                ..
+            } if button_state.is_pressed() && self.toggle_legend_entry() => {
+                if let Some(state) = &self.backend {
+                    state.request_redraw();
+                }
+            }
             WindowEvent::MouseInput {
                 state: button_state,
                 button: MouseButton::Middle,
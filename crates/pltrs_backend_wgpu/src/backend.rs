@@ -1,39 +1,71 @@
-use crate::vertex::{LineVertex, ScatterInstance, ScatterVertex};
+use crate::frame::Frame;
+use crate::retained::RetainedSeries;
+use crate::vertex::{LineVertex, ScatterInstance, ScatterVertex, StrokePoint};
 use anyhow::{anyhow, Context};
-use bytemuck::{Pod, Zeroable};
-use pltrs_core::{Color, Figure, RenderBackend};
+use pltrs_core::{
+    Color, Figure, FigureLayers, Image, Marker, MarkerBatch, PlotGrid, RenderBackend,
+};
 use pltrs_text::TextRenderer;
 use std::{
-    fs::File,
-    io::BufWriter,
-    path::Path,
+    collections::HashMap,
     sync::{mpsc, Arc},
 };
 use wgpu::util::DeviceExt;
 use winit::{event_loop::ActiveEventLoop, keyboard::KeyCode, window::Window};
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct LineUniforms {
-    color: [f32; 4],
-    _padding: [f32; 4],
+pub(crate) struct RenderResources {
+    pub(crate) device: wgpu::Device,
+    pub(crate) queue: wgpu::Queue,
+    pub(crate) size: winit::dpi::PhysicalSize<u32>,
+    pub(crate) line_pipeline: wgpu::RenderPipeline,
+    pub(crate) line_bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) scatter_pipeline: wgpu::RenderPipeline,
+    pub(crate) scatter_bind_group_layout: wgpu::BindGroupLayout,
+    /// The unit quad every marker is drawn on.
+    pub(crate) marker_quad: wgpu::Buffer,
+    pub(crate) stroke_pipeline: wgpu::RenderPipeline,
+    pub(crate) stroke_bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) text_renderer: TextRenderer,
+    pub(crate) target_format: wgpu::TextureFormat,
+    /// Samples per pixel shapes are drawn with.
+    pub(crate) samples: u32,
+    /// Where shapes are drawn when `samples` is above 1, resolved into the
+    /// target at the end of each pass.
+    pub(crate) msaa: Option<wgpu::TextureView>,
 }
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct ScatterGlobalUniforms {
-    viewport_size: [f32; 2],
-}
+impl RenderResources {
+    /// The colour attachment shapes are drawn to for `view`: the
+    /// multisampled texture resolving into `view`, or `view` itself.
+    pub(crate) fn color_attachment<'a>(
+        &'a self,
+        view: &'a wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        let ops = wgpu::Operations {
+            load,
+            store: wgpu::StoreOp::Store,
+        };
+        match &self.msaa {
+            Some(msaa) => wgpu::RenderPassColorAttachment {
+                view: msaa,
+                resolve_target: Some(view),
+                ops,
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops,
+            },
+        }
+    }
 
-struct RenderResources {
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    size: winit::dpi::PhysicalSize<u32>,
-    line_pipeline: wgpu::RenderPipeline,
-    line_bind_group_layout: wgpu::BindGroupLayout,
-    scatter_pipeline: wgpu::RenderPipeline,
-    scatter_bind_group_layout: wgpu::BindGroupLayout,
-    text_renderer: TextRenderer,
+    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        self.size = size;
+        if size.width > 0 && size.height > 0 {
+            self.msaa = create_msaa_view(&self.device, size, self.target_format, self.samples);
+        }
+    }
 }
 
 pub struct WgpuBackend {
@@ -43,6 +75,9 @@ pub struct WgpuBackend {
     config: wgpu::SurfaceConfiguration,
     // is_surface_configured: bool, // unused for now
     resources: RenderResources,
+    /// Series uploaded by [`WgpuBackend::draw_layers`], keyed by plot and
+    /// series index.
+    retained: HashMap<(usize, usize), RetainedSeries>,
 
     // Frame state
     current_texture: Option<wgpu::SurfaceTexture>,
@@ -52,7 +87,7 @@ pub struct WgpuBackend {
 
 impl WgpuBackend {
     /// Contains the core WgpuBackend logic, including initialization, resource management, and rendering pipeline setup.
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
+    pub async fn new(window: Arc<Window>, samples: u32) -> anyhow::Result<Self> {
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
             backends: wgpu::Backends::PRIMARY,
             ..Default::default()
@@ -70,12 +105,13 @@ impl WgpuBackend {
             .await
             .context("failed to find a suitable GPU adapter for the current window surface")?;
         let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default(), None)
+            .request_device(&device_descriptor(&adapter), None)
             .await
             .context("failed to create a logical device for the selected GPU adapter")?;
         let surface_caps = surface.get_capabilities(&adapter);
         let (surface_format, config) = surface_config_for_caps(&surface_caps, size)?;
-        let resources = build_render_resources(device, queue, size, surface_format)?;
+        let samples = supported_samples(&adapter, surface_format, samples);
+        let resources = build_render_resources(device, queue, size, surface_format, samples)?;
 
         let backend = Self {
             window,
@@ -83,6 +119,7 @@ impl WgpuBackend {
             surface_format,
             config,
             resources,
+            retained: HashMap::new(),
             current_texture: None,
             current_view: None,
             current_encoder: None,
@@ -106,6 +143,7 @@ impl WgpuBackend {
             .configure(&self.resources.device, &surface_config);
     }
 
+    #[allow(clippy::single_match)]
     pub fn handle_key(&self, event_loop: &ActiveEventLoop, code: KeyCode, is_pressed: bool) {
         match (code, is_pressed) {
             (KeyCode::Escape, true) => event_loop.exit(),
@@ -116,6 +154,24 @@ impl WgpuBackend {
     pub fn request_redraw(&self) {
         self.window.request_redraw();
     }
+
+    /// Draw `layers`, built from `grid`. Each series is uploaded the first
+    /// time it shows up and only placed by its layer's transforms after
+    /// that, so `grid` must not change while this backend draws it.
+    pub fn draw_layers(&mut self, grid: &PlotGrid, layers: &FigureLayers) {
+        let (view, encoder) = match (&self.current_view, &mut self.current_encoder) {
+            (Some(v), Some(e)) => (v, e),
+            _ => return,
+        };
+        draw_layers(
+            &mut self.resources,
+            &mut self.retained,
+            encoder,
+            view,
+            grid,
+            layers,
+        );
+    }
 }
 
 impl RenderBackend for WgpuBackend {
@@ -143,7 +199,7 @@ impl RenderBackend for WgpuBackend {
             .resources
             .device
             .create_command_encoder(&Default::default());
-        clear_view(&mut encoder, &texture_view, clear);
+        clear_view(&self.resources, &mut encoder, &texture_view, clear);
 
         self.current_texture = Some(surface_texture);
         self.current_view = Some(texture_view);
@@ -183,29 +239,31 @@ impl RenderBackend for WgpuBackend {
                 .resize(width, height, &self.resources.queue);
             // self.is_surface_configured = true;
         }
-        self.resources.size = winit::dpi::PhysicalSize { width, height };
+        self.resources
+            .resize(winit::dpi::PhysicalSize { width, height });
         self.configure_surface();
     }
 }
 
-pub async fn save_figure_png(fig: &Figure, path: &Path) -> anyhow::Result<()> {
-    if !path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
-    {
-        return Err(anyhow!("only .png output is currently supported"));
-    }
-
+pub async fn render_image(fig: &Figure, samples: u32) -> anyhow::Result<Image> {
     if fig.size.width == 0 || fig.size.height == 0 {
         return Err(anyhow!(
             "figure size must be non-zero for offscreen rendering"
         ));
     }
 
-    let size = winit::dpi::PhysicalSize::new(fig.size.width, fig.size.height);
+    if !(fig.size.dpi.is_finite() && fig.size.dpi > 0.0) {
+        return Err(anyhow!("figure dpi must be positive, got {}", fig.size.dpi));
+    }
+
+    if !matches!(samples, 1 | 4 | 8) {
+        return Err(anyhow!("samples must be 1, 4 or 8, got {samples}"));
+    }
+
+    let (width, height) = fig.size.pixels();
+    let size = winit::dpi::PhysicalSize::new(width, height);
     let format = wgpu::TextureFormat::Rgba8UnormSrgb;
-    let mut resources = create_headless_resources(size, format).await?;
+    let mut resources = create_headless_resources(size, format, samples).await?;
 
     let texture = resources.device.create_texture(&wgpu::TextureDescriptor {
         label: Some("Offscreen Render Target"),
@@ -228,7 +286,7 @@ pub async fn save_figure_png(fig: &Figure, path: &Path) -> anyhow::Result<()> {
             label: Some("Offscreen Render Encoder"),
         });
 
-    clear_view(&mut encoder, &view, fig.clear_color);
+    clear_view(&resources, &mut encoder, &view, fig.clear_color);
     draw_figure(&mut resources, &mut encoder, &view, fig);
 
     let unpadded_bytes_per_row = size.width * 4;
@@ -286,25 +344,17 @@ pub async fn save_figure_png(fig: &Figure, path: &Path) -> anyhow::Result<()> {
     drop(mapped);
     output_buffer.unmap();
 
-    let file = File::create(path)
-        .with_context(|| format!("failed to create output image at {}", path.display()))?;
-    let writer = BufWriter::new(file);
-    let mut encoder = png::Encoder::new(writer, size.width, size.height);
-    encoder.set_color(png::ColorType::Rgba);
-    encoder.set_depth(png::BitDepth::Eight);
-    let mut png_writer = encoder
-        .write_header()
-        .context("failed to write PNG header")?;
-    png_writer
-        .write_image_data(&pixels)
-        .context("failed to encode PNG image data")?;
-
-    Ok(())
+    Ok(Image {
+        width: size.width,
+        height: size.height,
+        pixels,
+    })
 }
 
 async fn create_headless_resources(
     size: winit::dpi::PhysicalSize<u32>,
     format: wgpu::TextureFormat,
+    samples: u32,
 ) -> anyhow::Result<RenderResources> {
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
         backends: wgpu::Backends::PRIMARY,
@@ -316,13 +366,77 @@ async fn create_headless_resources(
             ..Default::default()
         })
         .await
-        .context("failed to find a suitable GPU adapter for offscreen rendering")?;
+        .ok_or(NoAdapter)?;
     let (device, queue) = adapter
-        .request_device(&wgpu::DeviceDescriptor::default(), None)
+        .request_device(&device_descriptor(&adapter), None)
         .await
         .context("failed to create a logical device for offscreen rendering")?;
 
-    build_render_resources(device, queue, size, format)
+    let samples = supported_samples(&adapter, format, samples);
+    build_render_resources(device, queue, size, format, samples)
+}
+
+/// No GPU adapter was found for offscreen rendering.
+#[derive(Debug)]
+pub(crate) struct NoAdapter;
+
+impl std::fmt::Display for NoAdapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("failed to find a suitable GPU adapter for offscreen rendering")
+    }
+}
+
+impl std::error::Error for NoAdapter {}
+
+/// Asks for the adapter's own multisampling limits where it reports them,
+/// so 8 samples can be used where the hardware allows.
+fn device_descriptor(adapter: &wgpu::Adapter) -> wgpu::DeviceDescriptor<'static> {
+    wgpu::DeviceDescriptor {
+        required_features: adapter.features()
+            & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+        ..Default::default()
+    }
+}
+
+/// The most samples per pixel, up to `requested`, that `adapter` can draw
+/// `format` with.
+fn supported_samples(adapter: &wgpu::Adapter, format: wgpu::TextureFormat, requested: u32) -> u32 {
+    let features = adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+    let flags = if features.is_empty() {
+        format.guaranteed_format_features(features).flags
+    } else {
+        adapter.get_texture_format_features(format).flags
+    };
+    [8, 4, 2]
+        .into_iter()
+        .find(|&count| count <= requested && flags.sample_count_supported(count))
+        .unwrap_or(1)
+}
+
+fn create_msaa_view(
+    device: &wgpu::Device,
+    size: winit::dpi::PhysicalSize<u32>,
+    format: wgpu::TextureFormat,
+    samples: u32,
+) -> Option<wgpu::TextureView> {
+    if samples <= 1 {
+        return None;
+    }
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Multisampled Render Target"),
+        size: wgpu::Extent3d {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: samples,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
 }
 
 fn build_render_resources(
@@ -330,18 +444,23 @@ fn build_render_resources(
     queue: wgpu::Queue,
     size: winit::dpi::PhysicalSize<u32>,
     target_format: wgpu::TextureFormat,
+    samples: u32,
 ) -> anyhow::Result<RenderResources> {
-    let line_shader = device.create_shader_module(wgpu::include_wgsl!("line_shader.wgsl"));
+    let line_shader = create_view_shader(
+        &device,
+        "line_shader.wgsl",
+        include_str!("line_shader.wgsl"),
+    );
 
     let line_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Line Bind Group Layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
+                    has_dynamic_offset: true,
                     min_binding_size: None,
                 },
                 count: None,
@@ -368,7 +487,7 @@ fn build_render_resources(
             entry_point: "fs_main",
             targets: &[Some(wgpu::ColorTargetState {
                 format: target_format,
-                blend: Some(wgpu::BlendState::REPLACE),
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                 write_mask: wgpu::ColorWrites::ALL,
             })],
             compilation_options: wgpu::PipelineCompilationOptions::default(),
@@ -384,14 +503,18 @@ fn build_render_resources(
         },
         depth_stencil: None,
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: samples,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
         multiview: None,
     });
 
-    let scatter_shader = device.create_shader_module(wgpu::include_wgsl!("scatter_shader.wgsl"));
+    let scatter_shader = create_view_shader(
+        &device,
+        "scatter_shader.wgsl",
+        include_str!("scatter_shader.wgsl"),
+    );
 
     let scatter_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -401,7 +524,7 @@ fn build_render_resources(
                 visibility: wgpu::ShaderStages::VERTEX,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
+                    has_dynamic_offset: true,
                     min_binding_size: None,
                 },
                 count: None,
@@ -444,7 +567,91 @@ fn build_render_resources(
         },
         depth_stencil: None,
         multisample: wgpu::MultisampleState {
-            count: 1,
+            count: samples,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    });
+
+    let quad = [
+        ScatterVertex {
+            position: [-0.5, 0.5],
+        },
+        ScatterVertex {
+            position: [-0.5, -0.5],
+        },
+        ScatterVertex {
+            position: [0.5, 0.5],
+        },
+        ScatterVertex {
+            position: [0.5, -0.5],
+        },
+    ];
+    let marker_quad = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Scatter Quad Buffer"),
+        contents: bytemuck::cast_slice(&quad),
+        usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    let stroke_shader = create_view_shader(
+        &device,
+        "stroke_shader.wgsl",
+        include_str!("stroke_shader.wgsl"),
+    );
+
+    let stroke_bind_group_layout =
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Stroke Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+    let stroke_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Stroke Pipeline Layout"),
+        bind_group_layouts: &[&stroke_bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let stroke_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Stroke Render Pipeline"),
+        layout: Some(&stroke_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &stroke_shader,
+            entry_point: "vs_main",
+            buffers: &StrokePoint::descs(),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &stroke_shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: target_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: samples,
             mask: !0,
             alpha_to_coverage_enabled: false,
         },
@@ -452,6 +659,7 @@ fn build_render_resources(
     });
 
     let text_renderer = TextRenderer::new(&device, size.width, size.height, target_format)?;
+    let msaa = create_msaa_view(&device, size, target_format, samples);
 
     Ok(RenderResources {
         device,
@@ -461,7 +669,24 @@ fn build_render_resources(
         line_bind_group_layout,
         scatter_pipeline,
         scatter_bind_group_layout,
+        marker_quad,
+        stroke_pipeline,
+        stroke_bind_group_layout,
         text_renderer,
+        target_format,
+        samples,
+        msaa,
+    })
+}
+
+/// Compile a shader that places positions with the helpers in
+/// view_transform.wgsl.
+fn create_view_shader(device: &wgpu::Device, label: &str, source: &str) -> wgpu::ShaderModule {
+    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Wgsl(
+            format!("{}{source}", include_str!("view_transform.wgsl")).into(),
+        ),
     })
 }
 
@@ -495,22 +720,23 @@ fn surface_config_for_caps(
     ))
 }
 
-fn clear_view(encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, clear: Color) {
+fn clear_view(
+    resources: &RenderResources,
+    encoder: &mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    clear: Color,
+) {
     let _rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
         label: Some("Clear Pass"),
-        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+        color_attachments: &[Some(resources.color_attachment(
             view,
-            resolve_target: None,
-            ops: wgpu::Operations {
-                load: wgpu::LoadOp::Clear(wgpu::Color {
-                    r: clear.r as f64,
-                    g: clear.g as f64,
-                    b: clear.b as f64,
-                    a: clear.a as f64,
-                }),
-                store: wgpu::StoreOp::Store,
-            },
-        })],
+            wgpu::LoadOp::Clear(wgpu::Color {
+                r: clear.r as f64,
+                g: clear.g as f64,
+                b: clear.b as f64,
+                a: clear.a as f64,
+            }),
+        ))],
         depth_stencil_attachment: None,
         timestamp_writes: None,
         occlusion_query_set: None,
@@ -523,283 +749,110 @@ fn draw_figure(
     view: &wgpu::TextureView,
     fig: &Figure,
 ) {
-    let batches = pltrs_core::build_batches(fig);
-    let plot_scissor = fig
-        .axes
-        .first()
-        .and_then(|axes| scissor_rect_for_axes(axes.rect, resources.size));
-    resources.text_renderer.queue(
-        &resources.device,
-        &resources.queue,
-        resources.size.width,
-        resources.size.height,
-        &batches.texts,
-    );
-
-    for line_batch in &batches.lines {
-        if line_batch.vertices.len() < 2 {
-            continue;
-        }
+    draw_figure_with(resources, encoder, view, fig, &[]);
+}
 
-        let vertices: Vec<LineVertex> = line_batch
-            .vertices
-            .windows(2)
-            .flat_map(|segment| {
-                let [p0, p1] = [segment[0], segment[1]];
-                let dx = (p1[0] - p0[0]) * resources.size.width as f32;
-                let dy = (p1[1] - p0[1]) * resources.size.height as f32;
-                let len = (dx * dx + dy * dy).sqrt();
-
-                if len <= f32::EPSILON {
-                    return Vec::new();
-                }
-
-                let half_width = line_batch.width.max(1.0) * 0.5;
-                let offset_x = (-dy / len) * half_width / resources.size.width as f32;
-                let offset_y = (dx / len) * half_width / resources.size.height as f32;
-
-                vec![
-                    LineVertex {
-                        position: [p0[0] + offset_x, p0[1] + offset_y],
-                    },
-                    LineVertex {
-                        position: [p0[0] - offset_x, p0[1] - offset_y],
-                    },
-                    LineVertex {
-                        position: [p1[0] + offset_x, p1[1] + offset_y],
-                    },
-                    LineVertex {
-                        position: [p1[0] + offset_x, p1[1] + offset_y],
-                    },
-                    LineVertex {
-                        position: [p0[0] - offset_x, p0[1] - offset_y],
-                    },
-                    LineVertex {
-                        position: [p1[0] - offset_x, p1[1] - offset_y],
-                    },
-                ]
-            })
+/// Draw `fig` one axes at a time, with each `retained` series drawn among
+/// the scene nodes of the axes it is paired with.
+fn draw_figure_with(
+    resources: &mut RenderResources,
+    encoder: &mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    fig: &Figure,
+    retained: &[(usize, &RetainedSeries)],
+) {
+    let mut frame = Frame::new(resources, fig.size.dpi);
+    for (index, axes) in fig.axes.iter().enumerate() {
+        let kept: Vec<&RetainedSeries> = retained
+            .iter()
+            .filter(|(axes, _)| *axes == index)
+            .map(|(_, series)| *series)
             .collect();
-
-        if vertices.is_empty() {
-            continue;
-        }
-
-        let vertex_buffer =
-            resources
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Line Vertex Buffer"),
-                    contents: bytemuck::cast_slice(&vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-
-        draw_solid_triangles(
-            resources,
-            encoder,
-            view,
-            &vertex_buffer,
-            vertices.len() as u32,
-            line_batch.color,
-            plot_scissor,
-            "Line Draw Pass",
-            "Line Uniform Buffer",
-            "Line Bind Group",
-        );
+        frame.add_axes(axes, &kept);
     }
+    frame.draw(resources, encoder, view);
+}
 
-    for solid_batch in &batches.solids {
-        if solid_batch.vertices.is_empty() {
+fn draw_layers(
+    resources: &mut RenderResources,
+    retained: &mut HashMap<(usize, usize), RetainedSeries>,
+    encoder: &mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    grid: &PlotGrid,
+    layers: &FigureLayers,
+) {
+    for layer in &layers.series {
+        let Some(axes) = layers.figure.axes.get(layer.axes) else {
             continue;
-        }
-
-        let vertex_buffer =
-            resources
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Solid Vertex Buffer"),
-                    contents: bytemuck::cast_slice(&solid_batch.vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-
-        draw_solid_triangles(
-            resources,
-            encoder,
-            view,
-            &vertex_buffer,
-            solid_batch.vertices.len() as u32,
-            solid_batch.color,
-            plot_scissor,
-            "Solid Draw Pass",
-            "Solid Uniform Buffer",
-            "Solid Bind Group",
-        );
-    }
-
-    if !batches.markers.is_empty() {
-        let uniforms = ScatterGlobalUniforms {
-            viewport_size: [resources.size.width as f32, resources.size.height as f32],
         };
-        let uniform_buffer =
-            resources
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Scatter Global Uniforms"),
-                    contents: bytemuck::bytes_of(&uniforms),
-                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-                });
-
-        let bind_group = resources
-            .device
-            .create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Scatter Bind Group"),
-                layout: &resources.scatter_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                }],
-            });
-
-        let strip_vertices = [
-            ScatterVertex {
-                position: [-0.5, 0.5],
-            },
-            ScatterVertex {
-                position: [-0.5, -0.5],
-            },
-            ScatterVertex {
-                position: [0.5, 0.5],
-            },
-            ScatterVertex {
-                position: [0.5, -0.5],
-            },
-        ];
-
-        let vertex_buffer =
-            resources
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Scatter Quad Buffer"),
-                    contents: bytemuck::cast_slice(&strip_vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-
-        for batch in &batches.markers {
-            let instances: Vec<ScatterInstance> = batch
-                .positions
-                .iter()
-                .map(|p| ScatterInstance {
-                    position: *p,
-                    color: [batch.color.r, batch.color.g, batch.color.b, batch.color.a],
-                    size: batch.size,
-                    marker_type: match batch.marker {
-                        pltrs_core::Marker::Circle => 0,
-                        pltrs_core::Marker::Square => 1,
-                    },
-                })
-                .collect();
-
-            let instance_buffer =
-                resources
-                    .device
-                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: Some("Scatter Instance Buffer"),
-                        contents: bytemuck::cast_slice(&instances),
-                        usage: wgpu::BufferUsages::VERTEX,
-                    });
-
-            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Scatter Draw Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load,
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            rpass.set_pipeline(&resources.scatter_pipeline);
-            if let Some((x, y, width, height)) = plot_scissor {
-                rpass.set_scissor_rect(x, y, width, height);
-            }
-            rpass.set_bind_group(0, &bind_group, &[]);
-            rpass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            rpass.set_vertex_buffer(1, instance_buffer.slice(..));
-            rpass.draw(0..4, 0..instances.len() as u32);
+        let kinds = (axes.x.kind(), axes.y.kind());
+        let key = (layer.plot, layer.series);
+        if retained
+            .get(&key)
+            .is_some_and(|series| series.kinds == kinds)
+        {
+            continue;
         }
+        let series = grid
+            .plots
+            .get(layer.plot)
+            .and_then(|plot| plot.all_series().nth(layer.series))
+            .map(|series| RetainedSeries::upload(&resources.device, series, kinds))
+            .unwrap_or_default();
+        retained.insert(key, series);
     }
 
-    resources
-        .text_renderer
-        .draw(encoder, view, !batches.texts.is_empty());
+    let placed: Vec<(usize, &RetainedSeries)> = layers
+        .series
+        .iter()
+        .filter_map(|layer| {
+            let series = retained.get(&(layer.plot, layer.series))?;
+            Some((layer.axes, series))
+        })
+        .collect();
+    draw_figure_with(resources, encoder, view, &layers.figure, &placed);
 }
 
-fn draw_solid_triangles(
-    resources: &RenderResources,
-    encoder: &mut wgpu::CommandEncoder,
-    view: &wgpu::TextureView,
-    vertex_buffer: &wgpu::Buffer,
-    vertex_count: u32,
-    color: Color,
-    scissor_rect: Option<(u32, u32, u32, u32)>,
-    pass_label: &str,
-    uniform_label: &str,
-    bind_group_label: &str,
-) {
-    let uniforms = LineUniforms {
-        color: [color.r, color.g, color.b, color.a],
-        _padding: [0.0; 4],
-    };
-
-    let uniform_buffer = resources
-        .device
-        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some(uniform_label),
-            contents: bytemuck::bytes_of(&uniforms),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
-    let bind_group = resources
-        .device
-        .create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some(bind_group_label),
-            layout: &resources.line_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-        });
-
-    let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-        label: Some(pass_label),
-        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-            view,
-            resolve_target: None,
-            ops: wgpu::Operations {
-                load: wgpu::LoadOp::Load,
-                store: wgpu::StoreOp::Store,
-            },
-        })],
-        depth_stencil_attachment: None,
-        timestamp_writes: None,
-        occlusion_query_set: None,
-    });
-
-    rpass.set_pipeline(&resources.line_pipeline);
-    if let Some((x, y, width, height)) = scissor_rect {
-        rpass.set_scissor_rect(x, y, width, height);
-    }
-    rpass.set_bind_group(0, &bind_group, &[]);
-    rpass.set_vertex_buffer(0, vertex_buffer.slice(..));
-    rpass.draw(0..vertex_count, 0..1);
+/// Per-instance data for every point of `batch`, with `split` giving the
+/// high and low part of each position.
+pub(crate) fn marker_instances<P>(
+    batch: &MarkerBatch<P>,
+    split: impl Fn(&P) -> ([f32; 2], [f32; 2]),
+) -> Vec<ScatterInstance> {
+    batch
+        .positions
+        .iter()
+        .enumerate()
+        .map(|(idx, p)| {
+            let color = batch
+                .colors
+                .as_ref()
+                .and_then(|colors| colors.get(idx))
+                .unwrap_or(&batch.color);
+            let size = batch
+                .sizes
+                .as_ref()
+                .and_then(|sizes| sizes.get(idx))
+                .unwrap_or(&batch.size);
+            let (position, position_low) = split(p);
+            ScatterInstance {
+                position,
+                color: [color.r, color.g, color.b, color.a],
+                size: *size,
+                marker_type: marker_type(batch.marker),
+                edge_color: [
+                    batch.edge_color.r,
+                    batch.edge_color.g,
+                    batch.edge_color.b,
+                    batch.edge_color.a,
+                ],
+                edge_width: batch.edge_width,
+                position_low,
+            }
+        })
+        .collect()
 }
-
-fn scissor_rect_for_axes(
+pub(crate) fn scissor_rect_for_axes(
     rect: pltrs_core::Rect,
     size: winit::dpi::PhysicalSize<u32>,
 ) -> Option<(u32, u32, u32, u32)> {
@@ -825,3 +878,21 @@ fn padded_bytes_per_row(unpadded_bytes_per_row: u32) -> u32 {
     let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
     unpadded_bytes_per_row.div_ceil(alignment) * alignment
 }
+
+/// Shape id understood by `marker_sdf` in the scatter shader.
+fn marker_type(marker: Marker) -> u32 {
+    match marker {
+        Marker::Circle => 0,
+        Marker::Square => 1,
+        Marker::TriangleUp => 2,
+        Marker::TriangleDown => 3,
+        Marker::TriangleLeft => 4,
+        Marker::TriangleRight => 5,
+        Marker::Diamond => 6,
+        Marker::Plus => 7,
+        Marker::Cross => 8,
+        Marker::Star => 9,
+        Marker::Hexagon => 10,
+        Marker::Pentagon => 11,
+    }
+}
@@ -0,0 +1,543 @@
+//! One frame of drawing, gathered before anything reaches the GPU.
+//!
+//! Every axes adds its fills, lines and markers in turn. Figure geometry for
+//! all of them goes into one vertex buffer for triangles and one instance
+//! buffer for markers, with the colour carried per vertex, and the per-draw
+//! settings into one uniform buffer read at dynamic offsets. The whole frame
+//! is then drawn in a single render pass, and neighbouring draws that share
+//! their settings are merged, so a figure of thousands of grid segments and
+//! small series costs a handful of draw calls.
+
+use crate::backend::{marker_instances, scissor_rect_for_axes, RenderResources};
+use crate::retained::{RetainedLine, RetainedSeries};
+use crate::vertex::{split, LineVertex, ScatterInstance, StrokePoint};
+use bytemuck::{Pod, Zeroable};
+use pltrs_core::{
+    Axes, AxisTransform, Color, LineBatch, LineCap, LineJoin, LineStyle, Rect, TextBatch,
+};
+use pltrs_geom::{stroke_polyline, StrokeStyle};
+use std::{collections::HashMap, num::NonZeroU64, ops::Range};
+use wgpu::util::DeviceExt;
+
+/// Mirrors `AxisTransform` in view_transform.wgsl.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct AxisUniform {
+    origin_high: f32,
+    origin_low: f32,
+    scale: f32,
+    start: f32,
+}
+
+impl AxisUniform {
+    /// Place positions stored as offsets from `origin` along `axis`.
+    fn new(axis: AxisTransform, origin: f64) -> Self {
+        let [origin_high, origin_low] = split(axis.lo - origin);
+        let scale = if axis.hi == axis.lo {
+            0.0
+        } else {
+            axis.extent / (axis.hi - axis.lo)
+        };
+        Self {
+            origin_high,
+            origin_low,
+            scale: scale as f32,
+            start: axis.start as f32,
+        }
+    }
+}
+
+/// Where the shaders place positions; the identity for figure coordinates.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ViewUniform {
+    x: AxisUniform,
+    y: AxisUniform,
+}
+
+impl ViewUniform {
+    fn new(x: AxisTransform, y: AxisTransform, origin: [f64; 2]) -> Self {
+        Self {
+            x: AxisUniform::new(x, origin[0]),
+            y: AxisUniform::new(y, origin[1]),
+        }
+    }
+
+    fn identity() -> Self {
+        Self::new(AxisTransform::IDENTITY, AxisTransform::IDENTITY, [0.0; 2])
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct LineUniforms {
+    dashes: [f32; LineStyle::MAX_DASHES],
+    dash_count: u32,
+    dash_total: f32,
+    _padding: [f32; 2],
+    view: ViewUniform,
+}
+
+impl LineUniforms {
+    fn new(dashes: &[f32], view: ViewUniform) -> Self {
+        let dash_count = dashes.len().min(LineStyle::MAX_DASHES);
+        let mut dash_lengths = [0.0; LineStyle::MAX_DASHES];
+        dash_lengths[..dash_count].copy_from_slice(&dashes[..dash_count]);
+        Self {
+            dashes: dash_lengths,
+            dash_count: dash_count as u32,
+            dash_total: dash_lengths.iter().sum(),
+            _padding: [0.0; 2],
+            view,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ScatterGlobalUniforms {
+    viewport_size: [f32; 2],
+    _padding: [f32; 2],
+    view: ViewUniform,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct StrokeUniforms {
+    view: ViewUniform,
+    color: [f32; 4],
+    viewport_size: [f32; 2],
+    half_width: f32,
+    miter_limit: f32,
+    join: u32,
+    cap: u32,
+    fill_triangles: u32,
+    _padding: u32,
+}
+
+/// Triangles the stroke shader spends on a round join or cap.
+const ROUND_TRIANGLES: u32 = 8;
+
+type Scissor = Option<(u32, u32, u32, u32)>;
+
+enum DrawKind<'a> {
+    /// A range of the frame's triangle vertices.
+    Triangles(Range<u32>),
+    /// A range of the frame's marker instances.
+    Markers(Range<u32>),
+    RetainedTriangles(&'a wgpu::Buffer, u32),
+    RetainedMarkers(&'a wgpu::Buffer, u32),
+    Stroke(&'a RetainedLine),
+}
+
+struct Draw<'a> {
+    kind: DrawKind<'a>,
+    /// Offset of the draw's settings in the uniform buffer.
+    uniform: u32,
+    scissor: Scissor,
+}
+
+pub(crate) struct Frame<'a> {
+    size: winit::dpi::PhysicalSize<u32>,
+    /// Device pixels per logical pixel.
+    dpi: f32,
+    /// The target's size in logical pixels, which line widths, dashes and
+    /// marker sizes are given in.
+    viewport: [f32; 2],
+    /// Space each entry of `uniforms` takes.
+    uniform_stride: usize,
+    uniforms: Vec<u8>,
+    /// Offsets of the entries already in `uniforms`, so draws with the same
+    /// settings share one.
+    uniform_offsets: HashMap<Vec<u8>, u32>,
+    vertices: Vec<LineVertex>,
+    instances: Vec<ScatterInstance>,
+    draws: Vec<Draw<'a>>,
+    texts: Vec<TextBatch>,
+}
+
+impl<'a> Frame<'a> {
+    pub(crate) fn new(resources: &RenderResources, dpi: f32) -> Self {
+        let alignment = resources
+            .device
+            .limits()
+            .min_uniform_buffer_offset_alignment as usize;
+        let largest = [
+            std::mem::size_of::<LineUniforms>(),
+            std::mem::size_of::<ScatterGlobalUniforms>(),
+            std::mem::size_of::<StrokeUniforms>(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default();
+        let size = resources.size;
+        Self {
+            size,
+            dpi,
+            viewport: [size.width as f32 / dpi, size.height as f32 / dpi],
+            uniform_stride: largest.div_ceil(alignment) * alignment,
+            uniforms: Vec::new(),
+            uniform_offsets: HashMap::new(),
+            vertices: Vec::new(),
+            instances: Vec::new(),
+            draws: Vec::new(),
+            texts: Vec::new(),
+        }
+    }
+
+    /// Add `axes`, with the `retained` series placed by its scales drawn
+    /// among its scene nodes.
+    pub(crate) fn add_axes(&mut self, axes: &Axes, retained: &[&'a RetainedSeries]) {
+        let batches = pltrs_core::build_axes_batches(axes);
+        let x = AxisTransform::new(&axes.x, axes.rect.x, axes.rect.w);
+        let y = AxisTransform::new(&axes.y, axes.rect.y, axes.rect.h);
+        let views: Vec<ViewUniform> = retained
+            .iter()
+            .map(|series| ViewUniform::new(x, y, series.origin))
+            .collect();
+        let scissor = scissor_rect_for_axes(axes.rect, self.size);
+
+        // Filled shapes go under lines, so grid lines show over bars and
+        // legend swatches over the legend box.
+        for solid in &batches.solids {
+            let color = rgba(solid.color);
+            let vertices = solid.vertices.iter().map(|&position| LineVertex {
+                position,
+                distance: 0.0,
+                position_low: [0.0; 2],
+                color,
+            });
+            self.push_triangles(vertices, &[], solid.clip);
+        }
+        for (series, &view) in retained.iter().zip(&views) {
+            let uniform = self.uniform(&LineUniforms::new(&[], view));
+            for solid in &series.solids {
+                self.push_draw(
+                    DrawKind::RetainedTriangles(&solid.vertices, solid.count),
+                    uniform,
+                    scissor,
+                );
+            }
+        }
+
+        for line in &batches.lines {
+            let vertices = self.stroke(line);
+            self.push_triangles(vertices, &line.dashes, line.clip);
+        }
+        for (series, &view) in retained.iter().zip(&views) {
+            for line in &series.lines {
+                let uniform = self.uniform(&self.stroke_uniforms(line, view));
+                self.push_draw(DrawKind::Stroke(line), uniform, scissor);
+            }
+        }
+
+        for batch in &batches.markers {
+            let instances = marker_instances(batch, |&position| (position, [0.0; 2]));
+            let uniform = self.uniform(&self.scatter_uniforms(ViewUniform::identity()));
+            let start = self.instances.len() as u32;
+            self.instances.extend(instances);
+            let end = self.instances.len() as u32;
+            let scissor = scissor_rect_for_axes(batch.clip, self.size);
+            self.push_draw(DrawKind::Markers(start..end), uniform, scissor);
+        }
+        for (series, &view) in retained.iter().zip(&views) {
+            let uniform = self.uniform(&self.scatter_uniforms(view));
+            for markers in &series.markers {
+                self.push_draw(
+                    DrawKind::RetainedMarkers(&markers.instances, markers.count),
+                    uniform,
+                    scissor,
+                );
+            }
+        }
+
+        self.texts.extend(batches.texts);
+    }
+
+    /// Stroke `line` in logical pixels so joins and dashes keep their shape whatever
+    /// the window's aspect ratio, then map back to figure space.
+    fn stroke(&self, line: &LineBatch) -> Vec<LineVertex> {
+        if line.vertices.len() < 2 {
+            return Vec::new();
+        }
+        let [width, height] = self.viewport;
+        let points: Vec<[f32; 2]> = line
+            .vertices
+            .iter()
+            .map(|[x, y]| [x * width, y * height])
+            .collect();
+        let stroke = StrokeStyle {
+            width: line.width.max(1.0),
+            join: line.join,
+            cap: line.cap,
+            miter_limit: line.miter_limit,
+            period: line.dashes.iter().sum(),
+        };
+        let color = rgba(line.color);
+        stroke_polyline(&points, &stroke)
+            .into_iter()
+            .map(|vertex| LineVertex {
+                position: [vertex.position[0] / width, vertex.position[1] / height],
+                distance: vertex.distance,
+                position_low: [0.0; 2],
+                color,
+            })
+            .collect()
+    }
+
+    fn stroke_uniforms(&self, line: &RetainedLine, view: ViewUniform) -> StrokeUniforms {
+        StrokeUniforms {
+            view,
+            color: rgba(line.color),
+            viewport_size: self.viewport,
+            half_width: line.width.max(1.0) * 0.5,
+            miter_limit: line.miter_limit,
+            join: match line.join {
+                LineJoin::Miter => 0,
+                LineJoin::Round => 1,
+                LineJoin::Bevel => 2,
+            },
+            cap: match line.cap {
+                LineCap::Butt => 0,
+                LineCap::Round => 1,
+                LineCap::Square => 2,
+            },
+            fill_triangles: stroke_fill_triangles(line),
+            _padding: 0,
+        }
+    }
+
+    fn scatter_uniforms(&self, view: ViewUniform) -> ScatterGlobalUniforms {
+        ScatterGlobalUniforms {
+            viewport_size: self.viewport,
+            _padding: [0.0; 2],
+            view,
+        }
+    }
+
+    fn push_triangles(
+        &mut self,
+        vertices: impl IntoIterator<Item = LineVertex>,
+        dashes: &[f32],
+        clip: Rect,
+    ) {
+        let start = self.vertices.len() as u32;
+        self.vertices.extend(vertices);
+        let end = self.vertices.len() as u32;
+        if start == end {
+            return;
+        }
+        let uniform = self.uniform(&LineUniforms::new(dashes, ViewUniform::identity()));
+        let scissor = scissor_rect_for_axes(clip, self.size);
+        self.push_draw(DrawKind::Triangles(start..end), uniform, scissor);
+    }
+
+    /// Add a draw, or extend the last one when it draws the range just
+    /// before this one with the same settings.
+    fn push_draw(&mut self, kind: DrawKind<'a>, uniform: u32, scissor: Scissor) {
+        if matches!(&kind, DrawKind::Triangles(range) | DrawKind::Markers(range) if range.is_empty())
+        {
+            return;
+        }
+        if let Some(last) = self.draws.last_mut() {
+            if last.uniform == uniform && last.scissor == scissor {
+                match (&mut last.kind, &kind) {
+                    (DrawKind::Triangles(last), DrawKind::Triangles(next))
+                    | (DrawKind::Markers(last), DrawKind::Markers(next))
+                        if last.end == next.start =>
+                    {
+                        last.end = next.end;
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.draws.push(Draw {
+            kind,
+            uniform,
+            scissor,
+        });
+    }
+
+    /// The offset of `value` in the uniform buffer, adding it if no earlier
+    /// draw uses the same settings.
+    fn uniform<T: Pod>(&mut self, value: &T) -> u32 {
+        let bytes = bytemuck::bytes_of(value);
+        if let Some(&offset) = self.uniform_offsets.get(bytes) {
+            return offset;
+        }
+        let offset = self.uniforms.len();
+        self.uniforms.extend_from_slice(bytes);
+        self.uniforms.resize(offset + self.uniform_stride, 0);
+        self.uniform_offsets.insert(bytes.to_vec(), offset as u32);
+        offset as u32
+    }
+
+    /// Record the frame into `encoder`: one pass for the shapes, then the
+    /// text on top.
+    pub(crate) fn draw(
+        self,
+        resources: &mut RenderResources,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+    ) {
+        if !self.draws.is_empty() {
+            self.draw_shapes(resources, encoder, view);
+        }
+
+        resources.text_renderer.queue(
+            &resources.device,
+            &resources.queue,
+            resources.size.width,
+            resources.size.height,
+            self.dpi,
+            &self.texts,
+        );
+        resources
+            .text_renderer
+            .draw(encoder, view, !self.texts.is_empty());
+    }
+
+    fn draw_shapes(
+        &self,
+        resources: &RenderResources,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+    ) {
+        let device = &resources.device;
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Frame Uniform Buffer"),
+            contents: &self.uniforms,
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = |label, layout, size: usize| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(label),
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &uniform_buffer,
+                        offset: 0,
+                        size: NonZeroU64::new(size as u64),
+                    }),
+                }],
+            })
+        };
+        let line_bind_group = bind_group(
+            "Line Bind Group",
+            &resources.line_bind_group_layout,
+            std::mem::size_of::<LineUniforms>(),
+        );
+        let scatter_bind_group = bind_group(
+            "Scatter Bind Group",
+            &resources.scatter_bind_group_layout,
+            std::mem::size_of::<ScatterGlobalUniforms>(),
+        );
+        let stroke_bind_group = bind_group(
+            "Stroke Bind Group",
+            &resources.stroke_bind_group_layout,
+            std::mem::size_of::<StrokeUniforms>(),
+        );
+        let vertex_buffer = (!self.vertices.is_empty()).then(|| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Frame Vertex Buffer"),
+                contents: bytemuck::cast_slice(&self.vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        });
+        let instance_buffer = (!self.instances.is_empty()).then(|| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Frame Instance Buffer"),
+                contents: bytemuck::cast_slice(&self.instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Frame Draw Pass"),
+            color_attachments: &[Some(resources.color_attachment(view, wgpu::LoadOp::Load))],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let full = (0, 0, self.size.width, self.size.height);
+        for draw in &self.draws {
+            let (x, y, width, height) = draw.scissor.unwrap_or(full);
+            rpass.set_scissor_rect(x, y, width, height);
+            match &draw.kind {
+                DrawKind::Triangles(range) => {
+                    let Some(vertices) = &vertex_buffer else {
+                        continue;
+                    };
+                    rpass.set_pipeline(&resources.line_pipeline);
+                    rpass.set_bind_group(0, &line_bind_group, &[draw.uniform]);
+                    rpass.set_vertex_buffer(0, vertices.slice(..));
+                    rpass.draw(range.clone(), 0..1);
+                }
+                DrawKind::RetainedTriangles(vertices, count) => {
+                    rpass.set_pipeline(&resources.line_pipeline);
+                    rpass.set_bind_group(0, &line_bind_group, &[draw.uniform]);
+                    rpass.set_vertex_buffer(0, vertices.slice(..));
+                    rpass.draw(0..*count, 0..1);
+                }
+                DrawKind::Markers(range) => {
+                    let Some(instances) = &instance_buffer else {
+                        continue;
+                    };
+                    rpass.set_pipeline(&resources.scatter_pipeline);
+                    rpass.set_bind_group(0, &scatter_bind_group, &[draw.uniform]);
+                    rpass.set_vertex_buffer(0, resources.marker_quad.slice(..));
+                    rpass.set_vertex_buffer(1, instances.slice(..));
+                    rpass.draw(0..4, range.clone());
+                }
+                DrawKind::RetainedMarkers(instances, count) => {
+                    rpass.set_pipeline(&resources.scatter_pipeline);
+                    rpass.set_bind_group(0, &scatter_bind_group, &[draw.uniform]);
+                    rpass.set_vertex_buffer(0, resources.marker_quad.slice(..));
+                    rpass.set_vertex_buffer(1, instances.slice(..));
+                    rpass.draw(0..4, 0..*count);
+                }
+                DrawKind::Stroke(line) => {
+                    rpass.set_pipeline(&resources.stroke_pipeline);
+                    rpass.set_bind_group(0, &stroke_bind_group, &[draw.uniform]);
+                    // Each slot starts one point later, giving every segment
+                    // the point before it, its two ends and the point after
+                    // it.
+                    let stride = std::mem::size_of::<StrokePoint>() as u64;
+                    for slot in 0..4 {
+                        rpass.set_vertex_buffer(slot, line.points.slice(slot as u64 * stride..));
+                    }
+                    let fill = stroke_fill_triangles(line);
+                    let end = if line.cap == LineCap::Round {
+                        ROUND_TRIANGLES
+                    } else {
+                        0
+                    };
+                    rpass.draw(0..3 * (4 + fill + end), 0..line.segments);
+                }
+            }
+        }
+        // The GL backend resolves the multisampled target with a blit that
+        // the last scissor still applies to, which would keep only the last
+        // draw's clip of the frame.
+        let (width, height) = (self.size.width, self.size.height);
+        rpass.set_scissor_rect(0, 0, width, height);
+    }
+}
+
+/// Triangles the stroke shader leaves for the join or cap at the start of
+/// each segment of `line`.
+fn stroke_fill_triangles(line: &RetainedLine) -> u32 {
+    if line.join == LineJoin::Round || line.cap == LineCap::Round {
+        ROUND_TRIANGLES
+    } else {
+        2
+    }
+}
+
+fn rgba(color: Color) -> [f32; 4] {
+    [color.r, color.g, color.b, color.a]
+}
@@ -1,9 +1,11 @@
-use pltrs_core::{Figure, PlotDefinition};
+use pltrs_core::{check_png_path, Figure, Image, PlotDefinition, PlotGrid};
 use std::path::Path;
 use winit::event_loop::{ControlFlow, EventLoop};
 
 mod app;
 mod backend;
+mod frame;
+mod retained;
 mod vertex;
 
 pub use app::App;
@@ -11,13 +13,14 @@ pub use backend::WgpuBackend;
 
 pub const KEYBOARD_INTERRUPT_ERROR: &str = "__pltrs_keyboard_interrupt__";
 
-/// Public entry: run a simple loop with an optional scene.
-pub fn run_with_figure(fig: Option<Figure>) -> anyhow::Result<()> {
+/// Public entry: run a simple loop with an optional scene, drawn with
+/// `samples` samples per pixel (fewer if the GPU cannot draw that many).
+pub fn run_with_figure(fig: Option<Figure>, samples: u32) -> anyhow::Result<()> {
     let _ = env_logger::try_init();
     let event_loop = EventLoop::new()?;
     event_loop.set_control_flow(ControlFlow::Poll);
 
-    let mut app = App::new(fig);
+    let mut app = App::new(fig, samples);
     event_loop.run_app(&mut app)?;
     if let Some(err) = app.init_error.take() {
         return Err(anyhow::anyhow!(err));
@@ -25,12 +28,18 @@ pub fn run_with_figure(fig: Option<Figure>) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn run_with_plot(plot: PlotDefinition) -> anyhow::Result<()> {
+pub fn run_with_plot(plot: PlotDefinition, samples: u32) -> anyhow::Result<()> {
+    run_with_grid(PlotGrid::single(plot), samples)
+}
+
+/// Show several plots in one interactive window; zoom and pan act on the
+/// plot under the cursor.
+pub fn run_with_grid(grid: PlotGrid, samples: u32) -> anyhow::Result<()> {
     let _ = env_logger::try_init();
     let event_loop = EventLoop::new()?;
     event_loop.set_control_flow(ControlFlow::Poll);
 
-    let mut app = App::new_interactive(plot);
+    let mut app = App::new_grid(grid, samples);
     event_loop.run_app(&mut app)?;
     if let Some(err) = app.init_error.take() {
         return Err(anyhow::anyhow!(err));
@@ -38,7 +47,56 @@ pub fn run_with_plot(plot: PlotDefinition) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Samples per pixel for windows, and for PNG export unless asked
+/// otherwise; enough to keep diagonal lines and bar edges smooth.
+pub const DEFAULT_SAMPLES: u32 = 4;
+
+/// Which renderer draws PNG exports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Renderer {
+    /// The GPU when an adapter is found, the CPU otherwise.
+    #[default]
+    Auto,
+    Gpu,
+    /// The software renderer of `pltrs_backend_cpu`, which needs no GPU.
+    Cpu,
+}
+
+/// Render `fig` offscreen and save it as a PNG of `fig.size.pixels()`.
+/// Uses the GPU when there is one and the CPU otherwise.
 pub fn save_figure_png(fig: &Figure, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    save_figure_png_with(fig, path, DEFAULT_SAMPLES, Renderer::Auto)
+}
+
+/// Like [`save_figure_png`], drawn by `renderer` with `samples` samples per
+/// pixel (1, 4 or 8; fewer if the GPU cannot draw that many).
+pub fn save_figure_png_with(
+    fig: &Figure,
+    path: impl AsRef<Path>,
+    samples: u32,
+    renderer: Renderer,
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    check_png_path(path)?;
+    Ok(render_image_with(fig, samples, renderer)?.save_png(path)?)
+}
+
+/// Render `fig` offscreen into an image of `fig.size.pixels()` in memory, as
+/// [`save_figure_png`] would save it.
+pub fn render_image(fig: &Figure) -> anyhow::Result<Image> {
+    render_image_with(fig, DEFAULT_SAMPLES, Renderer::Auto)
+}
+
+/// Like [`render_image`], drawn by `renderer` with `samples` samples per pixel.
+pub fn render_image_with(fig: &Figure, samples: u32, renderer: Renderer) -> anyhow::Result<Image> {
     let _ = env_logger::try_init();
-    pollster::block_on(backend::save_figure_png(fig, path.as_ref()))
+    if renderer == Renderer::Cpu {
+        return pltrs_backend_cpu::render_image(fig, samples);
+    }
+    match pollster::block_on(backend::render_image(fig, samples)) {
+        Err(err) if renderer == Renderer::Auto && err.is::<backend::NoAdapter>() => {
+            pltrs_backend_cpu::render_image(fig, samples)
+        }
+        result => result,
+    }
 }
@@ -1,9 +1,12 @@
 struct LineUniforms {
-    color: vec4<f32>,
+    // On/off dash lengths in pixels, four per vector.
+    dashes: array<vec4<f32>, 2>,
+    dash_count: u32, // 0 draws a solid line
+    dash_total: f32,
     _pad0: f32,
     _pad1: f32,
-    _pad2: f32,
-    _pad3: f32,
+    // Places vertices in figure space; identity for figure coordinates.
+    view: ViewTransform,
 }
 
 @group(0) @binding(0)
@@ -11,23 +14,45 @@ var<uniform> uniforms: LineUniforms;
 
 struct VertexInput {
     @location(0) position: vec2<f32>,
+    @location(1) distance: f32,
+    @location(2) position_low: vec2<f32>,
+    @location(3) color: vec4<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
+    @location(0) distance: f32,
+    @location(1) color: vec4<f32>,
 }
 
 @vertex
 fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
-    let ndc_x = model.position.x * 2.0 - 1.0;
-    let ndc_y = model.position.y * 2.0 - 1.0;
+    let position = view_apply(uniforms.view, model.position, model.position_low);
+    let ndc_x = position.x * 2.0 - 1.0;
+    let ndc_y = position.y * 2.0 - 1.0;
 
     out.clip_position = vec4<f32>(ndc_x, ndc_y, 0.0, 1.0);
+    out.distance = model.distance;
+    out.color = model.color;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return uniforms.color;
+    if (uniforms.dash_count > 0u) {
+        // Walk the pattern to find whether this pixel is in a dash or a gap.
+        var t = in.distance % uniforms.dash_total;
+        for (var i = 0u; i < uniforms.dash_count; i++) {
+            let len = uniforms.dashes[i / 4u][i % 4u];
+            if (t < len) {
+                if (i % 2u == 1u) {
+                    discard;
+                }
+                break;
+            }
+            t -= len;
+        }
+    }
+    return in.color;
 }
@@ -0,0 +1,177 @@
+//! Series kept on the GPU between frames.
+//!
+//! Interactive windows upload each series once, in data coordinates, and the
+//! shaders place it for the current view, so zooming and panning leave the
+//! data alone. Positions are stored as offsets from an origin in the middle
+//! of the series, split into a high and a low `f32`, so epoch timestamps
+//! keep their detail down to the deepest zoom.
+
+use crate::backend::marker_instances;
+use crate::vertex::{split_position, LineVertex, ScatterInstance, StrokePoint};
+use pltrs_core::{Batches, Color, LineCap, LineJoin, PlotSeries, ScaleKind};
+use wgpu::util::DeviceExt;
+
+/// A solid polyline, stroked by the stroke shader.
+pub(crate) struct RetainedLine {
+    /// The points, with a gap before the first and two after the last so
+    /// every segment can read its neighbours.
+    pub points: wgpu::Buffer,
+    pub segments: u32,
+    pub color: Color,
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+}
+
+/// Filled triangles, such as bars.
+pub(crate) struct RetainedSolid {
+    pub vertices: wgpu::Buffer,
+    pub count: u32,
+}
+
+pub(crate) struct RetainedMarkers {
+    pub instances: wgpu::Buffer,
+    pub count: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct RetainedSeries {
+    /// The scale kinds of the axes the series was uploaded for; positions
+    /// are stored after their forward transform.
+    pub kinds: (ScaleKind, ScaleKind),
+    /// What positions are stored relative to.
+    pub origin: [f64; 2],
+    pub lines: Vec<RetainedLine>,
+    pub solids: Vec<RetainedSolid>,
+    pub markers: Vec<RetainedMarkers>,
+}
+
+impl RetainedSeries {
+    pub(crate) fn upload(
+        device: &wgpu::Device,
+        series: &PlotSeries,
+        kinds: (ScaleKind, ScaleKind),
+    ) -> Self {
+        let batches = series.data_batches(kinds.0, kinds.1);
+        let origin = middle(&batches);
+        let relative = |[x, y]: [f64; 2]| split_position([x - origin[0], y - origin[1]]);
+        let mut retained = Self {
+            kinds,
+            origin,
+            ..Self::default()
+        };
+
+        for batch in &batches.lines {
+            let mut points = vec![StrokePoint::GAP];
+            for &position in &batch.vertices {
+                let point = if position.iter().all(|v| v.is_finite()) {
+                    let (position, position_low) = relative(position);
+                    StrokePoint {
+                        position,
+                        position_low,
+                    }
+                } else {
+                    StrokePoint::GAP
+                };
+                // Repeated points have no direction to stroke along.
+                let last = points.last().map(|last| (last.position, last.position_low));
+                if last != Some((point.position, point.position_low)) {
+                    points.push(point);
+                }
+            }
+            let segments = points.len().saturating_sub(2) as u32;
+            if segments == 0 {
+                continue;
+            }
+            points.extend([StrokePoint::GAP; 2]);
+            retained.lines.push(RetainedLine {
+                points: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Retained Line Points"),
+                    contents: bytemuck::cast_slice(&points),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                segments,
+                color: batch.color,
+                width: batch.width,
+                join: batch.join,
+                cap: batch.cap,
+                miter_limit: batch.miter_limit,
+            });
+        }
+
+        for batch in &batches.solids {
+            if batch.vertices.is_empty() {
+                continue;
+            }
+            let color = [batch.color.r, batch.color.g, batch.color.b, batch.color.a];
+            let vertices: Vec<LineVertex> = batch
+                .vertices
+                .iter()
+                .map(|&position| {
+                    let (position, position_low) = relative(position);
+                    LineVertex {
+                        position,
+                        distance: 0.0,
+                        position_low,
+                        color,
+                    }
+                })
+                .collect();
+            retained.solids.push(RetainedSolid {
+                vertices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Retained Solid Vertices"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                count: vertices.len() as u32,
+            });
+        }
+
+        for batch in &batches.markers {
+            let instances: Vec<ScatterInstance> =
+                marker_instances(batch, |&position| relative(position));
+            if instances.is_empty() {
+                continue;
+            }
+            retained.markers.push(RetainedMarkers {
+                instances: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Retained Marker Instances"),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                count: instances.len() as u32,
+            });
+        }
+
+        retained
+    }
+}
+
+/// The middle of the finite positions in `batches`, which keeps the offsets
+/// stored for them small.
+fn middle(batches: &Batches<[f64; 2]>) -> [f64; 2] {
+    let positions = batches
+        .lines
+        .iter()
+        .flat_map(|batch| &batch.vertices)
+        .chain(batches.solids.iter().flat_map(|batch| &batch.vertices))
+        .chain(batches.markers.iter().flat_map(|batch| &batch.positions));
+    let mut lo = [f64::INFINITY; 2];
+    let mut hi = [f64::NEG_INFINITY; 2];
+    for position in positions {
+        for axis in 0..2 {
+            if position[axis].is_finite() {
+                lo[axis] = lo[axis].min(position[axis]);
+                hi[axis] = hi[axis].max(position[axis]);
+            }
+        }
+    }
+    [0, 1].map(|axis| {
+        if lo[axis] <= hi[axis] {
+            lo[axis] + (hi[axis] - lo[axis]) * 0.5
+        } else {
+            0.0
+        }
+    })
+}
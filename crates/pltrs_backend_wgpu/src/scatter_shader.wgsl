@@ -5,8 +5,11 @@ struct VertexInput {
 struct InstanceInput {
     @location(1) instance_pos: vec2<f32>,
     @location(2) color: vec4<f32>,
-    @location(3) size: f32, // Size in pixels (radius for circle, side for square)
-    @location(4) marker_type: u32, // 0 = Circle, 1 = Square
+    @location(3) size: f32, // Marker width in pixels
+    @location(4) marker_type: u32, // See `marker_sdf`
+    @location(5) edge_color: vec4<f32>,
+    @location(6) edge_width: f32, // Outline width in pixels, drawn inside the shape
+    @location(7) instance_pos_low: vec2<f32>,
 }
 
 struct VertexOutput {
@@ -14,10 +17,16 @@ struct VertexOutput {
     @location(0) color: vec4<f32>,
     @location(1) uv: vec2<f32>, // Local coordinates [-1, 1] for shaping
     @location(2) @interpolate(flat) marker_type: u32,
+    @location(3) edge_color: vec4<f32>,
+    @location(4) @interpolate(flat) edge_width: f32,
+    @location(5) @interpolate(flat) size: f32,
 }
 
 struct Uniforms {
     viewport_size: vec2<f32>,
+    _pad: vec2<f32>,
+    // Places instance positions in figure space; identity for figure coordinates.
+    view: ViewTransform,
 }
 
 @group(0) @binding(0)
@@ -37,17 +46,16 @@ fn vs_main(
     // Convert pixel size to normalized device coordinates (NDC)
     // NDC ranges from -1 to 1, length 2.
     // viewport_size is in pixels.
-    let aspect = uniforms.viewport_size.x / uniforms.viewport_size.y;
-
     let pixel_offset = model.position * scale;
 
     let ndc_offset_x = (pixel_offset.x / uniforms.viewport_size.x) * 2.0;
     let ndc_offset_y = (pixel_offset.y / uniforms.viewport_size.y) * 2.0;
 
-    // Instance position is in normalized figure coords [0, 1]
+    // Instance position lands in normalized figure coords [0, 1]
     // Convert to NDC [-1, 1]
-    let base_ndc_x = instance.instance_pos.x * 2.0 - 1.0;
-    let base_ndc_y = instance.instance_pos.y * 2.0 - 1.0;
+    let base = view_apply(uniforms.view, instance.instance_pos, instance.instance_pos_low);
+    let base_ndc_x = base.x * 2.0 - 1.0;
+    let base_ndc_y = base.y * 2.0 - 1.0;
 
     out.clip_position = vec4<f32>(
         base_ndc_x + ndc_offset_x,
@@ -59,22 +67,110 @@ fn vs_main(
     out.color = instance.color;
     out.uv = model.position * 2.0; // Map [-0.5, 0.5] to [-1, 1] for SDF
     out.marker_type = instance.marker_type;
+    out.edge_color = instance.edge_color;
+    out.edge_width = instance.edge_width;
+    out.size = instance.size;
 
     return out;
 }
 
+// Signed distance functions, negative inside. `r` is the radius of the
+// circle the shape fits in; all shapes point up.
+
+fn sd_box(p: vec2<f32>, b: vec2<f32>) -> f32 {
+    let d = abs(p) - b;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+fn sd_triangle(p_in: vec2<f32>, r: f32) -> f32 {
+    // Equilateral triangle with half side `h`, centred on its centroid.
+    let k = sqrt(3.0);
+    let h = r * k * 0.5;
+    var p = p_in;
+    p.x = abs(p.x) - h;
+    p.y = p.y + h / k;
+    if (p.x + k * p.y > 0.0) {
+        p = vec2<f32>(p.x - k * p.y, -k * p.x - p.y) / 2.0;
+    }
+    p.x -= clamp(p.x, -2.0 * h, 0.0);
+    return -length(p) * sign(p.y);
+}
+
+fn sd_plus(p: vec2<f32>, r: f32) -> f32 {
+    let arm = r * 0.3;
+    return min(sd_box(p, vec2<f32>(r, arm)), sd_box(p, vec2<f32>(arm, r)));
+}
+
+fn sd_star(p_in: vec2<f32>, r: f32) -> f32 {
+    let rf = 0.45;
+    let k1 = vec2<f32>(0.809016994375, -0.587785252292);
+    let k2 = vec2<f32>(-k1.x, k1.y);
+    var p = vec2<f32>(abs(p_in.x), p_in.y);
+    p -= 2.0 * max(dot(k1, p), 0.0) * k1;
+    p -= 2.0 * max(dot(k2, p), 0.0) * k2;
+    p.x = abs(p.x);
+    p.y -= r;
+    let ba = rf * vec2<f32>(-k1.y, k1.x) - vec2<f32>(0.0, 1.0);
+    let h = clamp(dot(p, ba) / dot(ba, ba), 0.0, r);
+    return length(p - ba * h) * sign(p.y * ba.x - p.x * ba.y);
+}
+
+fn sd_hexagon(p_in: vec2<f32>, r: f32) -> f32 {
+    let k = vec3<f32>(-0.866025404, 0.5, 0.577350269);
+    let inner = r * 0.866025404;
+    var p = abs(p_in);
+    p -= 2.0 * min(dot(k.xy, p), 0.0) * k.xy;
+    p -= vec2<f32>(clamp(p.x, -k.z * inner, k.z * inner), inner);
+    return length(p) * sign(p.y);
+}
+
+fn sd_pentagon(p_in: vec2<f32>, r: f32) -> f32 {
+    let k = vec3<f32>(0.809016994, 0.587785252, 0.726542528);
+    let inner = r * k.x;
+    var p = vec2<f32>(abs(p_in.x), -p_in.y);
+    p -= 2.0 * min(dot(vec2<f32>(-k.x, k.y), p), 0.0) * vec2<f32>(-k.x, k.y);
+    p -= 2.0 * min(dot(vec2<f32>(k.x, k.y), p), 0.0) * vec2<f32>(k.x, k.y);
+    p -= vec2<f32>(clamp(p.x, -inner * k.z, inner * k.z), inner);
+    return length(p) * sign(p.y);
+}
+
+fn rotate_45(p: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(p.x + p.y, p.y - p.x) * 0.707106781;
+}
+
+// Marker ids match `marker_type` in the backend.
+fn marker_sdf(p: vec2<f32>, marker: u32, r: f32) -> f32 {
+    switch marker {
+        case 0u: { return length(p) - r; }
+        case 1u: { return sd_box(p, vec2<f32>(r)); }
+        case 2u: { return sd_triangle(p, r); }
+        case 3u: { return sd_triangle(vec2<f32>(p.x, -p.y), r); }
+        case 4u: { return sd_triangle(vec2<f32>(p.y, -p.x), r); }
+        case 5u: { return sd_triangle(vec2<f32>(-p.y, p.x), r); }
+        case 6u: { return sd_box(rotate_45(p), vec2<f32>(r * 0.707106781)); }
+        case 7u: { return sd_plus(p, r); }
+        case 8u: { return sd_plus(rotate_45(p), r); }
+        case 9u: { return sd_star(p, r); }
+        case 10u: { return sd_hexagon(p, r); }
+        case 11u: { return sd_pentagon(p, r); }
+        default: { return length(p) - r; }
+    }
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    if (in.marker_type == 0u) {
-        // Circle SDF
-        let dist = length(in.uv);
-        let alpha = 1.0 - smoothstep(0.85, 1.0, dist);
-        if (dist > 1.0) {
-            discard;
-        }
-        return vec4<f32>(in.color.rgb, in.color.a * alpha);
-    } else {
-        // Square (just fill)
-        return in.color;
+    // Work in pixels so edges stay one pixel soft at every marker size.
+    let half_size = in.size * 0.5;
+    let d = marker_sdf(in.uv * half_size, in.marker_type, half_size - 0.5);
+    let coverage = clamp(0.5 - d, 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+
+    var color = in.color;
+    if (in.edge_width > 0.0) {
+        let fill = clamp(0.5 - (d + in.edge_width), 0.0, 1.0);
+        color = mix(in.edge_color, in.color, fill);
     }
+    return vec4<f32>(color.rgb, color.a * coverage);
 }
@@ -0,0 +1,308 @@
+// Solid polylines kept on the GPU in data coordinates and stroked here for
+// the current view. Each instance is one segment; it also reads the points
+// either side so joins and caps follow the same rules as `stroke_polyline`
+// in pltrs_geom. Neighbouring segments compute a shared join identically,
+// so their inner corners meet exactly.
+//
+// Triangles per instance:
+//   0-1   the segment body
+//   2-3   the inner part of the join at the start
+//   4..   the outer fill of that join, or a round cap at the start of a run
+//   after `fill_triangles` more, a round cap at the end of a run
+
+const ROUND_TRIANGLES: u32 = 8u;
+const PI: f32 = 3.14159265;
+// Points with x at or above this mark a gap in the line.
+const GAP: f32 = 3.0e38;
+// Segments shorter than this many pixels have no usable direction.
+const MIN_LENGTH: f32 = 1e-6;
+const FAR: f32 = 1e30;
+
+const JOIN_MITER: u32 = 0u;
+const JOIN_ROUND: u32 = 1u;
+const CAP_ROUND: u32 = 1u;
+const CAP_SQUARE: u32 = 2u;
+
+struct StrokeUniforms {
+    view: ViewTransform,
+    color: vec4<f32>,
+    viewport_size: vec2<f32>,
+    half_width: f32, // pixels
+    miter_limit: f32, // multiple of the line width
+    join: u32, // 0 miter, 1 round, 2 bevel
+    cap: u32, // 0 butt, 1 round, 2 square
+    fill_triangles: u32,
+    _pad: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: StrokeUniforms;
+
+struct SegmentInput {
+    @location(0) before: vec2<f32>,
+    @location(1) start: vec2<f32>,
+    @location(2) end: vec2<f32>,
+    @location(3) after: vec2<f32>,
+    @location(4) before_low: vec2<f32>,
+    @location(5) start_low: vec2<f32>,
+    @location(6) end_low: vec2<f32>,
+    @location(7) after_low: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+}
+
+// Where the segments meeting at a point connect.
+struct Join {
+    // False when the line goes straight on and needs no fill.
+    turns: bool,
+    // 1 when the outer side of the turn is left of the direction of travel.
+    outer: f32,
+    // Where the inner edges meet, when that is within both segments.
+    has_inner: bool,
+    inner: vec2<f32>,
+    // Outer corners of the incoming and outgoing segment.
+    from_corner: vec2<f32>,
+    to_corner: vec2<f32>,
+    has_tip: bool,
+    tip: vec2<f32>,
+}
+
+fn present(p: vec2<f32>) -> bool {
+    return p.x < GAP;
+}
+
+fn to_pixels(p: vec2<f32>, low: vec2<f32>) -> vec2<f32> {
+    return view_apply(uniforms.view, p, low) * uniforms.viewport_size;
+}
+
+fn perp(d: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(-d.y, d.x);
+}
+
+fn cross2(a: vec2<f32>, b: vec2<f32>) -> f32 {
+    return a.x * b.y - a.y * b.x;
+}
+
+fn unit(v: vec2<f32>) -> vec2<f32> {
+    let len = length(v);
+    if (len > 0.0) {
+        return v / len;
+    }
+    return v;
+}
+
+// The join at `p1` between segments `p0 -> p1` and `p1 -> p2`.
+fn join_at(p0: vec2<f32>, p1: vec2<f32>, p2: vec2<f32>) -> Join {
+    let half = uniforms.half_width;
+    let len0 = length(p1 - p0);
+    let len1 = length(p2 - p1);
+    let d0 = (p1 - p0) / len0;
+    let d1 = (p2 - p1) / len1;
+    let n0 = perp(d0);
+    let n1 = perp(d1);
+    let turn = cross2(d0, d1);
+
+    var join: Join;
+    join.turns = !(abs(turn) <= 1e-6 && dot(d0, d1) > 0.0);
+    // A left turn has its outer edge on the right.
+    join.outer = select(1.0, -1.0, turn > 0.0);
+
+    let bisector = n0 + n1;
+    let cos_half = dot(n0, unit(bisector));
+    var inner_reach = FAR;
+    var miter = FAR;
+    if (cos_half > 1e-4) {
+        inner_reach = half * sqrt(1.0 - cos_half * cos_half) / cos_half;
+        miter = half / cos_half;
+    }
+    join.has_inner = inner_reach <= min(len0, len1);
+    join.inner = p1 - n0 * (join.outer * half) - d0 * inner_reach;
+    join.from_corner = p1 + n0 * (join.outer * half);
+    join.to_corner = p1 + n1 * (join.outer * half);
+    join.has_tip = miter <= uniforms.miter_limit * half;
+    join.tip = p1 + unit(bisector) * (join.outer * miter);
+    return join;
+}
+
+// Corner `k` of a fan from `from_corner` turning by `sweep` around `center`.
+fn fan_point(center: vec2<f32>, from_corner: vec2<f32>, to_corner: vec2<f32>, sweep: f32, k: u32) -> vec2<f32> {
+    if (k == 0u) {
+        return from_corner;
+    }
+    if (k >= ROUND_TRIANGLES) {
+        return to_corner;
+    }
+    let offset = from_corner - center;
+    let angle = atan2(offset.y, offset.x) + sweep * f32(k) / f32(ROUND_TRIANGLES);
+    return center + uniforms.half_width * vec2<f32>(cos(angle), sin(angle));
+}
+
+fn fan_corner(center: vec2<f32>, from_corner: vec2<f32>, to_corner: vec2<f32>, sweep: f32, k: u32, corner: u32) -> vec2<f32> {
+    switch corner {
+        case 0u: {
+            return center;
+        }
+        case 1u: {
+            return fan_point(center, from_corner, to_corner, sweep, k);
+        }
+        default: {
+            return fan_point(center, from_corner, to_corner, sweep, k + 1u);
+        }
+    }
+}
+
+fn pick(a: vec2<f32>, b: vec2<f32>, c: vec2<f32>, corner: u32) -> vec2<f32> {
+    switch corner {
+        case 0u: {
+            return a;
+        }
+        case 1u: {
+            return b;
+        }
+        default: {
+            return c;
+        }
+    }
+}
+
+fn place(pixel: vec2<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    let ndc = pixel / uniforms.viewport_size * 2.0 - 1.0;
+    out.clip_position = vec4<f32>(ndc, 0.0, 1.0);
+    return out;
+}
+
+// Every corner of an unused triangle lands on the same point, so it covers
+// nothing.
+fn unused() -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(-2.0, -2.0, 0.0, 1.0);
+    return out;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, segment: SegmentInput) -> VertexOutput {
+    if (!present(segment.start) || !present(segment.end)) {
+        return unused();
+    }
+    let half = uniforms.half_width;
+    let a = to_pixels(segment.start, segment.start_low);
+    let b = to_pixels(segment.end, segment.end_low);
+    let len = length(b - a);
+    if (!(len > MIN_LENGTH)) {
+        return unused();
+    }
+    let d = (b - a) / len;
+    let n = perp(d);
+
+    let run_start = !present(segment.before);
+    let run_end = !present(segment.after);
+    let p = to_pixels(segment.before, segment.before_low);
+    let c = to_pixels(segment.after, segment.after_low);
+    let join_start = !run_start && length(a - p) > MIN_LENGTH;
+    let join_end = !run_end && length(c - b) > MIN_LENGTH;
+
+    var start_join: Join;
+    var end_join: Join;
+    if (join_start) {
+        start_join = join_at(p, a, b);
+    }
+    if (join_end) {
+        end_join = join_at(a, b, c);
+    }
+
+    var l0 = a + n * half;
+    var r0 = a - n * half;
+    var l1 = b + n * half;
+    var r1 = b - n * half;
+    if (join_start && start_join.turns && start_join.has_inner) {
+        if (start_join.outer > 0.0) {
+            r0 = start_join.inner;
+        } else {
+            l0 = start_join.inner;
+        }
+    }
+    if (join_end && end_join.turns && end_join.has_inner) {
+        if (end_join.outer > 0.0) {
+            r1 = end_join.inner;
+        } else {
+            l1 = end_join.inner;
+        }
+    }
+    if (uniforms.cap == CAP_SQUARE) {
+        if (run_start) {
+            l0 -= d * half;
+            r0 -= d * half;
+        }
+        if (run_end) {
+            l1 += d * half;
+            r1 += d * half;
+        }
+    }
+
+    let triangle = index / 3u;
+    let corner = index % 3u;
+    if (triangle == 0u) {
+        return place(pick(l0, r0, l1, corner));
+    }
+    if (triangle == 1u) {
+        return place(pick(l1, r0, r1, corner));
+    }
+
+    let turning = join_start && start_join.turns;
+    if (triangle < 4u) {
+        if (!turning || !start_join.has_inner) {
+            return unused();
+        }
+        if (triangle == 2u) {
+            return place(pick(start_join.inner, start_join.from_corner, a, corner));
+        }
+        return place(pick(start_join.inner, a, start_join.to_corner, corner));
+    }
+
+    let fill_end = 4u + uniforms.fill_triangles;
+    if (triangle < fill_end) {
+        let k = triangle - 4u;
+        if (turning) {
+            let from_corner = start_join.from_corner;
+            let to_corner = start_join.to_corner;
+            if (uniforms.join == JOIN_ROUND) {
+                let sweep = atan2(cross2(from_corner - a, to_corner - a), dot(from_corner - a, to_corner - a));
+                return place(fan_corner(a, from_corner, to_corner, sweep, k, corner));
+            }
+            if (uniforms.join == JOIN_MITER && start_join.has_tip) {
+                if (k == 0u) {
+                    return place(pick(a, from_corner, start_join.tip, corner));
+                }
+                if (k == 1u) {
+                    return place(pick(a, start_join.tip, to_corner, corner));
+                }
+                return unused();
+            }
+            if (k == 0u) {
+                return place(pick(a, from_corner, to_corner, corner));
+            }
+            return unused();
+        }
+        // Counter-clockwise from left to right goes round the back of the
+        // start.
+        if (run_start && uniforms.cap == CAP_ROUND) {
+            return place(fan_corner(a, a + n * half, a - n * half, PI, k, corner));
+        }
+        return unused();
+    }
+
+    // And from right to left round the front of the end.
+    if (run_end && uniforms.cap == CAP_ROUND) {
+        let k = triangle - fill_end;
+        return place(fan_corner(b, b - n * half, b + n * half, PI, k, corner));
+    }
+    return unused();
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return uniforms.color;
+}
@@ -4,10 +4,21 @@ use wgpu::vertex_attr_array;
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct LineVertex {
     pub position: [f32; 2],
+    /// Pixel distance along the line, used to lay out dashes.
+    pub distance: f32,
+    /// What `position` leaves out of a position kept uploaded; zero for
+    /// figure coordinates. See [`split`].
+    pub position_low: [f32; 2],
+    pub color: [f32; 4],
 }
 
 impl LineVertex {
-    pub const ATTRIBS: [wgpu::VertexAttribute; 1] = vertex_attr_array![0 => Float32x2];
+    pub const ATTRIBS: [wgpu::VertexAttribute; 4] = vertex_attr_array![
+        0 => Float32x2,
+        1 => Float32,
+        2 => Float32x2,
+        3 => Float32x4
+    ];
 
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
@@ -45,11 +56,23 @@ pub struct ScatterInstance {
     pub color: [f32; 4],
     pub size: f32,
     pub marker_type: u32,
+    pub edge_color: [f32; 4],
+    pub edge_width: f32,
+    /// What `position` leaves out of a position kept uploaded; zero for
+    /// figure coordinates. See [`split`].
+    pub position_low: [f32; 2],
 }
 
 impl ScatterInstance {
-    pub const ATTRIBS: [wgpu::VertexAttribute; 4] =
-        vertex_attr_array![1 => Float32x2, 2 => Float32x4, 3 => Float32, 4 => Uint32];
+    pub const ATTRIBS: [wgpu::VertexAttribute; 7] = vertex_attr_array![
+        1 => Float32x2,
+        2 => Float32x4,
+        3 => Float32,
+        4 => Uint32,
+        5 => Float32x4,
+        6 => Float32,
+        7 => Float32x2
+    ];
 
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
@@ -59,3 +82,57 @@ impl ScatterInstance {
         }
     }
 }
+
+/// A polyline point kept uploaded, split as by [`split`]. The stroke shader
+/// reads the point buffer four times per segment, one point apart: the
+/// point before the segment, its two ends and the point after it.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct StrokePoint {
+    pub position: [f32; 2],
+    pub position_low: [f32; 2],
+}
+
+impl StrokePoint {
+    /// Marks a gap; the shader treats any x at or above 3e38 as missing.
+    pub const GAP: Self = Self {
+        position: [f32::MAX, f32::MAX],
+        position_low: [0.0, 0.0],
+    };
+
+    pub fn descs() -> [wgpu::VertexBufferLayout<'static>; 4] {
+        const BEFORE: [wgpu::VertexAttribute; 2] =
+            vertex_attr_array![0 => Float32x2, 4 => Float32x2];
+        const START: [wgpu::VertexAttribute; 2] =
+            vertex_attr_array![1 => Float32x2, 5 => Float32x2];
+        const END: [wgpu::VertexAttribute; 2] = vertex_attr_array![2 => Float32x2, 6 => Float32x2];
+        const AFTER: [wgpu::VertexAttribute; 2] =
+            vertex_attr_array![3 => Float32x2, 7 => Float32x2];
+        let layout = |attributes: &'static [wgpu::VertexAttribute]| wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<StrokePoint>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes,
+        };
+        [
+            layout(&BEFORE),
+            layout(&START),
+            layout(&END),
+            layout(&AFTER),
+        ]
+    }
+}
+
+/// `v` as a high `f32` and the low `f32` it leaves out. Shaders subtract
+/// the view start split the same way, high from high and low from low, so
+/// the offset keeps close to `f64` precision however deep the zoom.
+pub fn split(v: f64) -> [f32; 2] {
+    let high = v as f32;
+    [high, (v - high as f64) as f32]
+}
+
+/// [`split`] for both coordinates of `position`, as `(high, low)`.
+pub fn split_position([x, y]: [f64; 2]) -> ([f32; 2], [f32; 2]) {
+    let [x_high, x_low] = split(x);
+    let [y_high, y_low] = split(y);
+    ([x_high, y_high], [x_low, y_low])
+}
@@ -0,0 +1,27 @@
+// Placing positions in figure space, shared by the shaders that draw series
+// kept on the GPU. Those are uploaded after `ScaleKind::forward`, relative to
+// an origin near the series and split into a high and a low f32. The view
+// start is split the same way, so the offset of a position from it keeps its
+// precision however deep the zoom. Figure coordinates pass through the
+// identity transform unchanged.
+
+struct AxisTransform {
+    // Where the axis starts, relative to the series origin.
+    origin_high: f32,
+    origin_low: f32,
+    scale: f32, // figure units per unit of the forward transform
+    start: f32, // figure-space start of the axis
+}
+
+struct ViewTransform {
+    x: AxisTransform,
+    y: AxisTransform,
+}
+
+fn axis_apply(axis: AxisTransform, high: f32, low: f32) -> f32 {
+    return axis.start + ((high - axis.origin_high) + (low - axis.origin_low)) * axis.scale;
+}
+
+fn view_apply(view: ViewTransform, high: vec2<f32>, low: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(axis_apply(view.x, high.x, low.x), axis_apply(view.y, high.y, low.y));
+}
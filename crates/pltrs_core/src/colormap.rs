@@ -0,0 +1,182 @@
+//! Colormaps for colouring points by a value, and the colorbar that explains
+//! them.
+
+use crate::scene::Color;
+
+/// A colour gradient sampled by linear interpolation between evenly spaced
+/// stops.
+#[derive(Clone, Debug)]
+pub struct Colormap {
+    stops: Vec<Color>,
+}
+
+const VIRIDIS: [u32; 9] = [
+    0x440154, 0x472c7a, 0x3b518b, 0x2c718e, 0x21908d, 0x27ad81, 0x5cc863, 0xaadc32, 0xfde725,
+];
+const MAGMA: [u32; 9] = [
+    0x000004, 0x1c1044, 0x4f127b, 0x812581, 0xb5367a, 0xe55064, 0xfb8761, 0xfec287, 0xfcfdbf,
+];
+const PLASMA: [u32; 9] = [
+    0x0d0887, 0x4c02a1, 0x7e03a8, 0xa92395, 0xcc4778, 0xe56b5d, 0xf89441, 0xfdc328, 0xf0f921,
+];
+const CIVIDIS: [u32; 9] = [
+    0x00224e, 0x123570, 0x3b496c, 0x575d6d, 0x707173, 0x8a8678, 0xa59c74, 0xc3b369, 0xfee838,
+];
+const COOLWARM: [u32; 9] = [
+    0x3b4cc0, 0x6282ea, 0x8db0fe, 0xb8d0f9, 0xdddddd, 0xf5c4ad, 0xf49a7b, 0xde604d, 0xb40426,
+];
+
+impl Colormap {
+    /// A colormap running through `stops` from low to high values. Returns
+    /// `None` when `stops` is empty.
+    pub fn from_colors(stops: Vec<Color>) -> Option<Self> {
+        (!stops.is_empty()).then_some(Self { stops })
+    }
+
+    pub fn viridis() -> Self {
+        Self::from_hex(&VIRIDIS)
+    }
+
+    pub fn magma() -> Self {
+        Self::from_hex(&MAGMA)
+    }
+
+    pub fn plasma() -> Self {
+        Self::from_hex(&PLASMA)
+    }
+
+    pub fn cividis() -> Self {
+        Self::from_hex(&CIVIDIS)
+    }
+
+    /// Diverging blue to red, light grey in the middle.
+    pub fn coolwarm() -> Self {
+        Self::from_hex(&COOLWARM)
+    }
+
+    /// A built-in colormap by name, e.g. `"viridis"`. A `_r` suffix reverses it.
+    pub fn by_name(name: &str) -> Option<Self> {
+        if let Some(name) = name.strip_suffix("_r") {
+            return Self::by_name(name).map(Self::reversed);
+        }
+        match name {
+            "viridis" => Some(Self::viridis()),
+            "magma" => Some(Self::magma()),
+            "plasma" => Some(Self::plasma()),
+            "cividis" => Some(Self::cividis()),
+            "coolwarm" => Some(Self::coolwarm()),
+            _ => None,
+        }
+    }
+
+    /// Names accepted by [`Colormap::by_name`], without the `_r` variants.
+    pub const NAMES: [&'static str; 5] = ["viridis", "magma", "plasma", "cividis", "coolwarm"];
+
+    pub fn reversed(mut self) -> Self {
+        self.stops.reverse();
+        self
+    }
+
+    /// The colour at `t` in `[0, 1]`; values outside are clamped.
+    pub fn sample(&self, t: f64) -> Color {
+        let last = self.stops.len() - 1;
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let pos = t * last as f64;
+        let idx = (pos.floor() as usize).min(last.saturating_sub(1));
+        let Some(&hi) = self.stops.get(idx + 1) else {
+            return self.stops[last];
+        };
+        let lo = self.stops[idx];
+        let frac = (pos - idx as f64) as f32;
+        let mix = |a: f32, b: f32| a + (b - a) * frac;
+        Color {
+            r: mix(lo.r, hi.r),
+            g: mix(lo.g, hi.g),
+            b: mix(lo.b, hi.b),
+            a: mix(lo.a, hi.a),
+        }
+    }
+
+    fn from_hex(stops: &[u32]) -> Self {
+        let channel = |hex: u32, shift: u32| ((hex >> shift) & 0xff) as f32 / 255.0;
+        Self {
+            stops: stops
+                .iter()
+                .map(|&hex| Color {
+                    r: channel(hex, 16),
+                    g: channel(hex, 8),
+                    b: channel(hex, 0),
+                    a: 1.0,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for Colormap {
+    fn default() -> Self {
+        Self::viridis()
+    }
+}
+
+/// Values mapped linearly from `range` onto a colormap.
+#[derive(Clone, Debug)]
+pub struct ColorMapping {
+    pub cmap: Colormap,
+    pub range: (f64, f64),
+}
+
+impl ColorMapping {
+    /// The colour for `value`, or a transparent one for NaN so such points
+    /// are not drawn.
+    pub fn color(&self, value: f64) -> Color {
+        if value.is_nan() {
+            return Color {
+                a: 0.0,
+                ..Color::WHITE
+            };
+        }
+        let (lo, hi) = self.range;
+        let t = if hi != lo {
+            (value - lo) / (hi - lo)
+        } else {
+            0.5
+        };
+        self.cmap.sample(t)
+    }
+}
+
+/// A colour bar drawn right of the plot area.
+#[derive(Clone, Debug)]
+pub struct Colorbar {
+    pub mapping: ColorMapping,
+    pub label: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_interpolate_between_stops() {
+        let cmap = Colormap::from_colors(vec![Color::BLACK, Color::WHITE]).unwrap();
+        assert_eq!(cmap.sample(0.25).r, 0.25);
+        assert_eq!(cmap.sample(2.0).g, 1.0);
+
+        let viridis = Colormap::by_name("viridis").unwrap();
+        let reversed = Colormap::by_name("viridis_r").unwrap();
+        assert_eq!(viridis.sample(1.0).g, reversed.sample(0.0).g);
+        assert!(Colormap::by_name("jet").is_none());
+    }
+
+    #[test]
+    fn mapping_normalizes_values_and_hides_nan() {
+        let mapping = ColorMapping {
+            cmap: Colormap::from_colors(vec![Color::BLACK, Color::WHITE]).unwrap(),
+            range: (10.0, 20.0),
+        };
+        assert_eq!(mapping.color(15.0).b, 0.5);
+        assert_eq!(mapping.color(5.0).b, 0.0);
+        assert_eq!(mapping.color(f64::NAN).a, 0.0);
+    }
+}
@@ -0,0 +1,185 @@
+//! Level of detail for long line series.
+//!
+//! A line with far more points than the plot has pixel columns is reduced to
+//! the first, lowest, highest and last point of each column it crosses, which
+//! covers the same pixels as the full line. The reduction runs per view, so
+//! zooming in brings back full detail.
+
+use crate::scale::Scale;
+
+/// Lines with at most this many points per pixel column are drawn in full.
+const POINTS_PER_COLUMN: usize = 4;
+
+/// The points of a line worth drawing when `xscale` spans `columns` pixel
+/// columns: only the visible part plus one point either side, reduced per
+/// column when it is still dense. Points with a non-finite x or y are kept
+/// so gaps stay in place. Returns `None` when the line is short enough to
+/// draw as it is, or when its finite x values are not sorted.
+pub fn decimate_line(
+    xs: &[f64],
+    ys: &[f64],
+    xscale: &Scale,
+    columns: usize,
+) -> Option<(Vec<f64>, Vec<f64>)> {
+    let len = xs.len().min(ys.len());
+    let limit = POINTS_PER_COLUMN * columns.max(1);
+    let xs = &xs[..len];
+    if len <= limit
+        || !xs
+            .iter()
+            .filter(|x| x.is_finite())
+            .is_sorted_by(|a, b| a <= b)
+    {
+        return None;
+    }
+
+    // Comparisons with NaN are false, so these scans step over gaps in x
+    // where a binary search could stop on one.
+    let (d0, d1) = xscale.domain();
+    let (lo, hi) = (d0.min(d1), d0.max(d1));
+    let start = xs
+        .iter()
+        .position(|&x| x >= lo)
+        .unwrap_or(len)
+        .saturating_sub(1);
+    let end = (xs.iter().rposition(|&x| x <= hi).map_or(0, |i| i + 1) + 1).min(len);
+    if end - start <= limit {
+        return Some((xs[start..end].to_vec(), ys[start..end].to_vec()));
+    }
+
+    let mut out = (Vec::with_capacity(limit), Vec::with_capacity(limit));
+    let mut column: Option<Column> = None;
+    for i in start..end {
+        let y = ys[i];
+        if !(xs[i].is_finite() && y.is_finite()) {
+            if let Some(done) = column.take() {
+                done.emit(xs, ys, &mut out);
+            }
+            out.0.push(xs[i]);
+            out.1.push(y);
+            continue;
+        }
+
+        let index = (xscale.map(xs[i]) * columns as f64).floor() as i64;
+        match &mut column {
+            Some(current) if current.index == index => current.add(i, y, ys),
+            _ => {
+                if let Some(done) = column.replace(Column::new(index, i)) {
+                    done.emit(xs, ys, &mut out);
+                }
+            }
+        }
+    }
+    if let Some(done) = column {
+        done.emit(xs, ys, &mut out);
+    }
+    Some(out)
+}
+
+/// Indices of the points kept for one pixel column.
+struct Column {
+    index: i64,
+    first: usize,
+    min: usize,
+    max: usize,
+    last: usize,
+}
+
+impl Column {
+    fn new(index: i64, i: usize) -> Self {
+        Self {
+            index,
+            first: i,
+            min: i,
+            max: i,
+            last: i,
+        }
+    }
+
+    fn add(&mut self, i: usize, y: f64, ys: &[f64]) {
+        if y < ys[self.min] {
+            self.min = i;
+        }
+        if y > ys[self.max] {
+            self.max = i;
+        }
+        self.last = i;
+    }
+
+    /// Push the kept points in their original order.
+    fn emit(&self, xs: &[f64], ys: &[f64], out: &mut (Vec<f64>, Vec<f64>)) {
+        let mut kept = [self.first, self.min, self.max, self.last];
+        kept.sort_unstable();
+        let mut previous = None;
+        for i in kept {
+            if previous != Some(i) {
+                out.0.push(xs[i]);
+                out.1.push(ys[i]);
+                previous = Some(i);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(len: usize) -> (Vec<f64>, Vec<f64>) {
+        let xs: Vec<f64> = (0..len).map(|i| i as f64).collect();
+        let ys = xs.iter().map(|x| (x * 0.37).sin() * x.sqrt()).collect();
+        (xs, ys)
+    }
+
+    #[test]
+    fn dense_lines_keep_each_columns_extremes() {
+        let (xs, ys) = wave(100_000);
+        let scale = Scale::linear((0.0, 99_999.0), (0.0, 1.0));
+        let (dx, dy) = decimate_line(&xs, &ys, &scale, 200).unwrap();
+        assert!(dx.len() <= 4 * 201, "{}", dx.len());
+        assert!(dx.is_sorted());
+        let max = |v: &[f64]| v.iter().copied().fold(f64::MIN, f64::max);
+        let min = |v: &[f64]| v.iter().copied().fold(f64::MAX, f64::min);
+        assert_eq!(max(&dy), max(&ys));
+        assert_eq!(min(&dy), min(&ys));
+        assert_eq!((dx[0], *dx.last().unwrap()), (0.0, 99_999.0));
+    }
+
+    #[test]
+    fn zooming_in_returns_the_visible_points_in_full() {
+        let (xs, ys) = wave(100_000);
+        let scale = Scale::linear((500.0, 600.0), (0.0, 1.0));
+        let (dx, dy) = decimate_line(&xs, &ys, &scale, 200).unwrap();
+        assert_eq!(dx, xs[499..602]);
+        assert_eq!(dy, ys[499..602]);
+    }
+
+    #[test]
+    fn gaps_survive_and_unsorted_or_short_lines_are_left_alone() {
+        let (xs, mut ys) = wave(10_000);
+        ys[5_000] = f64::NAN;
+        let scale = Scale::linear((0.0, 9_999.0), (0.0, 1.0));
+        let (_, dy) = decimate_line(&xs, &ys, &scale, 100).unwrap();
+        assert_eq!(dy.iter().filter(|y| y.is_nan()).count(), 1);
+
+        assert!(decimate_line(&xs[..300], &ys[..300], &scale, 100).is_none());
+        let mut shuffled = xs.clone();
+        shuffled.swap(10, 20);
+        assert!(decimate_line(&shuffled, &ys, &scale, 100).is_none());
+    }
+
+    #[test]
+    fn missing_x_values_are_gaps_rather_than_disorder() {
+        let (mut xs, ys) = wave(100_000);
+        xs[300] = f64::NAN;
+        xs[50_000] = f64::NAN;
+        let full = Scale::linear((0.0, 99_999.0), (0.0, 1.0));
+        let (dx, _) = decimate_line(&xs, &ys, &full, 200).unwrap();
+        assert!(dx.len() <= 4 * 201 + 2, "{}", dx.len());
+        assert_eq!(dx.iter().filter(|x| x.is_nan()).count(), 2);
+
+        let zoomed = Scale::linear((60_000.0, 60_100.0), (0.0, 1.0));
+        let (dx, _) = decimate_line(&xs, &ys, &zoomed, 200).unwrap();
+        assert_eq!(dx, xs[59_999..60_102]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Rendered images and their PNG encoding, shared by the raster backends.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Why an [`Image`] could not be encoded or saved.
+#[derive(Debug, thiserror::Error)]
+pub enum ImageError {
+    #[error("only .png output is currently supported")]
+    NotPng,
+    #[error("failed to create output image at {}", path.display())]
+    Create {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to encode PNG image data")]
+    Encode(#[from] png::EncodingError),
+}
+
+/// An 8-bit sRGB RGBA image, rows top to bottom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// `width * height * 4` bytes.
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// The image encoded as a PNG file.
+    pub fn encode_png(&self) -> Result<Vec<u8>, ImageError> {
+        let mut png = Vec::new();
+        self.write_png(&mut png)?;
+        Ok(png)
+    }
+
+    /// Write the image to `path` as a PNG.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), ImageError> {
+        let path = path.as_ref();
+        check_png_path(path)?;
+        let file = File::create(path).map_err(|source| ImageError::Create {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        self.write_png(BufWriter::new(file))
+    }
+
+    fn write_png(&self, writer: impl Write) -> Result<(), ImageError> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+}
+
+/// Fail unless `path` ends in `.png`, so callers can check before the work
+/// of rendering.
+pub fn check_png_path(path: &Path) -> Result<(), ImageError> {
+    if path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+    {
+        Ok(())
+    } else {
+        Err(ImageError::NotPng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_encode_to_the_pixels_they_hold() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![0, 0, 0, 255, 255, 128, 0, 64],
+        };
+
+        let png = image.encode_png().unwrap();
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut decoded).unwrap();
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(decoded, image.pixels);
+
+        assert!(check_png_path(Path::new("plot.PNG")).is_ok());
+        assert!(image.save_png("plot.jpg").is_err());
+        assert!(image.save_png("plot").is_err());
+    }
+}
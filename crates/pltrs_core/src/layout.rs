@@ -1,6 +1,7 @@
 use crate::scale::Scale;
 use crate::scene::{Axes, Figure, Rect, Size};
 
+#[derive(Clone, Copy, Debug)]
 pub struct LayoutParams {
     pub left: f32,
     pub right: f32,
@@ -42,3 +43,88 @@ pub fn single_axes(size: Size, xlim: (f64, f64), ylim: (f64, f64)) -> (Figure, u
     let idx = fig.axes.len() - 1;
     (fig, idx)
 }
+
+/// Margins around and gaps between the cells of a subplot grid, in pixels.
+///
+/// The gaps leave room for each cell's tick labels, axis labels and title.
+#[derive(Clone, Copy, Debug)]
+pub struct GridParams {
+    pub margins: LayoutParams,
+    pub wspace: f32,
+    pub hspace: f32,
+}
+
+impl Default for GridParams {
+    fn default() -> Self {
+        Self {
+            margins: LayoutParams {
+                left: 130.0,
+                right: 30.0,
+                top: 70.0,
+                bottom: 90.0,
+            },
+            wspace: 140.0,
+            hspace: 150.0,
+        }
+    }
+}
+
+/// Plot rectangles for a `rows` × `cols` grid, row-major from the top-left
+/// cell. Cells shrink to nothing rather than overlap when the figure is too
+/// small for the requested margins.
+pub fn grid_rects(size: Size, rows: usize, cols: usize, params: &GridParams) -> Vec<Rect> {
+    let rows = rows.max(1);
+    let cols = cols.max(1);
+    let w = size.width as f32;
+    let h = size.height as f32;
+    let margins = &params.margins;
+    let cell_w = ((w - margins.left - margins.right - params.wspace * (cols - 1) as f32)
+        / cols as f32)
+        .max(0.0);
+    let cell_h = ((h - margins.top - margins.bottom - params.hspace * (rows - 1) as f32)
+        / rows as f32)
+        .max(0.0);
+
+    (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            let x = margins.left + col as f32 * (cell_w + params.wspace);
+            let top = margins.top + row as f32 * (cell_h + params.hspace);
+            Rect {
+                x: x / w,
+                y: (h - top - cell_h) / h,
+                w: cell_w / w,
+                h: cell_h / h,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_rects_are_row_major_from_the_top_left() {
+        let size = Size {
+            width: 1000,
+            height: 800,
+            dpi: 1.0,
+        };
+        let params = GridParams {
+            margins: LayoutParams {
+                left: 100.0,
+                right: 100.0,
+                top: 100.0,
+                bottom: 100.0,
+            },
+            wspace: 200.0,
+            hspace: 200.0,
+        };
+        let rects = grid_rects(size, 2, 2, &params);
+        assert_eq!(rects.len(), 4);
+        assert_eq!((rects[0].x, rects[0].w), (0.1, 0.3));
+        assert_eq!((rects[0].y, rects[0].h), (0.625, 0.25));
+        assert_eq!((rects[3].x, rects[3].y), (0.6, 0.125));
+    }
+}
@@ -0,0 +1,499 @@
+//! Legend boxes listing the labelled series of a plot.
+//!
+//! Sizes are given in pixels and converted against the figure size, so a
+//! legend looks the same in a single plot and in a subplot cell.
+
+use crate::{
+    plot::{format_tick, generate_ticks, PlotSeries, TICK_LABEL_SIZE},
+    scale::Scale,
+    scene::{Axes, Color, Line, LineCap, Node, Patch, Rect, Scatter, Size, Text},
+};
+
+const PADDING_PX: f32 = 8.0;
+const ROW_PX: f32 = 24.0;
+const SWATCH_PX: f32 = 28.0;
+const GAP_PX: f32 = 8.0;
+const INSET_PX: f32 = 10.0;
+const TEXT_COLOR: Color = Color {
+    r: 0.08,
+    g: 0.1,
+    b: 0.14,
+    a: 1.0,
+};
+/// Opacity multiplier for the entries of hidden series.
+const HIDDEN_ALPHA: f32 = 0.3;
+
+/// Where a legend is placed relative to the plot area.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LegendLoc {
+    /// The inside corner covering the fewest data points.
+    #[default]
+    Best,
+    UpperRight,
+    UpperLeft,
+    LowerLeft,
+    LowerRight,
+    /// Right of the plot area, aligned with its top edge.
+    OutsideRight,
+}
+
+/// Where a legend ended up and which series each row stands for.
+#[derive(Clone, Debug)]
+pub struct LegendLayout {
+    /// The legend box in figure coordinates.
+    pub rect: Rect,
+    /// Height of one row in figure coordinates.
+    pub row_height: f32,
+    /// Series index per row, counting primary series first and then those
+    /// on the secondary axis.
+    pub entries: Vec<usize>,
+}
+
+impl LegendLayout {
+    /// The series whose row contains the figure-space point `(x, y)`.
+    pub fn entry_at(&self, x: f32, y: f32) -> Option<usize> {
+        let rect = self.rect;
+        if x < rect.x || x > rect.x + rect.w || y < rect.y || y > rect.y + rect.h {
+            return None;
+        }
+        let top = rect.y + rect.h - self.row_height * PADDING_PX / ROW_PX;
+        let row = ((top - y) / self.row_height).floor();
+        if row < 0.0 {
+            return None;
+        }
+        self.entries.get(row as usize).copied()
+    }
+}
+
+/// Maps values onto marker sizes in pixels, for bubble charts.
+#[derive(Clone, Copy, Debug)]
+pub struct SizeMapping {
+    /// Values mapped to the smallest and largest size.
+    pub range: (f64, f64),
+    /// Smallest and largest marker size in pixels.
+    pub px: (f32, f32),
+    /// Interpolate marker area instead of diameter, so area is proportional
+    /// to the value.
+    pub area: bool,
+}
+
+impl SizeMapping {
+    /// Marker size for `value`; non-finite values get size zero and are not
+    /// drawn.
+    pub fn size(&self, value: f64) -> f32 {
+        if !value.is_finite() {
+            return 0.0;
+        }
+        let (lo, hi) = self.range;
+        let t = if hi != lo {
+            ((value - lo) / (hi - lo)).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        };
+        let (min, max) = self.px;
+        if self.area {
+            (min * min + (max * max - min * min) * t).sqrt()
+        } else {
+            min + (max - min) * t
+        }
+    }
+}
+
+/// A legend of sample marker sizes and the values they stand for.
+#[derive(Clone, Debug)]
+pub struct SizeLegend {
+    pub mapping: SizeMapping,
+    pub loc: LegendLoc,
+    pub label: Option<String>,
+    pub color: Color,
+}
+
+impl SizeLegend {
+    /// Up to four round values inside the mapped range.
+    pub fn values(&self) -> Vec<f64> {
+        const MAX_ENTRIES: usize = 4;
+        let (lo, hi) = self.mapping.range;
+        let (lo, hi) = (lo.min(hi), lo.max(hi));
+        let ticks: Vec<f64> = generate_ticks(&Scale::linear((lo, hi), (0.0, 1.0)), MAX_ENTRIES + 1)
+            .into_iter()
+            .filter(|&tick| tick >= lo && tick <= hi && self.mapping.size(tick) > 0.0)
+            .collect();
+        if ticks.is_empty() {
+            let mut values = vec![lo, hi];
+            values.dedup();
+            return values;
+        }
+        let stride = ticks.len().div_ceil(MAX_ENTRIES).max(1);
+        ticks.into_iter().rev().step_by(stride).rev().collect()
+    }
+
+    /// Legend box size in pixels.
+    pub fn size_px(&self) -> (f32, f32) {
+        let values = self.values();
+        let scale = Scale::linear(self.mapping.range, (0.0, 1.0));
+        let widest_text = values
+            .iter()
+            .map(|&value| text_width_px(&format_tick(value, &scale)))
+            .fold(0.0_f32, f32::max);
+        let widest_marker = values
+            .iter()
+            .map(|&value| self.mapping.size(value))
+            .fold(SWATCH_PX, f32::max);
+        let title = self.label.as_deref().map_or(0.0, text_width_px);
+        let rows: f32 = values
+            .iter()
+            .map(|&value| size_row_px(self.mapping.size(value)))
+            .sum();
+        (
+            2.0 * PADDING_PX + (widest_marker + GAP_PX + widest_text).max(title),
+            2.0 * PADDING_PX + rows + if self.label.is_some() { ROW_PX } else { 0.0 },
+        )
+    }
+}
+
+fn size_row_px(marker_px: f32) -> f32 {
+    ROW_PX.max(marker_px + 6.0)
+}
+
+/// Legend box size in pixels for the given labels.
+pub fn legend_size_px<'a>(labels: impl IntoIterator<Item = &'a str>) -> (f32, f32) {
+    let (rows, widest) = labels
+        .into_iter()
+        .fold((0, 0.0_f32), |(rows, widest), label| {
+            (rows + 1, widest.max(text_width_px(label)))
+        });
+    (
+        2.0 * PADDING_PX + SWATCH_PX + GAP_PX + widest,
+        2.0 * PADDING_PX + rows as f32 * ROW_PX,
+    )
+}
+
+/// Height of one legend row in figure coordinates.
+pub(crate) fn legend_row_height(size: Size) -> f32 {
+    ROW_PX / size.height.max(1) as f32
+}
+
+/// Place a legend of `box_px` pixels for a plot occupying `plot_rect`.
+///
+/// `points` are data points in figure coordinates; `Best` picks the inside
+/// corner that covers the fewest of them, preferring the upper right, and
+/// avoids overlapping `taken`.
+pub(crate) fn place_legend(
+    loc: LegendLoc,
+    plot_rect: Rect,
+    size: Size,
+    box_px: (f32, f32),
+    points: &[[f32; 2]],
+    taken: Option<Rect>,
+) -> Rect {
+    let w = box_px.0 / size.width.max(1) as f32;
+    let h = box_px.1 / size.height.max(1) as f32;
+    let inset_x = INSET_PX / size.width.max(1) as f32;
+    let inset_y = INSET_PX / size.height.max(1) as f32;
+    let left = plot_rect.x + inset_x;
+    let right = plot_rect.x + plot_rect.w - inset_x - w;
+    let bottom = plot_rect.y + inset_y;
+    let top = plot_rect.y + plot_rect.h - inset_y - h;
+
+    let corner = |loc| {
+        let (x, y) = match loc {
+            LegendLoc::UpperLeft => (left, top),
+            LegendLoc::LowerLeft => (left, bottom),
+            LegendLoc::LowerRight => (right, bottom),
+            LegendLoc::OutsideRight => (
+                plot_rect.x + plot_rect.w + inset_x,
+                plot_rect.y + plot_rect.h - h,
+            ),
+            LegendLoc::UpperRight | LegendLoc::Best => (right, top),
+        };
+        Rect { x, y, w, h }
+    };
+
+    if loc != LegendLoc::Best {
+        return corner(loc);
+    }
+
+    [
+        LegendLoc::UpperRight,
+        LegendLoc::UpperLeft,
+        LegendLoc::LowerLeft,
+        LegendLoc::LowerRight,
+    ]
+    .into_iter()
+    .map(corner)
+    .min_by_key(|rect| {
+        let overlaps_taken = taken.is_some_and(|taken| {
+            rect.x < taken.x + taken.w
+                && taken.x < rect.x + rect.w
+                && rect.y < taken.y + taken.h
+                && taken.y < rect.y + rect.h
+        });
+        let covered = points
+            .iter()
+            .filter(|[x, y]| {
+                *x >= rect.x && *x <= rect.x + rect.w && *y >= rect.y && *y <= rect.y + rect.h
+            })
+            .count();
+        (overlaps_taken, covered)
+    })
+    .unwrap_or_else(|| corner(LegendLoc::UpperRight))
+}
+
+/// Build an axes spanning the figure that draws the legend box, one swatch
+/// per row and the labels. Rows flagged as hidden are drawn faded.
+pub(crate) fn legend_axes(
+    layout: &LegendLayout,
+    rows: &[(&PlotSeries, &str, bool)],
+    size: Size,
+) -> Axes {
+    let rect = layout.rect;
+    let mut axes = legend_box(rect, size);
+    let px_w = 1.0 / size.width.max(1) as f64;
+    let px_h = 1.0 / size.height.max(1) as f64;
+    let x0 = rect.x as f64;
+    let y1 = (rect.y + rect.h) as f64;
+
+    let swatch_x0 = x0 + PADDING_PX as f64 * px_w;
+    let swatch_x1 = swatch_x0 + SWATCH_PX as f64 * px_w;
+    for (row, &(series, label, hidden)) in rows.iter().enumerate() {
+        let fade = |color: Color| Color {
+            a: if hidden {
+                color.a * HIDDEN_ALPHA
+            } else {
+                color.a
+            },
+            ..color
+        };
+        let center_y = y1 - (PADDING_PX as f64 + (row as f64 + 0.5) * ROW_PX as f64) * px_h;
+
+        match series {
+            PlotSeries::Line(line) => {
+                axes.add(Node::Line(Line {
+                    xs: vec![swatch_x0, swatch_x1],
+                    ys: vec![center_y, center_y],
+                    color: fade(line.color),
+                    width: line.width.clamp(2.0, 8.0),
+                    style: line.style.clone(),
+                    join: line.join,
+                    cap: LineCap::Butt,
+                    miter_limit: line.miter_limit,
+                    label: None,
+                }));
+            }
+            PlotSeries::Scatter(scatter) => {
+                axes.add(Node::Scatter(Scatter {
+                    xs: vec![(swatch_x0 + swatch_x1) * 0.5],
+                    ys: vec![center_y],
+                    color: fade(scatter.color),
+                    size: scatter.size.min(14.0),
+                    marker: scatter.marker,
+                    colors: None,
+                    sizes: None,
+                    edge_color: fade(scatter.edge_color),
+                    edge_width: scatter.edge_width,
+                    label: None,
+                }));
+            }
+            PlotSeries::Bar(bar) => {
+                axes.add(Node::Patch(Patch {
+                    x0: swatch_x0,
+                    y0: center_y - 6.0 * px_h,
+                    x1: swatch_x1,
+                    y1: center_y + 6.0 * px_h,
+                    color: fade(bar.color),
+                }));
+            }
+        }
+
+        axes.add(Node::Text(Text {
+            content: label.to_string(),
+            x: swatch_x1 + GAP_PX as f64 * px_w,
+            // Text is positioned by its top edge.
+            y: center_y + (TICK_LABEL_SIZE as f64 * 0.5) * px_h,
+            color: fade(TEXT_COLOR),
+            size: TICK_LABEL_SIZE,
+        }));
+    }
+    axes
+}
+
+/// Build an axes spanning the figure that draws a size legend in `rect`: an
+/// optional title and one sample marker per value, largest last.
+pub(crate) fn size_legend_axes(legend: &SizeLegend, rect: Rect, size: Size) -> Axes {
+    let mut axes = legend_box(rect, size);
+    let px_w = 1.0 / size.width.max(1) as f64;
+    let px_h = 1.0 / size.height.max(1) as f64;
+    let x0 = rect.x as f64 + PADDING_PX as f64 * px_w;
+    let mut top = (rect.y + rect.h) as f64 - PADDING_PX as f64 * px_h;
+
+    if let Some(label) = &legend.label {
+        axes.add(Node::Text(Text {
+            content: label.clone(),
+            x: x0,
+            y: top - 4.0 * px_h,
+            color: TEXT_COLOR,
+            size: TICK_LABEL_SIZE,
+        }));
+        top -= ROW_PX as f64 * px_h;
+    }
+
+    let values = legend.values();
+    let marker_column = values
+        .iter()
+        .map(|&value| legend.mapping.size(value))
+        .fold(SWATCH_PX, f32::max) as f64;
+    let scale = Scale::linear(legend.mapping.range, (0.0, 1.0));
+    for value in values {
+        let marker_px = legend.mapping.size(value);
+        let row_h = size_row_px(marker_px) as f64 * px_h;
+        let center_y = top - row_h * 0.5;
+        axes.add(Node::Scatter(Scatter {
+            xs: vec![x0 + marker_column * 0.5 * px_w],
+            ys: vec![center_y],
+            color: legend.color,
+            size: marker_px,
+            marker: crate::scene::Marker::Circle,
+            colors: None,
+            sizes: None,
+            edge_color: legend.color,
+            edge_width: 0.0,
+            label: None,
+        }));
+        axes.add(Node::Text(Text {
+            content: format_tick(value, &scale),
+            x: x0 + (marker_column + GAP_PX as f64) * px_w,
+            y: center_y + (TICK_LABEL_SIZE as f64 * 0.5) * px_h,
+            color: TEXT_COLOR,
+            size: TICK_LABEL_SIZE,
+        }));
+        top -= row_h;
+    }
+    axes
+}
+
+/// A figure-spanning axes holding the bordered, filled box `rect`.
+fn legend_box(rect: Rect, size: Size) -> Axes {
+    let mut axes = Axes::new(
+        Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 1.0,
+            h: 1.0,
+        },
+        Scale::linear((0.0, 1.0), (0.0, 1.0)),
+        Scale::linear((0.0, 1.0), (0.0, 1.0)),
+    );
+    let px_w = 1.0 / size.width.max(1) as f64;
+    let px_h = 1.0 / size.height.max(1) as f64;
+    let (x0, y0) = (rect.x as f64, rect.y as f64);
+    let (x1, y1) = ((rect.x + rect.w) as f64, (rect.y + rect.h) as f64);
+
+    // Border, then the fill inset by one pixel.
+    axes.add(Node::Patch(Patch {
+        x0,
+        y0,
+        x1,
+        y1,
+        color: Color {
+            r: 0.7,
+            g: 0.72,
+            b: 0.76,
+            a: 1.0,
+        },
+    }));
+    axes.add(Node::Patch(Patch {
+        x0: x0 + px_w,
+        y0: y0 + px_h,
+        x1: x1 - px_w,
+        y1: y1 - px_h,
+        color: Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 0.92,
+        },
+    }));
+    axes
+}
+
+fn text_width_px(text: &str) -> f32 {
+    text.chars().count() as f32 * TICK_LABEL_SIZE * 0.38
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Size = Size {
+        width: 1000,
+        height: 1000,
+        dpi: 1.0,
+    };
+    const PLOT: Rect = Rect {
+        x: 0.1,
+        y: 0.1,
+        w: 0.8,
+        h: 0.8,
+    };
+
+    #[test]
+    fn best_location_avoids_crowded_corners() {
+        let crowded_upper_right = vec![[0.85, 0.85]; 5];
+        let rect = place_legend(
+            LegendLoc::Best,
+            PLOT,
+            SIZE,
+            (100.0, 60.0),
+            &crowded_upper_right,
+            None,
+        );
+        assert!((rect.x - 0.11).abs() < 1e-6 && (rect.y - 0.83).abs() < 1e-6);
+
+        let fixed = place_legend(LegendLoc::LowerRight, PLOT, SIZE, (100.0, 60.0), &[], None);
+        assert!((fixed.x - 0.79).abs() < 1e-6 && (fixed.y - 0.11).abs() < 1e-6);
+    }
+
+    #[test]
+    fn entry_at_maps_rows_to_series() {
+        let (w, h) = legend_size_px(["cpu", "memory"]);
+        assert_eq!(h, 2.0 * PADDING_PX + 2.0 * ROW_PX);
+        let layout = LegendLayout {
+            rect: Rect {
+                x: 0.5,
+                y: 0.5,
+                w: w / 1000.0,
+                h: h / 1000.0,
+            },
+            row_height: ROW_PX / 1000.0,
+            entries: vec![3, 7],
+        };
+        let top = 0.5 + h / 1000.0;
+        assert_eq!(layout.entry_at(0.51, top - 0.015), Some(3));
+        assert_eq!(layout.entry_at(0.51, top - 0.04), Some(7));
+        assert_eq!(layout.entry_at(0.4, top - 0.015), None);
+    }
+
+    #[test]
+    fn area_sizes_grow_with_the_square_root() {
+        let mapping = SizeMapping {
+            range: (0.0, 100.0),
+            px: (0.0, 40.0),
+            area: true,
+        };
+        assert_eq!(mapping.size(25.0), 20.0);
+        assert_eq!(mapping.size(f64::NAN), 0.0);
+        let linear = SizeMapping {
+            area: false,
+            ..mapping
+        };
+        assert_eq!(linear.size(25.0), 10.0);
+
+        let legend = SizeLegend {
+            mapping,
+            loc: LegendLoc::LowerRight,
+            label: None,
+            color: Color::BLACK,
+        };
+        assert_eq!(legend.values(), vec![20.0, 60.0, 100.0]);
+    }
+}
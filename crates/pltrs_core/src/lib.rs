@@ -1,13 +1,23 @@
+pub mod colormap;
+pub mod decimate;
+pub mod image;
 pub mod layout;
+pub mod legend;
 pub mod plot;
 pub mod render;
 pub mod scale;
 pub mod scene;
 pub mod style;
+pub mod time;
 
+pub use colormap::*;
+pub use decimate::*;
+pub use image::*;
 pub use layout::*;
+pub use legend::*;
 pub use plot::*;
 pub use render::*;
 pub use scale::*;
 pub use scene::*;
 pub use style::*;
+pub use time::*;
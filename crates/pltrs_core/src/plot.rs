@@ -1,9 +1,32 @@
 use crate::{
-    scale::Scale,
-    scene::{Axes, Color, Figure, Line, Node, Rect, Scatter, Size, Text},
+    colormap::Colorbar,
+    decimate::decimate_line,
+    legend::{
+        legend_axes, legend_row_height, legend_size_px, place_legend, size_legend_axes,
+        LegendLayout, LegendLoc, SizeLegend,
+    },
+    render::{build_axes_batches, Batches},
+    scale::{Scale, ScaleKind},
+    scene::{
+        Axes, Color, Figure, Line, LineCap, LineJoin, LineStyle, Node, Patch, Rect, Scatter, Size,
+        Text, DEFAULT_MITER_LIMIT,
+    },
+    time::{choose_time_step, format_time_tick, time_ticks},
 };
 
 const DEFAULT_TICK_COUNT: usize = 6;
+/// Text size of tick labels, also used for legend entries.
+pub(crate) const TICK_LABEL_SIZE: f32 = 16.0;
+/// Longest line kept uploaded; longer ones are decimated for every view.
+const MAX_RETAINED_LINE_POINTS: usize = 1 << 22;
+const MAX_CATEGORY_LABELS: usize = 12;
+/// Room right of the plot area taken by secondary tick labels and label.
+pub const SECONDARY_AXIS_WIDTH_PX: f32 = 64.0;
+/// Room right of the plot area taken by a colorbar and its tick labels.
+pub const COLORBAR_WIDTH_PX: f32 = 88.0;
+const COLORBAR_GAP_PX: f32 = 16.0;
+const COLORBAR_BAR_PX: f32 = 16.0;
+const COLORBAR_STEPS: usize = 64;
 
 #[derive(Clone, Debug)]
 pub enum PlotSeries {
@@ -12,6 +35,85 @@ pub enum PlotSeries {
     Bar(crate::scene::Bar),
 }
 
+impl PlotSeries {
+    /// The legend label, if the series has a non-blank one.
+    pub fn label(&self) -> Option<&str> {
+        let label = match self {
+            PlotSeries::Line(line) => line.label.as_deref(),
+            PlotSeries::Scatter(scatter) => scatter.label.as_deref(),
+            PlotSeries::Bar(bar) => bar.label.as_deref(),
+        };
+        label.filter(|label| !label.trim().is_empty())
+    }
+
+    /// Whether a renderer can keep this series uploaded and place it with an
+    /// [`AxisTransform`](crate::scale::AxisTransform). Dashed lines and very
+    /// long lines are stroked again for every view instead, so dashes stay
+    /// even and long lines can be decimated.
+    pub fn retainable(&self) -> bool {
+        match self {
+            PlotSeries::Line(line) => {
+                line.style == LineStyle::Solid && line.xs.len() <= MAX_RETAINED_LINE_POINTS
+            }
+            PlotSeries::Scatter(_) | PlotSeries::Bar(_) => true,
+        }
+    }
+
+    /// Batches for this series with positions in the space where scales of
+    /// kind `x` and `y` are linear ([`ScaleKind::forward`] of the data), for
+    /// renderers that keep it uploaded and place it with the
+    /// [`AxisTransform`](crate::scale::AxisTransform)s of its
+    /// [`SeriesLayer`]'s axes. Positions stay `f64` so they can be stored
+    /// relative to the view.
+    pub fn data_batches(&self, x: ScaleKind, y: ScaleKind) -> Batches<[f64; 2]> {
+        let unit = Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 1.0,
+            h: 1.0,
+        };
+        // A domain from inverse(0) to inverse(1) makes `map` the forward
+        // transform itself.
+        let forward =
+            |kind: ScaleKind| kind.build((kind.inverse(0.0), kind.inverse(1.0)), (0.0, 1.0));
+        let mut axes = Axes::new(unit, forward(x), forward(y));
+        axes.add(match self {
+            PlotSeries::Line(line) => Node::Line(line.clone()),
+            PlotSeries::Scatter(scatter) => Node::Scatter(scatter.clone()),
+            PlotSeries::Bar(bar) => Node::Bar(bar.clone()),
+        });
+        build_axes_batches(&axes)
+    }
+
+    fn columns(&self) -> (&[f64], &[f64]) {
+        match self {
+            PlotSeries::Line(line) => (&line.xs, &line.ys),
+            PlotSeries::Scatter(scatter) => (&scatter.xs, &scatter.ys),
+            PlotSeries::Bar(bar) => (&bar.xs, &bar.heights),
+        }
+    }
+
+    /// Finite data points in plot order, used to refit axes.
+    fn points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let (xs, ys) = self.columns();
+        xs.iter()
+            .copied()
+            .zip(ys.iter().copied())
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+    }
+
+    /// About `max` evenly spaced finite points, picked without walking the
+    /// whole series, used to keep legends off the data.
+    fn sampled_points(&self, max: usize) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let (xs, ys) = self.columns();
+        let len = xs.len().min(ys.len());
+        (0..len)
+            .step_by(len.div_ceil(max).max(1))
+            .map(|i| (xs[i], ys[i]))
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PlotDefinition {
     pub size: Size,
@@ -19,18 +121,88 @@ pub struct PlotDefinition {
     pub plot_rect: Rect,
     pub base_xlim: (f64, f64),
     pub base_ylim: (f64, f64),
+    pub x_scale: ScaleKind,
+    pub y_scale: ScaleKind,
+    /// Slot names for a categorical x axis; category `i` sits at `x = i`.
+    pub x_categories: Vec<String>,
     pub title: Option<String>,
     pub x_label: Option<String>,
     pub y_label: Option<String>,
     pub grid: bool,
     pub annotations: Vec<Text>,
     pub series: Vec<PlotSeries>,
+    /// Series plotted against a right-hand y axis, if any.
+    pub y2: Option<SecondaryAxis>,
+    /// Where to draw a legend of the labelled series; `None` hides it.
+    pub legend: Option<LegendLoc>,
+    /// Refit the y range to the visible series whenever a legend entry is
+    /// toggled.
+    pub legend_autoscale: bool,
+    /// Colour scale drawn right of the plot area, if any.
+    pub colorbar: Option<Colorbar>,
+    /// Legend of marker sizes for bubble charts, if any.
+    pub size_legend: Option<SizeLegend>,
+}
+
+/// A second y axis drawn on the right of the plot area. Its series share the
+/// plot's x axis but have their own limits, scale and ticks.
+#[derive(Clone, Debug)]
+pub struct SecondaryAxis {
+    pub base_lim: (f64, f64),
+    pub scale: ScaleKind,
+    pub label: Option<String>,
+    pub series: Vec<PlotSeries>,
 }
 
+/// Several independent plots drawn into one figure, e.g. a subplot grid.
+///
+/// Every plot keeps its own `plot_rect`, limits and labels; its `size` should
+/// match the grid's so text is laid out against the whole figure.
+#[derive(Clone, Debug)]
+pub struct PlotGrid {
+    pub size: Size,
+    pub clear_color: Color,
+    pub plots: Vec<PlotDefinition>,
+    /// Groups of plot indices whose x limits stay equal. Zooming or panning
+    /// one member moves the others.
+    pub shared_x: Vec<Vec<usize>>,
+    /// Like `shared_x`, for y limits.
+    pub shared_y: Vec<Vec<usize>>,
+}
+
+/// A grid's figure for renderers that keep series data uploaded: the scene
+/// without those series, and where each of them goes.
+#[derive(Clone, Debug)]
+pub struct FigureLayers {
+    pub figure: Figure,
+    pub series: Vec<SeriesLayer>,
+}
+
+/// A series drawn from data the renderer keeps. It is placed by the scales
+/// of its axes and drawn with that axes' other content, as its scene node
+/// would have been.
 #[derive(Clone, Copy, Debug)]
+pub struct SeriesLayer {
+    /// Index into [`PlotGrid::plots`].
+    pub plot: usize,
+    /// Index into that plot's [`PlotDefinition::all_series`].
+    pub series: usize,
+    /// Index into the figure's axes.
+    pub axes: usize,
+}
+
+#[derive(Clone, Debug)]
 pub struct PlotView {
     pub xlim: (f64, f64),
     pub ylim: (f64, f64),
+    pub x_scale: ScaleKind,
+    pub y_scale: ScaleKind,
+    /// Limits of the secondary y axis; `None` when the plot has none.
+    pub y2lim: Option<(f64, f64)>,
+    pub y2_scale: ScaleKind,
+    /// Whether each series is hidden, counted as in
+    /// [`PlotDefinition::all_series`].
+    pub hidden: Vec<bool>,
 }
 
 impl PlotDefinition {
@@ -38,30 +210,68 @@ impl PlotDefinition {
         PlotView {
             xlim: self.base_xlim,
             ylim: self.base_ylim,
+            x_scale: self.x_scale,
+            y_scale: self.y_scale,
+            y2lim: self.y2.as_ref().map(|y2| y2.base_lim),
+            y2_scale: self.y2.as_ref().map(|y2| y2.scale).unwrap_or_default(),
+            hidden: vec![false; self.all_series().count()],
         }
     }
 
     pub fn build_figure(&self, view: &PlotView) -> Figure {
         let mut fig = Figure::new(self.size);
         fig.clear_color = self.clear_color;
+        self.add_to_figure(&mut fig, view);
+        fig
+    }
+
+    /// Add this plot's axes and its text overlay to `fig`.
+    pub fn add_to_figure(&self, fig: &mut Figure, view: &PlotView) {
+        self.add_layers(fig, view, None);
+    }
 
-        let xscale = Scale::linear(view.xlim, (0.0, 1.0));
-        let yscale = Scale::linear(view.ylim, (0.0, 1.0));
-        let mut plot_axes = Axes::new(self.plot_rect, xscale, yscale);
-        add_plot_frame(&mut plot_axes, view.xlim, view.ylim, self.grid);
+    /// Like [`PlotDefinition::add_to_figure`], but with `retain` set to this
+    /// plot's index in its grid, series a renderer can keep uploaded are
+    /// returned as layers instead of being added as scene nodes.
+    fn add_layers(
+        &self,
+        fig: &mut Figure,
+        view: &PlotView,
+        retain: Option<usize>,
+    ) -> Vec<SeriesLayer> {
+        let (xscale, yscale) = self.scales(view);
+        let mut plot_axes = Axes::new(self.plot_rect, xscale.clone(), yscale.clone());
+        add_plot_frame(&mut plot_axes, &xscale, &yscale, self.grid);
 
         for annotation in &self.annotations {
             plot_axes.add(Node::Text(annotation.clone()));
         }
 
-        for series in &self.series {
-            let node = match series {
-                PlotSeries::Line(line) => Node::Line(line.clone()),
-                PlotSeries::Scatter(scatter) => Node::Scatter(scatter.clone()),
-                PlotSeries::Bar(bar) => Node::Bar(bar.clone()),
-            };
-            plot_axes.add(node);
-        }
+        let columns = (self.plot_rect.w * self.size.pixels().0 as f32).ceil() as usize;
+        let plot_index = fig.axes.len();
+        let mut layers = add_series(
+            &mut plot_axes,
+            &self.series,
+            0,
+            view,
+            columns,
+            retain.map(|plot| (plot, plot_index)),
+        );
+
+        let secondary = self.y2.as_ref().zip(view.y2lim).map(|(y2, y2lim)| {
+            let y2scale = view.y2_scale.build(y2lim, (0.0, 1.0));
+            let mut axes = Axes::new(self.plot_rect, xscale.clone(), y2scale.clone());
+            add_secondary_ticks(&mut axes, &xscale, &y2scale);
+            layers.extend(add_series(
+                &mut axes,
+                &y2.series,
+                self.series.len(),
+                view,
+                columns,
+                retain.map(|plot| (plot, plot_index + 1)),
+            ));
+            (axes, y2scale, y2.label.as_deref())
+        });
 
         let mut overlay_axes = Axes::new(
             Rect {
@@ -76,8 +286,8 @@ impl PlotDefinition {
         add_tick_labels(
             &mut overlay_axes,
             self.plot_rect,
-            view.xlim,
-            view.ylim,
+            &xscale,
+            &yscale,
             self.size,
         );
         add_axis_labels(
@@ -90,8 +300,218 @@ impl PlotDefinition {
         );
 
         fig.add_axes(plot_axes);
+        if let Some((axes, y2scale, label)) = secondary {
+            add_secondary_labels(
+                &mut overlay_axes,
+                self.plot_rect,
+                &y2scale,
+                label,
+                self.size,
+            );
+            fig.add_axes(axes);
+        }
+        if let Some(colorbar) = &self.colorbar {
+            let offset_px = if self.y2.is_some() {
+                SECONDARY_AXIS_WIDTH_PX
+            } else {
+                0.0
+            };
+            add_colorbar(
+                &mut overlay_axes,
+                self.plot_rect,
+                colorbar,
+                offset_px,
+                self.size,
+            );
+        }
         fig.add_axes(overlay_axes);
-        fig
+
+        let layout = self.legend_layout(view);
+        if let Some(layout) = &layout {
+            let rows: Vec<(&PlotSeries, &str, bool)> = self
+                .all_series()
+                .enumerate()
+                .filter_map(|(idx, series)| Some((series, series.label()?, view.is_hidden(idx))))
+                .collect();
+            fig.add_axes(legend_axes(layout, &rows, self.size));
+        }
+        if let Some(size_legend) = &self.size_legend {
+            let rect = self.size_legend_rect(size_legend, view, layout.as_ref());
+            fig.add_axes(size_legend_axes(size_legend, rect, self.size));
+        }
+        layers
+    }
+
+    /// Where the size legend goes, keeping clear of the series legend.
+    fn size_legend_rect(
+        &self,
+        legend: &SizeLegend,
+        view: &PlotView,
+        taken: Option<&LegendLayout>,
+    ) -> Rect {
+        let box_px = legend.size_px();
+        let points = if legend.loc == LegendLoc::Best {
+            self.figure_points(view)
+        } else {
+            Vec::new()
+        };
+        let taken = taken.map(|layout| layout.rect);
+        let mut rect = place_legend(
+            legend.loc,
+            self.plot_rect,
+            self.size,
+            box_px,
+            &points,
+            taken,
+        );
+        if legend.loc == LegendLoc::OutsideRight {
+            rect.x += self.right_decorations_px() / self.size.width.max(1) as f32;
+            // Stack below a series legend that is outside as well.
+            if let Some(taken) = taken.filter(|taken| taken.x > self.plot_rect.x + self.plot_rect.w)
+            {
+                rect.y = taken.y - rect.h - legend_row_height(self.size) * 0.5;
+            }
+        }
+        rect
+    }
+
+    /// Pixels right of the plot area taken by a secondary axis and colorbar.
+    pub fn right_decorations_px(&self) -> f32 {
+        let mut px = 0.0;
+        if self.y2.is_some() {
+            px += SECONDARY_AXIS_WIDTH_PX;
+        }
+        if self.colorbar.is_some() {
+            px += COLORBAR_WIDTH_PX;
+        }
+        px
+    }
+
+    /// Primary series followed by those on the secondary axis.
+    pub fn all_series(&self) -> impl Iterator<Item = &PlotSeries> {
+        let secondary = self.y2.iter().flat_map(|y2| y2.series.iter());
+        self.series.iter().chain(secondary)
+    }
+
+    /// Where the legend goes in `view`, or `None` when it is turned off or no
+    /// series is labelled.
+    pub fn legend_layout(&self, view: &PlotView) -> Option<LegendLayout> {
+        let loc = self.legend?;
+        let entries: Vec<usize> = self
+            .all_series()
+            .enumerate()
+            .filter(|(_, series)| series.label().is_some())
+            .map(|(idx, _)| idx)
+            .collect();
+        if entries.is_empty() {
+            return None;
+        }
+
+        let box_px = legend_size_px(self.all_series().filter_map(PlotSeries::label));
+        let points = if loc == LegendLoc::Best {
+            self.figure_points(view)
+        } else {
+            Vec::new()
+        };
+        let mut rect = place_legend(loc, self.plot_rect, self.size, box_px, &points, None);
+        if loc == LegendLoc::OutsideRight {
+            rect.x += self.right_decorations_px() / self.size.width.max(1) as f32;
+        }
+        Some(LegendLayout {
+            rect,
+            row_height: legend_row_height(self.size),
+            entries,
+        })
+    }
+
+    /// Show or hide series `series` in `view`, refitting the y range to the
+    /// visible series when `legend_autoscale` is set. The range keeps 0 in
+    /// view while bars are shown, since they grow from it.
+    pub fn toggle_series(&self, view: &mut PlotView, series: usize) {
+        let Some(hidden) = view.hidden.get_mut(series) else {
+            return;
+        };
+        *hidden = !*hidden;
+        if !self.legend_autoscale {
+            return;
+        }
+
+        let current = &*view;
+        let visible = |offset: usize, series: &[PlotSeries]| {
+            let shown: Vec<&PlotSeries> = series
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| !current.is_hidden(offset + idx))
+                .map(|(_, series)| series)
+                .collect();
+            let baseline = shown
+                .iter()
+                .any(|series| matches!(series, PlotSeries::Bar(_)))
+                .then_some(0.0);
+            shown
+                .iter()
+                .flat_map(|series| series.points())
+                .filter(|(x, _)| within(*x, current.xlim))
+                .map(|(_, y)| y)
+                .chain(baseline)
+                .collect::<Vec<_>>()
+        };
+        let primary = visible(0, &self.series);
+        let secondary = self
+            .y2
+            .as_ref()
+            .map(|y2| visible(self.series.len(), &y2.series));
+
+        if let Some(ylim) = fit_range(view.ylim, view.y_scale, &primary) {
+            view.ylim = ylim;
+        }
+        if let (Some(y2lim), Some(ys)) = (view.y2lim, secondary) {
+            if let Some(fitted) = fit_range(y2lim, view.y2_scale, &ys) {
+                view.y2lim = Some(fitted);
+            }
+        }
+    }
+
+    /// A sample of every series' points in figure coordinates.
+    fn figure_points(&self, view: &PlotView) -> Vec<[f32; 2]> {
+        const MAX_POINTS_PER_SERIES: usize = 2_000;
+        let (xscale, yscale) = self.scales(view);
+        let y2scale = view
+            .y2lim
+            .map(|y2lim| view.y2_scale.build(y2lim, (0.0, 1.0)));
+        let secondary = self
+            .y2
+            .iter()
+            .flat_map(|y2| y2.series.iter())
+            .map(|series| (series, y2scale.as_ref().unwrap_or(&yscale)));
+        let rect = self.plot_rect;
+        let xscale = &xscale;
+
+        self.series
+            .iter()
+            .map(|series| (series, &yscale))
+            .chain(secondary)
+            .flat_map(|(series, yscale)| {
+                series
+                    .sampled_points(MAX_POINTS_PER_SERIES)
+                    .map(move |(x, y)| {
+                        [
+                            rect.x + rect.w * xscale.map(x) as f32,
+                            rect.y + rect.h * yscale.map(y) as f32,
+                        ]
+                    })
+            })
+            .collect()
+    }
+
+    fn scales(&self, view: &PlotView) -> (Scale, Scale) {
+        let xscale = match view.x_scale {
+            ScaleKind::Categorical => {
+                Scale::categorical(view.xlim, (0.0, 1.0), self.x_categories.clone())
+            }
+            kind => kind.build(view.xlim, (0.0, 1.0)),
+        };
+        (xscale, view.y_scale.build(view.ylim, (0.0, 1.0)))
     }
 
     pub fn plot_normalized_position(
@@ -123,38 +543,270 @@ impl PlotDefinition {
     }
 }
 
+impl PlotGrid {
+    /// A grid holding just `plot`.
+    pub fn single(plot: PlotDefinition) -> Self {
+        Self {
+            size: plot.size,
+            clear_color: plot.clear_color,
+            plots: vec![plot],
+            shared_x: Vec::new(),
+            shared_y: Vec::new(),
+        }
+    }
+
+    /// Draw the grid at `dpi` device pixels per logical pixel. The layout
+    /// stays the same; text, lines and markers get sharper.
+    pub fn set_dpi(&mut self, dpi: f32) {
+        self.size.dpi = dpi;
+        for plot in &mut self.plots {
+            plot.size.dpi = dpi;
+        }
+    }
+
+    /// Every plot's initial view. Shared axes start from the union of their
+    /// members' limits, oriented like the first member.
+    pub fn initial_views(&self) -> Vec<PlotView> {
+        let mut views: Vec<PlotView> = self
+            .plots
+            .iter()
+            .map(PlotDefinition::initial_view)
+            .collect();
+        for group in &self.shared_x {
+            if let Some(limits) = union_limits(
+                group
+                    .iter()
+                    .filter_map(|&idx| views.get(idx))
+                    .map(|view| view.xlim),
+            ) {
+                for &idx in group {
+                    if let Some(view) = views.get_mut(idx) {
+                        view.xlim = limits;
+                    }
+                }
+            }
+        }
+        for group in &self.shared_y {
+            if let Some(limits) = union_limits(
+                group
+                    .iter()
+                    .filter_map(|&idx| views.get(idx))
+                    .map(|view| view.ylim),
+            ) {
+                for &idx in group {
+                    if let Some(view) = views.get_mut(idx) {
+                        view.ylim = limits;
+                    }
+                }
+            }
+        }
+        views
+    }
+
+    /// Zoom plot `plot` around `anchor` and carry the result over to the
+    /// axes it shares.
+    pub fn zoom_at(&self, views: &mut [PlotView], plot: usize, anchor: (f64, f64), factor: f64) {
+        if let Some(view) = views.get_mut(plot) {
+            view.zoom_at(anchor, factor);
+            self.sync_shared(views, plot);
+        }
+    }
+
+    /// Pan plot `plot` and carry the result over to the axes it shares.
+    pub fn pan_by(&self, views: &mut [PlotView], plot: usize, delta: (f64, f64)) {
+        if let Some(view) = views.get_mut(plot) {
+            view.pan_by(delta);
+            self.sync_shared(views, plot);
+        }
+    }
+
+    fn sync_shared(&self, views: &mut [PlotView], source: usize) {
+        let Some(&PlotView { xlim, ylim, .. }) = views.get(source) else {
+            return;
+        };
+        for group in self.shared_x.iter().filter(|group| group.contains(&source)) {
+            for &idx in group {
+                if let Some(view) = views.get_mut(idx) {
+                    view.xlim = xlim;
+                }
+            }
+        }
+        for group in self.shared_y.iter().filter(|group| group.contains(&source)) {
+            for &idx in group {
+                if let Some(view) = views.get_mut(idx) {
+                    view.ylim = ylim;
+                }
+            }
+        }
+    }
+
+    /// Toggle the series whose legend entry sits under `cursor_px`. Returns
+    /// whether a series was toggled.
+    pub fn toggle_legend_entry(
+        &self,
+        views: &mut [PlotView],
+        cursor_px: (f64, f64),
+        window_size: (u32, u32),
+    ) -> bool {
+        let (width, height) = window_size;
+        if width == 0 || height == 0 {
+            return false;
+        }
+        let x = (cursor_px.0 / width as f64) as f32;
+        let y = (1.0 - cursor_px.1 / height as f64) as f32;
+
+        for (idx, plot) in self.plots.iter().enumerate() {
+            let Some(view) = views.get_mut(idx) else {
+                continue;
+            };
+            let Some(series) = plot
+                .legend_layout(view)
+                .and_then(|layout| layout.entry_at(x, y))
+            else {
+                continue;
+            };
+            plot.toggle_series(view, series);
+            self.sync_shared(views, idx);
+            return true;
+        }
+        false
+    }
+
+    /// Build the figure for `views`, which holds one view per plot.
+    pub fn build_figure(&self, views: &[PlotView]) -> Figure {
+        let mut fig = Figure::new(self.size);
+        fig.clear_color = self.clear_color;
+        for (plot, view) in self.plots.iter().zip(views) {
+            plot.add_to_figure(&mut fig, view);
+        }
+        fig
+    }
+
+    /// Like [`PlotGrid::build_figure`], but leaves out the series a renderer
+    /// can keep uploaded, so redrawing after a view change costs the same
+    /// however much data the plots hold.
+    pub fn build_layers(&self, views: &[PlotView]) -> FigureLayers {
+        let mut figure = Figure::new(self.size);
+        figure.clear_color = self.clear_color;
+        let mut series = Vec::new();
+        for (idx, (plot, view)) in self.plots.iter().zip(views).enumerate() {
+            series.extend(plot.add_layers(&mut figure, view, Some(idx)));
+        }
+        FigureLayers { figure, series }
+    }
+
+    /// The plot under the cursor, with the cursor position normalized to
+    /// that plot's area.
+    pub fn plot_at(
+        &self,
+        cursor_px: (f64, f64),
+        window_size: (u32, u32),
+    ) -> Option<(usize, (f64, f64))> {
+        self.plots.iter().enumerate().find_map(|(idx, plot)| {
+            plot.plot_normalized_position(cursor_px, window_size)
+                .map(|pos| (idx, pos))
+        })
+    }
+}
+
 impl PlotView {
+    /// Whether series `series` is hidden in this view.
+    pub fn is_hidden(&self, series: usize) -> bool {
+        self.hidden.get(series).copied().unwrap_or(false)
+    }
+
     pub fn zoom_at(&mut self, anchor: (f64, f64), factor: f64) {
         if !(factor.is_finite() && factor > 0.0) {
             return;
         }
 
-        let anchor_x = self.xlim.0 + (self.xlim.1 - self.xlim.0) * anchor.0;
-        let anchor_y = self.ylim.0 + (self.ylim.1 - self.ylim.0) * anchor.1;
-
-        self.xlim = zoom_range(self.xlim, anchor_x, factor);
-        self.ylim = zoom_range(self.ylim, anchor_y, factor);
+        self.xlim = zoom_range(self.xlim, self.x_scale, anchor.0, factor);
+        self.ylim = zoom_range(self.ylim, self.y_scale, anchor.1, factor);
+        self.y2lim = self
+            .y2lim
+            .map(|y2lim| zoom_range(y2lim, self.y2_scale, anchor.1, factor));
     }
 
+    /// Pan by a fraction of the visible span. On log axes the shift is
+    /// applied in log space, so panning is multiplicative.
     pub fn pan_by(&mut self, delta: (f64, f64)) {
-        let dx = delta.0 * (self.xlim.1 - self.xlim.0);
-        let dy = delta.1 * (self.ylim.1 - self.ylim.0);
-        self.xlim = (self.xlim.0 - dx, self.xlim.1 - dx);
-        self.ylim = (self.ylim.0 - dy, self.ylim.1 - dy);
+        self.xlim = pan_range(self.xlim, self.x_scale, delta.0);
+        self.ylim = pan_range(self.ylim, self.y_scale, delta.1);
+        self.y2lim = self
+            .y2lim
+            .map(|y2lim| pan_range(y2lim, self.y2_scale, delta.1));
     }
 }
 
-fn zoom_range(range: (f64, f64), anchor: f64, factor: f64) -> (f64, f64) {
-    let min = anchor - (anchor - range.0) * factor;
-    let max = anchor + (range.1 - anchor) * factor;
+/// The smallest range covering all of `limits`, decreasing if the first
+/// one is.
+pub fn union_limits(mut limits: impl Iterator<Item = (f64, f64)>) -> Option<(f64, f64)> {
+    let first = limits.next()?;
+    let (lo, hi) = limits.fold(
+        (first.0.min(first.1), first.0.max(first.1)),
+        |(lo, hi), (a, b)| (lo.min(a.min(b)), hi.max(a.max(b))),
+    );
+    Some(if first.0 > first.1 {
+        (hi, lo)
+    } else {
+        (lo, hi)
+    })
+}
+
+fn within(value: f64, range: (f64, f64)) -> bool {
+    value >= range.0.min(range.1) && value <= range.0.max(range.1)
+}
+
+/// The range of `values` padded by 5% in scale space, oriented like `range`.
+/// `None` when no value can be shown on the scale.
+fn fit_range(range: (f64, f64), kind: ScaleKind, values: &[f64]) -> Option<(f64, f64)> {
+    let (lo, hi) = values
+        .iter()
+        .map(|&value| kind.forward(value))
+        .filter(|value| value.is_finite())
+        .fold(None, |acc: Option<(f64, f64)>, value| {
+            Some(acc.map_or((value, value), |(lo, hi)| (lo.min(value), hi.max(value))))
+        })?;
+    let pad = if hi > lo { (hi - lo) * 0.05 } else { 0.5 };
+    let (lo, hi) = (kind.inverse(lo - pad), kind.inverse(hi + pad));
+    if !(lo.is_finite() && hi.is_finite()) {
+        return None;
+    }
+    Some(if range.0 > range.1 {
+        (hi, lo)
+    } else {
+        (lo, hi)
+    })
+}
+
+fn zoom_range(range: (f64, f64), kind: ScaleKind, anchor: f64, factor: f64) -> (f64, f64) {
+    let (t0, t1) = (kind.forward(range.0), kind.forward(range.1));
+    let anchor = t0 + (t1 - t0) * anchor;
+    let min = anchor - (anchor - t0) * factor;
+    let max = anchor + (t1 - anchor) * factor;
     if (max - min).abs() < f64::EPSILON {
+        return range;
+    }
+    let zoomed = (kind.inverse(min), kind.inverse(max));
+    if zoomed.0.is_finite() && zoomed.1.is_finite() {
+        zoomed
+    } else {
         range
+    }
+}
+
+fn pan_range(range: (f64, f64), kind: ScaleKind, delta: f64) -> (f64, f64) {
+    let (t0, t1) = (kind.forward(range.0), kind.forward(range.1));
+    let shift = delta * (t1 - t0);
+    let panned = (kind.inverse(t0 - shift), kind.inverse(t1 - shift));
+    if panned.0.is_finite() && panned.1.is_finite() {
+        panned
     } else {
-        (min, max)
+        range
     }
 }
 
-fn add_plot_frame(axes: &mut Axes, xlim: (f64, f64), ylim: (f64, f64), grid: bool) {
+fn add_plot_frame(axes: &mut Axes, xscale: &Scale, yscale: &Scale, grid: bool) {
     let frame_color = Color {
         r: 0.15,
         g: 0.18,
@@ -168,75 +820,135 @@ fn add_plot_frame(axes: &mut Axes, xlim: (f64, f64), ylim: (f64, f64), grid: boo
         a: 1.0,
     };
 
+    let xlim = xscale.domain();
+    let ylim = yscale.domain();
+
     add_segment(axes, [xlim.0, xlim.1], [ylim.0, ylim.0], frame_color, 2.0);
     add_segment(axes, [xlim.0, xlim.1], [ylim.1, ylim.1], frame_color, 2.0);
     add_segment(axes, [xlim.0, xlim.0], [ylim.0, ylim.1], frame_color, 2.0);
     add_segment(axes, [xlim.1, xlim.1], [ylim.0, ylim.1], frame_color, 2.0);
 
-    let x_ticks = generate_ticks(xlim, DEFAULT_TICK_COUNT);
-    let y_ticks = generate_ticks(ylim, DEFAULT_TICK_COUNT);
-    let x_tick_len = (ylim.1 - ylim.0) * 0.015;
-    let y_tick_len = (xlim.1 - xlim.0) * 0.015;
+    let x_ticks = generate_ticks(xscale, DEFAULT_TICK_COUNT);
+    let y_ticks = generate_ticks(yscale, DEFAULT_TICK_COUNT);
+    let x_tick_end = yscale.invert(0.015);
+    let y_tick_end = xscale.invert(0.015);
 
     for tick in x_ticks {
         if grid {
             add_segment(axes, [tick, tick], [ylim.0, ylim.1], grid_color, 1.0);
         }
-        add_segment(
-            axes,
-            [tick, tick],
-            [ylim.0, ylim.0 + x_tick_len],
-            frame_color,
-            1.5,
-        );
+        add_segment(axes, [tick, tick], [ylim.0, x_tick_end], frame_color, 1.5);
     }
 
     for tick in y_ticks {
         if grid {
             add_segment(axes, [xlim.0, xlim.1], [tick, tick], grid_color, 1.0);
         }
-        add_segment(
-            axes,
-            [xlim.0, xlim.0 + y_tick_len],
-            [tick, tick],
-            frame_color,
-            1.5,
-        );
+        add_segment(axes, [xlim.0, y_tick_end], [tick, tick], frame_color, 1.5);
     }
 }
 
-fn add_tick_labels(axes: &mut Axes, rect: Rect, xlim: (f64, f64), ylim: (f64, f64), size: Size) {
+/// Add the series not hidden in `view` to `axes`, decimating long lines to
+/// the `columns` pixel columns of the plot area.
+///
+/// `offset` is the index of the first series among all series of the plot.
+/// With `retain` set to the plot's index in its grid and the index `axes`
+/// will have in the figure, series a renderer can keep uploaded are returned
+/// as layers instead of added.
+fn add_series(
+    axes: &mut Axes,
+    series: &[PlotSeries],
+    offset: usize,
+    view: &PlotView,
+    columns: usize,
+    retain: Option<(usize, usize)>,
+) -> Vec<SeriesLayer> {
+    let mut layers = Vec::new();
+    for (idx, series) in series.iter().enumerate() {
+        if view.is_hidden(offset + idx) {
+            continue;
+        }
+        if let Some((plot, axes)) = retain.filter(|_| series.retainable()) {
+            layers.push(SeriesLayer {
+                plot,
+                series: offset + idx,
+                axes,
+            });
+            continue;
+        }
+        let node = match series {
+            PlotSeries::Line(line) => match decimate_line(&line.xs, &line.ys, &axes.x, columns) {
+                Some((xs, ys)) => Node::Line(Line {
+                    xs,
+                    ys,
+                    color: line.color,
+                    width: line.width,
+                    style: line.style.clone(),
+                    join: line.join,
+                    cap: line.cap,
+                    miter_limit: line.miter_limit,
+                    label: line.label.clone(),
+                }),
+                None => Node::Line(line.clone()),
+            },
+            PlotSeries::Scatter(scatter) => Node::Scatter(scatter.clone()),
+            PlotSeries::Bar(bar) => Node::Bar(bar.clone()),
+        };
+        axes.add(node);
+    }
+    layers
+}
+
+/// Tick marks on the right edge of the plot area for a secondary y axis.
+fn add_secondary_ticks(axes: &mut Axes, xscale: &Scale, yscale: &Scale) {
+    let frame_color = Color {
+        r: 0.15,
+        g: 0.18,
+        b: 0.22,
+        a: 1.0,
+    };
+    let x_edge = xscale.domain().1;
+    let tick_end = xscale.invert(0.985);
+    for tick in generate_ticks(yscale, DEFAULT_TICK_COUNT) {
+        add_segment(axes, [tick_end, x_edge], [tick, tick], frame_color, 1.5);
+    }
+}
+
+fn add_tick_labels(axes: &mut Axes, rect: Rect, xscale: &Scale, yscale: &Scale, size: Size) {
     let label_color = Color {
         r: 0.2,
         g: 0.22,
         b: 0.27,
         a: 1.0,
     };
-    let x_ticks = generate_ticks(xlim, DEFAULT_TICK_COUNT);
-    let y_ticks = generate_ticks(ylim, DEFAULT_TICK_COUNT);
+    let x_ticks = generate_ticks(xscale, DEFAULT_TICK_COUNT);
+    let y_ticks = generate_ticks(yscale, DEFAULT_TICK_COUNT);
 
     for tick in x_ticks {
-        let x = rect.x + rect.w * normalize_value(tick, xlim);
-        let text = format_tick(tick);
+        let x = rect.x + rect.w * xscale.map(tick) as f32;
+        let text = format_tick(tick, xscale);
         axes.add(Node::Text(Text {
             content: text.clone(),
-            x: centered_text_x(&text, 16.0, x as f64, size.width),
-            y: (rect.y - 0.065).max(0.02) as f64,
+            x: centered_text_x(&text, TICK_LABEL_SIZE, x as f64, size.width),
+            y: (rect.y - px_to_height(39.0, size)).max(0.02) as f64,
             color: label_color,
-            size: 16.0,
+            size: TICK_LABEL_SIZE,
         }));
     }
 
     for tick in y_ticks {
-        let y = rect.y + rect.h * normalize_value(tick, ylim);
-        let text = format_tick(tick);
-        let label_x = (rect.x - estimate_text_width(&text, 16.0, size.width) - 0.02).max(0.01);
+        let y = rect.y + rect.h * yscale.map(tick) as f32;
+        let text = format_tick(tick, yscale);
+        let label_x = (rect.x
+            - estimate_text_width(&text, TICK_LABEL_SIZE, size.width)
+            - px_to_width(16.0, size))
+        .max(0.01);
         axes.add(Node::Text(Text {
             content: text,
             x: label_x as f64,
-            y: (y - 0.015).max(0.01) as f64,
+            y: (y - px_to_height(9.0, size)).max(0.01) as f64,
             color: label_color,
-            size: 16.0,
+            size: TICK_LABEL_SIZE,
         }));
     }
 }
@@ -260,7 +972,7 @@ fn add_axis_labels(
         axes.add(Node::Text(Text {
             content: title.to_string(),
             x: centered_text_x(title, 24.0, (rect.x + rect.w * 0.5) as f64, size.width),
-            y: (rect.y + rect.h + 0.08).min(0.96) as f64,
+            y: (rect.y + rect.h + px_to_height(48.0, size)).min(0.96) as f64,
             color: label_color,
             size: 24.0,
         }));
@@ -270,7 +982,7 @@ fn add_axis_labels(
         axes.add(Node::Text(Text {
             content: label.to_string(),
             x: centered_text_x(label, 20.0, (rect.x + rect.w * 0.5) as f64, size.width),
-            y: (rect.y - 0.12).max(0.02) as f64,
+            y: (rect.y - px_to_height(72.0, size)).max(0.02) as f64,
             color: label_color,
             size: 20.0,
         }));
@@ -279,7 +991,7 @@ fn add_axis_labels(
     if let Some(label) = y_label.filter(|value| !value.trim().is_empty()) {
         axes.add(Node::Text(Text {
             content: label.to_string(),
-            x: 0.02,
+            x: (rect.x - px_to_width(112.0, size)).max(0.02) as f64,
             y: (rect.y + rect.h * 0.5) as f64,
             color: label_color,
             size: 20.0,
@@ -287,23 +999,127 @@ fn add_axis_labels(
     }
 }
 
+/// Tick labels and the axis label to the right of the plot area.
+fn add_secondary_labels(
+    axes: &mut Axes,
+    rect: Rect,
+    yscale: &Scale,
+    label: Option<&str>,
+    size: Size,
+) {
+    let label_color = Color {
+        r: 0.2,
+        g: 0.22,
+        b: 0.27,
+        a: 1.0,
+    };
+    let right = rect.x + rect.w;
+    let mut widest = 0.0_f32;
+    for tick in generate_ticks(yscale, DEFAULT_TICK_COUNT) {
+        let y = rect.y + rect.h * yscale.map(tick) as f32;
+        let text = format_tick(tick, yscale);
+        widest = widest.max(estimate_text_width(&text, TICK_LABEL_SIZE, size.width));
+        axes.add(Node::Text(Text {
+            content: text,
+            x: (right + px_to_width(12.0, size)) as f64,
+            y: (y - px_to_height(9.0, size)).max(0.01) as f64,
+            color: label_color,
+            size: TICK_LABEL_SIZE,
+        }));
+    }
+
+    if let Some(label) = label.filter(|value| !value.trim().is_empty()) {
+        axes.add(Node::Text(Text {
+            content: label.to_string(),
+            x: (right + widest + px_to_width(24.0, size)).min(0.98) as f64,
+            y: (rect.y + rect.h * 0.5) as f64,
+            color: Color {
+                r: 0.08,
+                g: 0.1,
+                b: 0.14,
+                a: 1.0,
+            },
+            size: 20.0,
+        }));
+    }
+}
+
+/// A vertical colour ramp right of `rect`, `offset_px` past its edge, with
+/// ticks for the mapped value range and the label on top.
+fn add_colorbar(axes: &mut Axes, rect: Rect, colorbar: &Colorbar, offset_px: f32, size: Size) {
+    let x0 = rect.x + rect.w + px_to_width(offset_px + COLORBAR_GAP_PX, size);
+    let x1 = x0 + px_to_width(COLORBAR_BAR_PX, size);
+    let step = rect.h as f64 / COLORBAR_STEPS as f64;
+    for idx in 0..COLORBAR_STEPS {
+        let y0 = rect.y as f64 + step * idx as f64;
+        axes.add(Node::Patch(Patch {
+            x0: x0 as f64,
+            y0,
+            // Overlap the next step slightly so no seams show.
+            y1: y0 + step * 1.05,
+            x1: x1 as f64,
+            color: colorbar
+                .mapping
+                .cmap
+                .sample((idx as f64 + 0.5) / COLORBAR_STEPS as f64),
+        }));
+    }
+
+    let label_color = Color {
+        r: 0.2,
+        g: 0.22,
+        b: 0.27,
+        a: 1.0,
+    };
+    let (lo, hi) = colorbar.mapping.range;
+    let scale = Scale::linear((lo, hi), (0.0, 1.0));
+    for tick in generate_ticks(&scale, DEFAULT_TICK_COUNT) {
+        let y = rect.y + rect.h * scale.map(tick) as f32;
+        add_segment(
+            axes,
+            [x1 as f64, (x1 + px_to_width(4.0, size)) as f64],
+            [y as f64, y as f64],
+            label_color,
+            1.0,
+        );
+        axes.add(Node::Text(Text {
+            content: format_tick(tick, &scale),
+            x: (x1 + px_to_width(7.0, size)) as f64,
+            y: (y - px_to_height(9.0, size)).max(0.01) as f64,
+            color: label_color,
+            size: 14.0,
+        }));
+    }
+
+    if let Some(label) = colorbar
+        .label
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+    {
+        axes.add(Node::Text(Text {
+            content: label.to_string(),
+            x: x0 as f64,
+            y: (rect.y + rect.h + px_to_height(26.0, size)).min(0.99) as f64,
+            color: label_color,
+            size: 16.0,
+        }));
+    }
+}
+
 fn add_segment(axes: &mut Axes, xs: [f64; 2], ys: [f64; 2], color: Color, width: f32) {
     axes.add(Node::Line(Line {
         xs: xs.to_vec(),
         ys: ys.to_vec(),
         color,
         width,
+        style: LineStyle::Solid,
+        join: LineJoin::Miter,
+        cap: LineCap::Butt,
+        miter_limit: DEFAULT_MITER_LIMIT,
+        label: None,
     }));
 }
 
-fn normalize_value(value: f64, limits: (f64, f64)) -> f32 {
-    let span = limits.1 - limits.0;
-    if span.abs() <= f64::EPSILON {
-        return 0.0;
-    }
-    ((value - limits.0) / span) as f32
-}
-
 fn nice_number(value: f64, round: bool) -> f64 {
     let exponent = value.log10().floor();
     let fraction = value / 10_f64.powf(exponent);
@@ -331,8 +1147,17 @@ fn nice_number(value: f64, round: bool) -> f64 {
     nice_fraction * 10_f64.powf(exponent)
 }
 
-fn generate_ticks(limits: (f64, f64), target_count: usize) -> Vec<f64> {
-    let (min, max) = limits;
+pub(crate) fn generate_ticks(scale: &Scale, target_count: usize) -> Vec<f64> {
+    match scale.kind() {
+        ScaleKind::Linear => generate_linear_ticks(scale.domain(), target_count),
+        ScaleKind::Time => time_ticks(scale.domain(), target_count),
+        ScaleKind::Categorical => generate_category_ticks(scale),
+        kind => generate_decade_ticks(scale.domain(), kind, target_count),
+    }
+}
+
+fn generate_linear_ticks(limits: (f64, f64), target_count: usize) -> Vec<f64> {
+    let (min, max) = (limits.0.min(limits.1), limits.0.max(limits.1));
     let range = (max - min).abs();
     if range <= f64::EPSILON {
         return vec![min];
@@ -360,7 +1185,83 @@ fn generate_ticks(limits: (f64, f64), target_count: usize) -> Vec<f64> {
     ticks
 }
 
-fn format_tick(value: f64) -> String {
+/// One tick per visible slot centre, thinned out so labels do not pile up
+/// when many categories are on screen.
+fn generate_category_ticks(scale: &Scale) -> Vec<f64> {
+    let Scale::Categorical(categorical) = scale else {
+        return Vec::new();
+    };
+    let (lo, hi) = (
+        scale.domain().0.min(scale.domain().1),
+        scale.domain().0.max(scale.domain().1),
+    );
+    let last_slot = categorical.categories.len() as f64 - 1.0;
+    let first = lo.ceil().max(0.0);
+    let last = hi.floor().min(last_slot);
+    if last < first {
+        return Vec::new();
+    }
+
+    let visible = (last - first) as usize + 1;
+    let stride = visible.div_ceil(MAX_CATEGORY_LABELS).max(1);
+    (0..visible)
+        .step_by(stride)
+        .map(|offset| first + offset as f64)
+        .collect()
+}
+
+/// Place ticks on whole units of the transformed space, which are powers of
+/// `base` (and zero for symlog). Ticks are thinned out when the view spans
+/// many decades, and views narrower than two decades fall back to linear
+/// ticks so a deep zoom still gets labels.
+fn generate_decade_ticks(limits: (f64, f64), kind: ScaleKind, target_count: usize) -> Vec<f64> {
+    let lo = kind.forward(limits.0.min(limits.1));
+    let hi = kind.forward(limits.0.max(limits.1));
+    let first = (lo - 1e-9).ceil() as i32;
+    let last = (hi + 1e-9).floor() as i32;
+    if last - first < 1 {
+        return generate_linear_ticks(limits, target_count);
+    }
+
+    let decades = (last - first + 1) as usize;
+    let stride = decades.div_ceil(target_count.max(1)).max(1) as i32;
+    (first..=last)
+        .filter(|unit| unit.rem_euclid(stride) == 0)
+        .map(|unit| match kind {
+            ScaleKind::Log { base } => base.powi(unit),
+            _ => kind.inverse(unit as f64),
+        })
+        .collect()
+}
+
+pub(crate) fn format_tick(value: f64, scale: &Scale) -> String {
+    match scale.kind() {
+        ScaleKind::Linear => format_linear_tick(value),
+        ScaleKind::Time => {
+            let (d0, d1) = scale.domain();
+            format_time_tick(value, choose_time_step(d1 - d0, DEFAULT_TICK_COUNT))
+        }
+        ScaleKind::Categorical => match scale {
+            Scale::Categorical(categorical) => categorical
+                .category_at(value)
+                .map(str::to_string)
+                .unwrap_or_default(),
+            _ => format_linear_tick(value),
+        },
+        ScaleKind::Log { base } => format_log_tick(value, base),
+        ScaleKind::SymLog { base, .. } => {
+            if value.abs() < 1e-12 {
+                "0".to_string()
+            } else if value < 0.0 {
+                format!("-{}", format_log_tick(-value, base))
+            } else {
+                format_log_tick(value, base)
+            }
+        }
+    }
+}
+
+fn format_linear_tick(value: f64) -> String {
     let rounded = value.round();
     if (value - rounded).abs() < 1e-9 {
         return format!("{}", rounded as i64);
@@ -383,6 +1284,47 @@ fn format_tick(value: f64) -> String {
     }
 }
 
+/// Label whole powers of `base` as `base^n` with a superscript exponent, and
+/// anything else the same way as a linear tick.
+fn format_log_tick(value: f64, base: f64) -> String {
+    if value <= 0.0 {
+        return format_linear_tick(value);
+    }
+
+    let exponent = value.log(base).round();
+    if (base.powf(exponent) - value).abs() > value * 1e-9 {
+        return format_linear_tick(value);
+    }
+
+    let base_text = if (base - std::f64::consts::E).abs() < 1e-12 {
+        "e".to_string()
+    } else {
+        format_linear_tick(base)
+    };
+    let exponent_text: String = format!("{}", exponent as i64)
+        .chars()
+        .map(|ch| match ch {
+            '-' => '\u{207B}',
+            '0' => '\u{2070}',
+            '1' => '\u{00B9}',
+            '2' => '\u{00B2}',
+            '3' => '\u{00B3}',
+            digit => char::from_u32(0x2070 + digit.to_digit(10).unwrap_or(0)).unwrap_or(digit),
+        })
+        .collect();
+    format!("{base_text}{exponent_text}")
+}
+
+// Text sits a fixed number of pixels from its plot area, so labels keep
+// their spacing when a plot is one cell of a larger figure.
+fn px_to_width(px: f32, size: Size) -> f32 {
+    px / size.width.max(1) as f32
+}
+
+fn px_to_height(px: f32, size: Size) -> f32 {
+    px / size.height.max(1) as f32
+}
+
 fn estimate_text_width(text: &str, size: f32, figure_width: u32) -> f32 {
     let width_px = text.chars().count() as f32 * size * 0.38;
     width_px / figure_width as f32
@@ -396,11 +1338,51 @@ fn centered_text_x(text: &str, size: f32, center_x: f64, figure_width: u32) -> f
 mod tests {
     use super::*;
 
+    /// An 800×600 plot of `series` on unit limits with nothing around it.
+    /// Tests override the fields they care about.
+    fn test_plot(series: Vec<PlotSeries>) -> PlotDefinition {
+        PlotDefinition {
+            size: Size {
+                width: 800,
+                height: 600,
+                dpi: 1.0,
+            },
+            clear_color: Color::WHITE,
+            plot_rect: Rect {
+                x: 0.1,
+                y: 0.1,
+                w: 0.8,
+                h: 0.8,
+            },
+            base_xlim: (0.0, 1.0),
+            base_ylim: (0.0, 1.0),
+            x_scale: ScaleKind::Linear,
+            y_scale: ScaleKind::Linear,
+            x_categories: Vec::new(),
+            title: None,
+            x_label: None,
+            y_label: None,
+            grid: false,
+            annotations: Vec::new(),
+            series,
+            y2: None,
+            legend: None,
+            legend_autoscale: false,
+            colorbar: None,
+            size_legend: None,
+        }
+    }
+
     #[test]
     fn plot_view_zoom_preserves_anchor() {
         let mut view = PlotView {
             xlim: (0.0, 10.0),
             ylim: (0.0, 20.0),
+            x_scale: ScaleKind::Linear,
+            y_scale: ScaleKind::Linear,
+            y2lim: None,
+            y2_scale: ScaleKind::Linear,
+            hidden: Vec::new(),
         };
         view.zoom_at((0.25, 0.5), 0.5);
         assert_eq!(view.xlim, (1.25, 6.25));
@@ -412,9 +1394,354 @@ mod tests {
         let mut view = PlotView {
             xlim: (0.0, 10.0),
             ylim: (0.0, 20.0),
+            x_scale: ScaleKind::Linear,
+            y_scale: ScaleKind::Linear,
+            y2lim: None,
+            y2_scale: ScaleKind::Linear,
+            hidden: Vec::new(),
         };
         view.pan_by((0.1, -0.25));
         assert_eq!(view.xlim, (-1.0, 9.0));
         assert_eq!(view.ylim, (5.0, 25.0));
     }
+
+    #[test]
+    fn plot_view_zoom_on_log_axis_is_multiplicative() {
+        let mut view = PlotView {
+            xlim: (1.0, 10_000.0),
+            ylim: (0.0, 1.0),
+            x_scale: ScaleKind::LOG10,
+            y_scale: ScaleKind::Linear,
+            y2lim: None,
+            y2_scale: ScaleKind::Linear,
+            hidden: Vec::new(),
+        };
+        view.zoom_at((0.5, 0.5), 0.5);
+        assert!((view.xlim.0 - 10.0).abs() < 1e-9);
+        assert!((view.xlim.1 - 1000.0).abs() < 1e-9);
+
+        view.pan_by((0.5, 0.0));
+        assert!((view.xlim.0 - 1.0).abs() < 1e-9);
+        assert!((view.xlim.1 - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shared_axes_start_from_the_union_and_move_together() {
+        let plot = |xlim, ylim| PlotDefinition {
+            base_xlim: xlim,
+            base_ylim: ylim,
+            ..test_plot(Vec::new())
+        };
+        let grid = PlotGrid {
+            size: Size {
+                width: 800,
+                height: 600,
+                dpi: 1.0,
+            },
+            clear_color: Color::WHITE,
+            plots: vec![
+                plot((0.0, 10.0), (0.0, 1.0)),
+                plot((5.0, 20.0), (0.0, 100.0)),
+            ],
+            shared_x: vec![vec![0, 1]],
+            shared_y: Vec::new(),
+        };
+
+        let mut views = grid.initial_views();
+        assert_eq!(views[0].xlim, (0.0, 20.0));
+        assert_eq!(views[1].xlim, (0.0, 20.0));
+        assert_eq!(views[1].ylim, (0.0, 100.0));
+
+        grid.zoom_at(&mut views, 1, (0.5, 0.5), 0.5);
+        assert_eq!(views[0].xlim, (5.0, 15.0));
+        assert_eq!(views[0].ylim, (0.0, 1.0));
+
+        grid.pan_by(&mut views, 0, (0.1, 0.0));
+        assert_eq!(views[1].xlim, (4.0, 14.0));
+    }
+
+    #[test]
+    fn secondary_axis_gets_its_own_ticks_and_follows_zoom() {
+        let plot = PlotDefinition {
+            plot_rect: Rect {
+                x: 0.1,
+                y: 0.1,
+                w: 0.7,
+                h: 0.8,
+            },
+            base_xlim: (0.0, 10.0),
+            base_ylim: (0.0, 1000.0),
+            y2: Some(SecondaryAxis {
+                base_lim: (0.0, 5.0),
+                scale: ScaleKind::Linear,
+                label: Some("error %".to_string()),
+                series: Vec::new(),
+            }),
+            ..test_plot(Vec::new())
+        };
+
+        let mut view = plot.initial_view();
+        let fig = plot.build_figure(&view);
+        assert_eq!(fig.axes.len(), 3);
+        assert_eq!(fig.axes[1].y.domain(), (0.0, 5.0));
+        let right_labels: Vec<&str> = fig.axes[2]
+            .children
+            .iter()
+            .filter_map(|node| match node {
+                Node::Text(text) if text.x > 0.8 => Some(text.content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(right_labels.contains(&"5") && right_labels.contains(&"error %"));
+
+        view.zoom_at((0.5, 0.0), 0.5);
+        assert_eq!(view.ylim, (0.0, 500.0));
+        assert_eq!(view.y2lim, Some((0.0, 2.5)));
+    }
+
+    #[test]
+    fn colorbar_sits_right_of_the_secondary_axis() {
+        let mut plot = PlotDefinition {
+            size: Size {
+                width: 1000,
+                height: 1000,
+                dpi: 1.0,
+            },
+            plot_rect: Rect {
+                x: 0.1,
+                y: 0.1,
+                w: 0.6,
+                h: 0.8,
+            },
+            colorbar: Some(Colorbar {
+                mapping: crate::colormap::ColorMapping {
+                    cmap: crate::colormap::Colormap::magma(),
+                    range: (0.0, 50.0),
+                },
+                label: None,
+            }),
+            ..test_plot(Vec::new())
+        };
+        let ramp_left = |plot: &PlotDefinition| {
+            let fig = plot.build_figure(&plot.initial_view());
+            fig.axes
+                .last()
+                .unwrap()
+                .children
+                .iter()
+                .find_map(|node| match node {
+                    Node::Patch(patch) => Some(patch.x0),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert!((ramp_left(&plot) - 0.716).abs() < 1e-6);
+
+        plot.y2 = Some(SecondaryAxis {
+            base_lim: (0.0, 1.0),
+            scale: ScaleKind::Linear,
+            label: None,
+            series: Vec::new(),
+        });
+        assert!((ramp_left(&plot) - 0.78).abs() < 1e-6);
+        assert_eq!(
+            plot.right_decorations_px(),
+            SECONDARY_AXIS_WIDTH_PX + COLORBAR_WIDTH_PX
+        );
+    }
+
+    #[test]
+    fn clicking_a_legend_entry_hides_its_series_and_refits_y() {
+        let line = |ys: Vec<f64>, label: &str| {
+            PlotSeries::Line(Line {
+                xs: vec![0.0, 1.0, 2.0],
+                ys,
+                color: Color::BLACK,
+                width: 2.0,
+                style: LineStyle::Solid,
+                join: LineJoin::default(),
+                cap: LineCap::default(),
+                miter_limit: DEFAULT_MITER_LIMIT,
+                label: Some(label.to_string()),
+            })
+        };
+        let plot = PlotDefinition {
+            size: Size {
+                width: 1000,
+                height: 1000,
+                dpi: 1.0,
+            },
+            base_xlim: (0.0, 2.0),
+            base_ylim: (0.0, 100.0),
+            legend: Some(LegendLoc::UpperLeft),
+            legend_autoscale: true,
+            ..test_plot(vec![
+                line(vec![0.0, 10.0, 0.0], "small"),
+                line(vec![0.0, 100.0, 50.0], "big"),
+            ])
+        };
+        let grid = PlotGrid::single(plot);
+        let mut views = grid.initial_views();
+
+        // The legend sits in the upper left corner, "big" is its second row.
+        let layout = grid.plots[0].legend_layout(&views[0]).unwrap();
+        let x_px = f64::from(layout.rect.x + 0.01) * 1000.0;
+        let y_px =
+            f64::from(1.0 - (layout.rect.y + layout.rect.h - layout.row_height * 1.5)) * 1000.0;
+        assert!(grid.toggle_legend_entry(&mut views, (x_px, y_px), (1000, 1000)));
+        assert!(views[0].is_hidden(1) && !views[0].is_hidden(0));
+        assert_eq!(views[0].ylim, (-0.5, 10.5));
+
+        let fig = grid.build_figure(&views);
+        let lines = fig.axes[0]
+            .children
+            .iter()
+            .filter(|node| matches!(node, Node::Line(line) if line.label.is_some()))
+            .count();
+        assert_eq!(lines, 1);
+
+        assert!(!grid.toggle_legend_entry(&mut views, (500.0, 500.0), (1000, 1000)));
+    }
+
+    #[test]
+    fn hiding_series_keeps_bars_on_their_baseline() {
+        let bars = PlotSeries::Bar(crate::scene::Bar {
+            xs: vec![0.0, 1.0],
+            heights: vec![50.0, 60.0],
+            width: 0.8,
+            color: Color::BLACK,
+            label: Some("bars".to_string()),
+        });
+        let line = PlotSeries::Line(Line {
+            xs: vec![0.0, 1.0],
+            ys: vec![100.0, 200.0],
+            color: Color::BLACK,
+            width: 2.0,
+            style: LineStyle::Solid,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: DEFAULT_MITER_LIMIT,
+            label: None,
+        });
+        // Enough lines to check hiding works past the first 64 series.
+        let mut series = vec![bars];
+        series.extend(std::iter::repeat_n(line, 70));
+        let plot = PlotDefinition {
+            base_xlim: (-1.0, 2.0),
+            base_ylim: (0.0, 210.0),
+            legend_autoscale: true,
+            ..test_plot(series)
+        };
+
+        let mut view = plot.initial_view();
+        for idx in 1..=70 {
+            plot.toggle_series(&mut view, idx);
+        }
+        assert!(view.is_hidden(70) && !view.is_hidden(0));
+        assert!((view.ylim.0 + 3.0).abs() < 1e-9 && (view.ylim.1 - 63.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn layers_leave_kept_series_to_the_renderer() {
+        let line = |style: LineStyle| {
+            PlotSeries::Line(Line {
+                xs: vec![0.0, 1.0, 2.0],
+                ys: vec![1.0, 3.0, 2.0],
+                color: Color::BLACK,
+                width: 2.0,
+                style,
+                join: LineJoin::default(),
+                cap: LineCap::default(),
+                miter_limit: DEFAULT_MITER_LIMIT,
+                label: None,
+            })
+        };
+        let plot = PlotDefinition {
+            base_xlim: (0.0, 2.0),
+            base_ylim: (0.0, 4.0),
+            ..test_plot(vec![
+                line(LineStyle::Solid),
+                line(LineStyle::Dashed),
+                line(LineStyle::Solid),
+            ])
+        };
+        let grid = PlotGrid::single(plot);
+        let mut views = grid.initial_views();
+        views[0].hidden[2] = true;
+
+        let layers = grid.build_layers(&views);
+        assert_eq!(layers.series.len(), 1);
+        let layer = layers.series[0];
+        assert_eq!((layer.plot, layer.series, layer.axes), (0, 0, 0));
+
+        let styles: Vec<LineStyle> = layers
+            .figure
+            .axes
+            .iter()
+            .flat_map(|axes| &axes.children)
+            .filter_map(|node| match node {
+                Node::Line(line) if line.label.is_none() && line.xs.len() == 3 => {
+                    Some(line.style.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(styles, vec![LineStyle::Dashed]);
+    }
+
+    #[test]
+    fn log_ticks_land_on_decades() {
+        let scale = Scale::log((0.5, 2000.0), (0.0, 1.0), 10.0);
+        assert_eq!(
+            generate_ticks(&scale, DEFAULT_TICK_COUNT),
+            vec![1.0, 10.0, 100.0, 1000.0]
+        );
+        assert_eq!(format_tick(1000.0, &scale), "10\u{00B3}");
+        assert_eq!(format_tick(0.01, &scale), "10\u{207B}\u{00B2}");
+    }
+
+    #[test]
+    fn symlog_ticks_are_symmetric_around_zero() {
+        let scale = Scale::symlog((-500.0, 500.0), (0.0, 1.0), 10.0, 1.0);
+        let ticks = generate_ticks(&scale, 7);
+        assert_eq!(ticks.len(), 7);
+        assert_eq!(ticks[3], 0.0);
+        assert!((ticks[0] + 100.0).abs() < 1e-9 && (ticks[6] - 100.0).abs() < 1e-9);
+        assert_eq!(format_tick(ticks[0], &scale), "-10\u{00B2}");
+    }
+
+    #[test]
+    fn categorical_ticks_sit_on_slot_centres() {
+        let categories = vec!["api".to_string(), "db".to_string(), "cache".to_string()];
+        let scale = Scale::categorical((-0.5, 2.5), (0.0, 1.0), categories);
+        let ticks = generate_ticks(&scale, DEFAULT_TICK_COUNT);
+        assert_eq!(ticks, vec![0.0, 1.0, 2.0]);
+        assert_eq!(format_tick(ticks[1], &scale), "db");
+
+        let zoomed = Scale::categorical((0.6, 2.5), (0.0, 1.0), vec![String::new(); 3]);
+        assert_eq!(generate_ticks(&zoomed, DEFAULT_TICK_COUNT), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn inverted_view_pans_and_ticks_like_normal_view() {
+        let mut view = PlotView {
+            xlim: (0.0, 10.0),
+            ylim: (20.0, 0.0),
+            x_scale: ScaleKind::Linear,
+            y_scale: ScaleKind::Linear,
+            y2lim: None,
+            y2_scale: ScaleKind::Linear,
+            hidden: Vec::new(),
+        };
+        view.pan_by((0.0, 0.25));
+        assert_eq!(view.ylim, (25.0, 5.0));
+        view.zoom_at((0.0, 0.0), 0.5);
+        assert_eq!(view.ylim, (25.0, 15.0));
+
+        let scale = Scale::linear((10.0, 0.0), (0.0, 1.0));
+        assert_eq!(
+            generate_ticks(&scale, DEFAULT_TICK_COUNT),
+            vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]
+        );
+    }
 }
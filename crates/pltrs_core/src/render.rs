@@ -1,4 +1,4 @@
-use crate::scene::{Color, Figure, Marker, Node};
+use crate::scene::{Axes, Color, Figure, LineCap, LineJoin, Marker, Node, Rect};
 
 /// Description of the render target (window or texture).
 pub struct RenderTargetDesc {
@@ -19,23 +19,70 @@ pub trait RenderBackend {
     fn resize(&mut self, width: u32, height: u32);
 }
 
-/// --- Batching System ---
+// --- Batching System ---
+
+/// How far a line may reach past its axes, in axes lengths, before it is
+/// cut. Vertices far outside a deeply zoomed view would otherwise lose the
+/// precision of the visible part when cast to `f32`.
+const LINE_REACH: f64 = 16.0;
+
+/// Positions batches can be built with. Figures are drawn from `[f32; 2]`
+/// figure coordinates; series kept uploaded use `[f64; 2]` so renderers can
+/// place them relative to the view without losing precision.
+pub trait BatchPosition: Copy {
+    /// Whether lines are cut to a band around their axes.
+    const CUT_LINES: bool;
+
+    fn from_f64(position: [f64; 2]) -> Self;
+}
+
+impl BatchPosition for [f32; 2] {
+    const CUT_LINES: bool = true;
+
+    fn from_f64([x, y]: [f64; 2]) -> Self {
+        [x as f32, y as f32]
+    }
+}
+
+impl BatchPosition for [f64; 2] {
+    const CUT_LINES: bool = false;
+
+    fn from_f64(position: [f64; 2]) -> Self {
+        position
+    }
+}
 
 /// A batch of lines to be rendered.
 #[derive(Debug)]
-pub struct LineBatch {
-    pub vertices: Vec<[f32; 2]>,
+pub struct LineBatch<P = [f32; 2]> {
+    /// Polyline vertices; NaN vertices split it into separate runs.
+    pub vertices: Vec<P>,
     pub color: Color,
     pub width: f32,
+    /// On/off dash lengths in pixels; empty for a solid line.
+    pub dashes: Vec<f32>,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest miter allowed, as a multiple of the line width.
+    pub miter_limit: f32,
+    /// Figure-space rectangle the batch is clipped to (its axes rect).
+    pub clip: Rect,
 }
 
 /// A batch of markers (scatter plot points) to be rendered.
 #[derive(Debug)]
-pub struct MarkerBatch {
-    pub positions: Vec<[f32; 2]>,
+pub struct MarkerBatch<P = [f32; 2]> {
+    pub positions: Vec<P>,
     pub color: Color,
+    /// Per-point colours used instead of `color`.
+    pub colors: Option<Vec<Color>>,
     pub size: f32,
+    /// Per-point marker sizes used instead of `size`.
+    pub sizes: Option<Vec<f32>>,
     pub marker: Marker,
+    pub edge_color: Color,
+    pub edge_width: f32,
+    pub clip: Rect,
 }
 
 /// A batch of text labels to be rendered.
@@ -49,115 +96,400 @@ pub struct TextBatch {
 
 /// A batch of filled triangles to be rendered in a single solid color.
 #[derive(Debug)]
-pub struct SolidBatch {
-    pub vertices: Vec<[f32; 2]>,
+pub struct SolidBatch<P = [f32; 2]> {
+    pub vertices: Vec<P>,
     pub color: Color,
+    pub clip: Rect,
 }
 
 /// Collection of renderable batches.
-#[derive(Debug, Default)]
-pub struct Batches {
-    pub lines: Vec<LineBatch>,
-    pub markers: Vec<MarkerBatch>,
+#[derive(Debug)]
+pub struct Batches<P = [f32; 2]> {
+    pub lines: Vec<LineBatch<P>>,
+    pub markers: Vec<MarkerBatch<P>>,
     pub texts: Vec<TextBatch>,
-    pub solids: Vec<SolidBatch>,
+    pub solids: Vec<SolidBatch<P>>,
+}
+
+impl<P> Default for Batches<P> {
+    fn default() -> Self {
+        Self {
+            lines: Vec::new(),
+            markers: Vec::new(),
+            texts: Vec::new(),
+            solids: Vec::new(),
+        }
+    }
 }
 
 /// Build renderable batches from the Scene Graph.
 pub fn build_batches(fig: &Figure) -> Batches {
     let mut batches = Batches::default();
-
     for axes in &fig.axes {
-        let axes_rect = axes.rect;
-
-        for node in &axes.children {
-            match node {
-                Node::Line(line) => {
-                    let mut vertices = Vec::with_capacity(line.xs.len());
-                    for (&x, &y) in line.xs.iter().zip(&line.ys) {
-                        // Step 1: Map from data space to normalized axes space ([0, 1])
-                        let x_norm_axes = axes.x.map(x) as f32;
-                        let y_norm_axes = axes.y.map(y) as f32;
-
-                        // Step 2: Map from normalized axes space to normalized figure space ([0, 1])
-                        let x_norm_fig = axes_rect.x + axes_rect.w * x_norm_axes;
-                        let y_norm_fig = axes_rect.y + axes_rect.h * y_norm_axes;
-
-                        vertices.push([x_norm_fig, y_norm_fig]);
-                    }
+        push_axes_batches(axes, &mut batches);
+    }
+    batches
+}
 
-                    batches.lines.push(LineBatch {
-                        vertices,
-                        color: line.color,
-                        width: line.width,
-                    });
-                }
-                Node::Scatter(scatter) => {
-                    let mut positions = Vec::with_capacity(scatter.xs.len());
-                    for (&x, &y) in scatter.xs.iter().zip(&scatter.ys) {
-                        let x_norm_axes = axes.x.map(x) as f32;
-                        let y_norm_axes = axes.y.map(y) as f32;
+/// Batches for one axes. Backends draw a figure one axes at a time, filled
+/// shapes first, then lines, then markers, so later axes such as legends
+/// cover earlier ones.
+pub fn build_axes_batches<P: BatchPosition>(axes: &Axes) -> Batches<P> {
+    let mut batches = Batches::default();
+    push_axes_batches(axes, &mut batches);
+    batches
+}
 
-                        let x_norm_fig = axes_rect.x + axes_rect.w * x_norm_axes;
-                        let y_norm_fig = axes_rect.y + axes_rect.h * y_norm_axes;
+fn push_axes_batches<P: BatchPosition>(axes: &Axes, batches: &mut Batches<P>) {
+    let axes_rect = axes.rect;
+    // Map from data space to normalized axes space ([0, 1]), then on to
+    // normalized figure space, all in f64 so deep zooms keep their detail.
+    let place = |x: f64, y: f64| {
+        [
+            axes_rect.x as f64 + axes_rect.w as f64 * axes.x.map(x),
+            axes_rect.y as f64 + axes_rect.h as f64 * axes.y.map(y),
+        ]
+    };
 
-                        positions.push([x_norm_fig, y_norm_fig]);
+    for node in &axes.children {
+        match node {
+            Node::Line(line) => {
+                let mut vertices = Vec::with_capacity(line.xs.len());
+                for (&x, &y) in line.xs.iter().zip(&line.ys) {
+                    // Missing samples break the line instead of joining
+                    // their neighbours.
+                    if !(x.is_finite() && y.is_finite()) {
+                        vertices.push([f64::NAN; 2]);
+                        continue;
                     }
+                    vertices.push(place(x, y));
+                }
+                if P::CUT_LINES {
+                    vertices = cut_line(&vertices, axes_rect, LINE_REACH);
+                }
 
-                    batches.markers.push(MarkerBatch {
-                        positions,
-                        color: scatter.color,
-                        size: scatter.size,
-                        marker: scatter.marker,
-                    });
+                batches.lines.push(LineBatch {
+                    vertices: vertices.into_iter().map(P::from_f64).collect(),
+                    color: line.color,
+                    width: line.width,
+                    dashes: line.style.dashes(line.width),
+                    join: line.join,
+                    cap: line.cap,
+                    miter_limit: line.miter_limit,
+                    clip: axes_rect,
+                });
+            }
+            Node::Scatter(scatter) => {
+                // Points that cannot be placed are dropped along with
+                // their per-point colour and size.
+                let mut kept = Vec::with_capacity(scatter.xs.len());
+                let mut positions = Vec::with_capacity(scatter.xs.len());
+                for (idx, (&x, &y)) in scatter.xs.iter().zip(&scatter.ys).enumerate() {
+                    let position = place(x, y);
+                    let size = scatter.sizes.as_ref().and_then(|sizes| sizes.get(idx));
+                    if !position.iter().all(|v| v.is_finite())
+                        || size.is_some_and(|size| !size.is_finite())
+                    {
+                        continue;
+                    }
+
+                    positions.push(P::from_f64(position));
+                    kept.push(idx);
                 }
-                Node::Bar(bar) => {
-                    let mut vertices = Vec::with_capacity(bar.xs.len() * 6);
-                    for (&x, &height) in bar.xs.iter().zip(&bar.heights) {
-                        let x0_norm_axes = axes.x.map(x - bar.width as f64 * 0.5) as f32;
-                        let x1_norm_axes = axes.x.map(x + bar.width as f64 * 0.5) as f32;
-                        let y0_norm_axes = axes.y.map(0.0) as f32;
-                        let y1_norm_axes = axes.y.map(height) as f32;
-
-                        let left = axes_rect.x + axes_rect.w * x0_norm_axes;
-                        let right = axes_rect.x + axes_rect.w * x1_norm_axes;
-                        let bottom = axes_rect.y + axes_rect.h * y0_norm_axes;
-                        let top = axes_rect.y + axes_rect.h * y1_norm_axes;
-
-                        let min_y = bottom.min(top);
-                        let max_y = bottom.max(top);
-
-                        vertices.extend_from_slice(&[
+
+                batches.markers.push(MarkerBatch {
+                    positions,
+                    color: scatter.color,
+                    colors: pick(&scatter.colors, &kept),
+                    size: scatter.size,
+                    sizes: pick(&scatter.sizes, &kept),
+                    marker: scatter.marker,
+                    edge_color: scatter.edge_color,
+                    edge_width: scatter.edge_width,
+                    clip: axes_rect,
+                });
+            }
+            Node::Bar(bar) => {
+                let mut vertices = Vec::with_capacity(bar.xs.len() * 6);
+                for (&x, &height) in bar.xs.iter().zip(&bar.heights) {
+                    if !(x.is_finite() && height.is_finite()) {
+                        continue;
+                    }
+                    let half = bar.width as f64 * 0.5;
+                    let [left, bottom] = place(x - half, 0.0);
+                    let [right, top] = place(x + half, height);
+
+                    let min_y = bottom.min(top);
+                    let max_y = bottom.max(top);
+
+                    vertices.extend(
+                        [
                             [left, min_y],
                             [right, min_y],
                             [right, max_y],
                             [left, min_y],
                             [right, max_y],
                             [left, max_y],
-                        ]);
+                        ]
+                        .map(P::from_f64),
+                    );
+                }
+
+                batches.solids.push(SolidBatch {
+                    vertices,
+                    color: bar.color,
+                    clip: axes_rect,
+                });
+            }
+            Node::Patch(patch) => {
+                let [left, bottom] = place(patch.x0, patch.y0);
+                let [right, top] = place(patch.x1, patch.y1);
+
+                batches.solids.push(SolidBatch {
+                    vertices: [
+                        [left, bottom],
+                        [right, bottom],
+                        [right, top],
+                        [left, bottom],
+                        [right, top],
+                        [left, top],
+                    ]
+                    .map(P::from_f64)
+                    .to_vec(),
+                    color: patch.color,
+                    clip: axes_rect,
+                });
+            }
+            Node::Text(text) => {
+                let [x, y] = place(text.x, text.y);
+
+                batches.texts.push(TextBatch {
+                    content: text.content.clone(),
+                    position: [x as f32, y as f32],
+                    color: text.color,
+                    size: text.size,
+                });
+            }
+        }
+    }
+}
+
+/// Cut the polyline `vertices` to within `reach` axes lengths of `rect`.
+/// Segments that leave the region end at its edge and start a new run
+/// where they come back, so the visible part keeps its direction.
+fn cut_line(vertices: &[[f64; 2]], rect: Rect, reach: f64) -> Vec<[f64; 2]> {
+    let (w, h) = (rect.w as f64, rect.h as f64);
+    let lo = [rect.x as f64 - reach * w, rect.y as f64 - reach * h];
+    let hi = [
+        rect.x as f64 + (1.0 + reach) * w,
+        rect.y as f64 + (1.0 + reach) * h,
+    ];
+    let inside = |p: [f64; 2]| (0..2).all(|i| p[i] >= lo[i] && p[i] <= hi[i]);
+    if vertices.iter().all(|&p| p[0].is_nan() || inside(p)) {
+        return vertices.to_vec();
+    }
+
+    let mut out: Vec<[f64; 2]> = Vec::with_capacity(vertices.len());
+    let mut prev: Option<[f64; 2]> = None;
+    for &p in vertices {
+        if p[0].is_nan() {
+            break_run(&mut out);
+            prev = None;
+            continue;
+        }
+        match prev {
+            None if inside(p) => out.push(p),
+            None => {}
+            Some(start) => match clip_segment(start, p, lo, hi) {
+                Some((a, b)) => {
+                    if out.last() != Some(&a) {
+                        break_run(&mut out);
+                        out.push(a);
                     }
+                    out.push(b);
+                }
+                None => break_run(&mut out),
+            },
+        }
+        prev = Some(p);
+    }
+    if out.last().is_some_and(|p| p[0].is_nan()) {
+        out.pop();
+    }
+    out
+}
 
-                    batches.solids.push(SolidBatch {
-                        vertices,
-                        color: bar.color,
-                    });
+/// End the current run of `vertices`, if there is one.
+fn break_run(vertices: &mut Vec<[f64; 2]>) {
+    if vertices.last().is_some_and(|p| !p[0].is_nan()) {
+        vertices.push([f64::NAN; 2]);
+    }
+}
+
+/// The part of segment `a -> b` inside the box `lo..hi` (Liang-Barsky).
+fn clip_segment(
+    a: [f64; 2],
+    b: [f64; 2],
+    lo: [f64; 2],
+    hi: [f64; 2],
+) -> Option<([f64; 2], [f64; 2])> {
+    let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+    for i in 0..2 {
+        let d = b[i] - a[i];
+        for (p, q) in [(-d, a[i] - lo[i]), (d, hi[i] - a[i])] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
                 }
-                Node::Text(text) => {
-                    let x_norm_axes = axes.x.map(text.x) as f32;
-                    let y_norm_axes = axes.y.map(text.y) as f32;
-
-                    let x_norm_fig = axes_rect.x + axes_rect.w * x_norm_axes;
-                    let y_norm_fig = axes_rect.y + axes_rect.h * y_norm_axes;
-
-                    batches.texts.push(TextBatch {
-                        content: text.content.clone(),
-                        position: [x_norm_fig, y_norm_fig],
-                        color: text.color,
-                        size: text.size,
-                    });
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
                 }
             }
         }
     }
-    batches
+    if t0 > t1 {
+        return None;
+    }
+    let at = |t: f64| {
+        if t == 0.0 {
+            a
+        } else if t == 1.0 {
+            b
+        } else {
+            [a[0] + t * (b[0] - a[0]), a[1] + t * (b[1] - a[1])]
+        }
+    };
+    Some((at(t0), at(t1)))
+}
+
+/// The entries of per-point `values` at the indices in `kept`.
+fn pick<T: Copy>(values: &Option<Vec<T>>, kept: &[usize]) -> Option<Vec<T>> {
+    values.as_ref().map(|values| {
+        kept.iter()
+            .filter_map(|&idx| values.get(idx).copied())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::{
+        Bar, Line, LineCap, LineJoin, LineStyle, Scatter, Size, DEFAULT_MITER_LIMIT,
+    };
+    use crate::Scale;
+
+    #[test]
+    fn non_finite_values_break_lines_and_drop_points() {
+        let unit = || Scale::linear((0.0, 1.0), (0.0, 1.0));
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 1.0,
+            h: 1.0,
+        };
+        let mut axes = Axes::new(rect, unit(), unit());
+        axes.add(Node::Line(Line {
+            xs: vec![0.0, 0.5, 1.0],
+            ys: vec![0.0, f64::NAN, 1.0],
+            color: Color::BLACK,
+            width: 1.0,
+            style: LineStyle::Solid,
+            join: LineJoin::Round,
+            cap: LineCap::Butt,
+            miter_limit: DEFAULT_MITER_LIMIT,
+            label: None,
+        }));
+        axes.add(Node::Scatter(Scatter {
+            xs: vec![0.0, f64::INFINITY, 1.0],
+            ys: vec![0.0, 0.5, 1.0],
+            color: Color::BLACK,
+            size: 4.0,
+            marker: Marker::Circle,
+            colors: Some(vec![Color::BLACK, Color::WHITE, Color::WHITE]),
+            sizes: Some(vec![1.0, 2.0, 3.0]),
+            edge_color: Color::BLACK,
+            edge_width: 0.0,
+            label: None,
+        }));
+        axes.add(Node::Bar(Bar {
+            xs: vec![0.25, 0.75],
+            heights: vec![f64::NAN, 0.5],
+            width: 0.1,
+            color: Color::BLACK,
+            label: None,
+        }));
+        let fig = Figure {
+            size: Size {
+                width: 100,
+                height: 100,
+                dpi: 1.0,
+            },
+            clear_color: Color::WHITE,
+            axes: vec![axes],
+        };
+
+        let batches = build_batches(&fig);
+        let line = &batches.lines[0].vertices;
+        assert_eq!(line.len(), 3);
+        assert!(line[1][0].is_nan() && line[2] == [1.0, 1.0]);
+        let markers = &batches.markers[0];
+        assert_eq!(markers.positions, vec![[0.0, 0.0], [1.0, 1.0]]);
+        assert_eq!(markers.sizes, Some(vec![1.0, 3.0]));
+        assert_eq!(batches.solids[0].vertices.len(), 6);
+    }
+
+    fn line_axes(x: Scale, xs: Vec<f64>, ys: Vec<f64>) -> Axes {
+        let rect = Rect {
+            x: 0.0,
+            y: 0.0,
+            w: 1.0,
+            h: 1.0,
+        };
+        let mut axes = Axes::new(rect, x, Scale::linear((0.0, 1.0), (0.0, 1.0)));
+        axes.add(Node::Line(Line {
+            xs,
+            ys,
+            color: Color::BLACK,
+            width: 1.0,
+            style: LineStyle::Solid,
+            join: LineJoin::Round,
+            cap: LineCap::Butt,
+            miter_limit: DEFAULT_MITER_LIMIT,
+            label: None,
+        }));
+        axes
+    }
+
+    #[test]
+    fn deep_zooms_at_large_offsets_keep_their_detail() {
+        // A microsecond window on epoch seconds.
+        let t0 = 1.7e9;
+        let x = Scale::linear((t0, t0 + 1e-6), (0.0, 1.0));
+        let axes = line_axes(x, vec![t0 + 0.25e-6, t0 + 0.75e-6], vec![0.5, 0.5]);
+        let batches: Batches = build_axes_batches(&axes);
+        let line = &batches.lines[0].vertices;
+        assert!((line[0][0] - 0.25).abs() < 1e-3);
+        assert!((line[1][0] - 0.75).abs() < 1e-3);
+    }
+
+    #[test]
+    fn lines_far_outside_the_axes_are_cut_at_the_same_slope() {
+        let x = Scale::linear((0.0, 1.0), (0.0, 1.0));
+        let axes = line_axes(x.clone(), vec![-1e9, 1e9, 0.5], vec![-1e9, 1e9, 5e8]);
+        let batches: Batches = build_axes_batches(&axes);
+        let line = &batches.lines[0].vertices;
+        // The diagonal is cut where it leaves the band around the axes, and
+        // the segment that stays outside is dropped.
+        assert_eq!(line.len(), 2);
+        assert!(line.iter().all(|p| (p[0] - p[1]).abs() < 1e-6));
+        assert_eq!(line[0], [-(LINE_REACH as f32); 2]);
+
+        let data: Batches<[f64; 2]> = build_axes_batches(&axes);
+        assert_eq!(data.lines[0].vertices[1], [1e9, 1e9]);
+    }
 }
@@ -1,7 +1,36 @@
+/// Maps a data domain onto an output range.
+///
+/// The domain may be decreasing (`(10.0, 0.0)`), which flips the axis so that
+/// values grow towards the start of the range.
 #[derive(Clone, Debug)]
 pub enum Scale {
     Linear(Linear),
-    // Log(Log), Time(Time) later
+    Log(Log),
+    SymLog(SymLog),
+    Time(Time),
+    Categorical(Categorical),
+}
+
+/// The transform a [`Scale`] applies, without its domain or range.
+///
+/// Plots keep this around so they can rebuild the scale for every view and
+/// run zoom and pan in the transformed space.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ScaleKind {
+    #[default]
+    Linear,
+    Log {
+        base: f64,
+    },
+    /// Linear within `±linthresh`, logarithmic beyond it.
+    SymLog {
+        base: f64,
+        linthresh: f64,
+    },
+    /// Linear over seconds since the Unix epoch, with calendar ticks.
+    Time,
+    /// Linear over slot indices; category `i` is centred on `i`.
+    Categorical,
 }
 
 #[derive(Clone, Debug)]
@@ -26,14 +55,346 @@ impl Linear {
     }
 }
 
+/// A linear scale over epoch seconds (UTC).
+#[derive(Clone, Debug)]
+pub struct Time {
+    pub domain: (f64, f64),
+    pub range: (f64, f64),
+}
+
+impl Time {
+    pub fn new(domain: (f64, f64), range: (f64, f64)) -> Self {
+        Self { domain, range }
+    }
+
+    pub fn map(&self, v: f64) -> f64 {
+        Linear::new(self.domain, self.range).map(v)
+    }
+}
+
+/// A scale over named slots. Category `i` occupies `[i - 0.5, i + 0.5]` in
+/// data space, so the domain is usually `(-0.5, n - 0.5)`.
+#[derive(Clone, Debug)]
+pub struct Categorical {
+    pub domain: (f64, f64),
+    pub range: (f64, f64),
+    pub categories: Vec<String>,
+}
+
+impl Categorical {
+    pub fn new(domain: (f64, f64), range: (f64, f64), categories: Vec<String>) -> Self {
+        Self {
+            domain,
+            range,
+            categories,
+        }
+    }
+
+    pub fn map(&self, v: f64) -> f64 {
+        Linear::new(self.domain, self.range).map(v)
+    }
+
+    /// The category whose slot contains `v`, if any.
+    pub fn category_at(&self, v: f64) -> Option<&str> {
+        let idx = v.round();
+        if idx < 0.0 {
+            return None;
+        }
+        self.categories.get(idx as usize).map(String::as_str)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Log {
+    pub domain: (f64, f64),
+    pub range: (f64, f64),
+    pub base: f64,
+}
+
+impl Log {
+    pub fn new(domain: (f64, f64), range: (f64, f64), base: f64) -> Self {
+        Self {
+            domain,
+            range,
+            base,
+        }
+    }
+
+    /// Map `v` logarithmically. Non-positive values are clipped to the
+    /// smallest positive float, which places them far outside the range.
+    pub fn map(&self, v: f64) -> f64 {
+        let kind = ScaleKind::Log { base: self.base };
+        let d0 = kind.forward(self.domain.0);
+        let d1 = kind.forward(self.domain.1);
+        let (r0, r1) = self.range;
+        if d1 == d0 {
+            return r0;
+        }
+        let t = (kind.forward(v) - d0) / (d1 - d0);
+        r0 + t * (r1 - r0)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SymLog {
+    pub domain: (f64, f64),
+    pub range: (f64, f64),
+    pub base: f64,
+    pub linthresh: f64,
+}
+
+impl SymLog {
+    pub fn new(domain: (f64, f64), range: (f64, f64), base: f64, linthresh: f64) -> Self {
+        Self {
+            domain,
+            range,
+            base,
+            linthresh,
+        }
+    }
+
+    pub fn map(&self, v: f64) -> f64 {
+        let kind = ScaleKind::SymLog {
+            base: self.base,
+            linthresh: self.linthresh,
+        };
+        let d0 = kind.forward(self.domain.0);
+        let d1 = kind.forward(self.domain.1);
+        let (r0, r1) = self.range;
+        if d1 == d0 {
+            return r0;
+        }
+        let t = (kind.forward(v) - d0) / (d1 - d0);
+        r0 + t * (r1 - r0)
+    }
+}
+
+impl ScaleKind {
+    pub const LOG10: ScaleKind = ScaleKind::Log { base: 10.0 };
+
+    pub fn build(self, domain: (f64, f64), range: (f64, f64)) -> Scale {
+        match self {
+            ScaleKind::Linear => Scale::linear(domain, range),
+            ScaleKind::Log { base } => Scale::log(domain, range, base),
+            ScaleKind::SymLog { base, linthresh } => Scale::symlog(domain, range, base, linthresh),
+            ScaleKind::Time => Scale::time(domain, range),
+            ScaleKind::Categorical => Scale::categorical(domain, range, Vec::new()),
+        }
+    }
+
+    /// Transform a data value into the space where the scale is linear.
+    ///
+    /// For symlog, `±linthresh` lands on `±1` and every further unit is one
+    /// power of `base`.
+    pub fn forward(self, v: f64) -> f64 {
+        match self {
+            ScaleKind::Linear | ScaleKind::Time | ScaleKind::Categorical => v,
+            ScaleKind::Log { base } => v.max(f64::MIN_POSITIVE).log(base),
+            ScaleKind::SymLog { base, linthresh } => {
+                if v.abs() <= linthresh {
+                    v / linthresh
+                } else {
+                    v.signum() * (1.0 + (v.abs() / linthresh).log(base))
+                }
+            }
+        }
+    }
+
+    /// Inverse of [`ScaleKind::forward`].
+    pub fn inverse(self, v: f64) -> f64 {
+        match self {
+            ScaleKind::Linear | ScaleKind::Time | ScaleKind::Categorical => v,
+            ScaleKind::Log { base } => base.powf(v),
+            ScaleKind::SymLog { base, linthresh } => {
+                if v.abs() <= 1.0 {
+                    v * linthresh
+                } else {
+                    v.signum() * linthresh * base.powf(v.abs() - 1.0)
+                }
+            }
+        }
+    }
+
+    /// Whether `v` can be represented on this scale.
+    pub fn accepts(self, v: f64) -> bool {
+        match self {
+            ScaleKind::Linear
+            | ScaleKind::SymLog { .. }
+            | ScaleKind::Time
+            | ScaleKind::Categorical => v.is_finite(),
+            ScaleKind::Log { .. } => v.is_finite() && v > 0.0,
+        }
+    }
+}
+
 impl Scale {
     pub fn linear(domain: (f64, f64), range: (f64, f64)) -> Self {
         Scale::Linear(Linear::new(domain, range))
     }
 
+    pub fn log(domain: (f64, f64), range: (f64, f64), base: f64) -> Self {
+        Scale::Log(Log::new(domain, range, base))
+    }
+
+    pub fn symlog(domain: (f64, f64), range: (f64, f64), base: f64, linthresh: f64) -> Self {
+        Scale::SymLog(SymLog::new(domain, range, base, linthresh))
+    }
+
+    pub fn time(domain: (f64, f64), range: (f64, f64)) -> Self {
+        Scale::Time(Time::new(domain, range))
+    }
+
+    pub fn categorical(domain: (f64, f64), range: (f64, f64), categories: Vec<String>) -> Self {
+        Scale::Categorical(Categorical::new(domain, range, categories))
+    }
+
+    pub fn kind(&self) -> ScaleKind {
+        match self {
+            Scale::Linear(_) => ScaleKind::Linear,
+            Scale::Log(l) => ScaleKind::Log { base: l.base },
+            Scale::SymLog(s) => ScaleKind::SymLog {
+                base: s.base,
+                linthresh: s.linthresh,
+            },
+            Scale::Time(_) => ScaleKind::Time,
+            Scale::Categorical(_) => ScaleKind::Categorical,
+        }
+    }
+
+    pub fn domain(&self) -> (f64, f64) {
+        match self {
+            Scale::Linear(l) => l.domain,
+            Scale::Log(l) => l.domain,
+            Scale::SymLog(s) => s.domain,
+            Scale::Time(t) => t.domain,
+            Scale::Categorical(c) => c.domain,
+        }
+    }
+
+    pub fn range(&self) -> (f64, f64) {
+        match self {
+            Scale::Linear(l) => l.range,
+            Scale::Log(l) => l.range,
+            Scale::SymLog(s) => s.range,
+            Scale::Time(t) => t.range,
+            Scale::Categorical(c) => c.range,
+        }
+    }
+
     pub fn map(&self, v: f64) -> f64 {
         match self {
             Scale::Linear(l) => l.map(v),
+            Scale::Log(l) => l.map(v),
+            Scale::SymLog(s) => s.map(v),
+            Scale::Time(t) => t.map(v),
+            Scale::Categorical(c) => c.map(v),
+        }
+    }
+
+    /// Map a value from the range back into the domain.
+    pub fn invert(&self, v: f64) -> f64 {
+        let kind = self.kind();
+        let (d0, d1) = self.domain();
+        let (r0, r1) = self.range();
+        if r1 == r0 {
+            return d0;
+        }
+        let t = (v - r0) / (r1 - r0);
+        let d0 = kind.forward(d0);
+        let d1 = kind.forward(d1);
+        kind.inverse(d0 + t * (d1 - d0))
+    }
+}
+
+/// Where one axis puts data in figure space, in a form a shader can run:
+/// `start + extent * (forward(v) - lo) / (hi - lo)`.
+///
+/// Renderers that keep series data uploaded use this to follow zoom and pan
+/// without touching the data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisTransform {
+    pub kind: ScaleKind,
+    /// [`ScaleKind::forward`] of the domain start and end.
+    pub lo: f64,
+    pub hi: f64,
+    /// Figure-space start and length of the axis.
+    pub start: f64,
+    pub extent: f64,
+}
+
+impl AxisTransform {
+    /// Leaves figure coordinates as they are.
+    pub const IDENTITY: Self = Self {
+        kind: ScaleKind::Linear,
+        lo: 0.0,
+        hi: 1.0,
+        start: 0.0,
+        extent: 1.0,
+    };
+
+    /// Place `scale`, which maps onto `0..1`, along `start..start + extent`.
+    pub fn new(scale: &Scale, start: f32, extent: f32) -> Self {
+        let kind = scale.kind();
+        let (d0, d1) = scale.domain();
+        Self {
+            kind,
+            lo: kind.forward(d0),
+            hi: kind.forward(d1),
+            start: start as f64,
+            extent: extent as f64,
+        }
+    }
+
+    pub fn apply(&self, v: f64) -> f64 {
+        if self.hi == self.lo {
+            return self.start;
+        }
+        let t = (self.kind.forward(v) - self.lo) / (self.hi - self.lo);
+        self.start + self.extent * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_scale_maps_decades_evenly() {
+        let scale = Scale::log((1.0, 1000.0), (0.0, 1.0), 10.0);
+        assert!((scale.map(10.0) - 1.0 / 3.0).abs() < 1e-12);
+        assert!((scale.map(100.0) - 2.0 / 3.0).abs() < 1e-12);
+        assert!((scale.invert(0.5) - 1000.0_f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn symlog_scale_is_linear_inside_threshold_and_symmetric() {
+        let scale = Scale::symlog((-1000.0, 1000.0), (0.0, 1.0), 10.0, 10.0);
+        assert!((scale.map(0.0) - 0.5).abs() < 1e-12);
+        assert!((scale.map(5.0) - 3.5 / 6.0).abs() < 1e-12);
+        assert!((scale.map(100.0) + scale.map(-100.0) - 1.0).abs() < 1e-12);
+        assert!((scale.invert(scale.map(-250.0)) + 250.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn decreasing_domain_flips_the_mapping() {
+        let scale = Scale::linear((10.0, 0.0), (0.0, 1.0));
+        assert_eq!(scale.map(10.0), 0.0);
+        assert_eq!(scale.map(2.5), 0.75);
+    }
+
+    #[test]
+    fn axis_transform_matches_the_scale_inside_its_rect() {
+        for scale in [
+            Scale::linear((10.0, 0.0), (0.0, 1.0)),
+            Scale::log((1.0, 1000.0), (0.0, 1.0), 10.0),
+            Scale::symlog((-1000.0, 1000.0), (0.0, 1.0), 10.0, 10.0),
+        ] {
+            let transform = AxisTransform::new(&scale, 0.25, 0.5);
+            for v in [-50.0, 2.5, 7.0, 400.0] {
+                let expected = 0.25 + 0.5 * scale.map(v);
+                assert!((transform.apply(v) - expected).abs() < 1e-12);
+            }
         }
+        assert_eq!(AxisTransform::IDENTITY.apply(0.3), 0.3);
     }
 }
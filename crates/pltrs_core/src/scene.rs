@@ -1,12 +1,26 @@
 use crate::Scale;
+pub use pltrs_geom::{LineCap, LineJoin, DEFAULT_MITER_LIMIT};
 
+/// A figure's size in logical pixels, the unit layout, text, line widths and
+/// marker sizes are given in.
 #[derive(Clone, Copy, Debug)]
 pub struct Size {
     pub width: u32,
     pub height: u32,
+    /// Device pixels per logical pixel rather than dots per inch: 1 for an
+    /// ordinary screen or 100 dpi, 2 for a HiDPI one or for an image saved
+    /// at twice the resolution.
     pub dpi: f32,
 }
 
+impl Size {
+    /// Width and height in device pixels.
+    pub fn pixels(&self) -> (u32, u32) {
+        let scale = |length: u32| ((length as f32 * self.dpi).round() as u32).max(1);
+        (scale(self.width), scale(self.height))
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Color {
     pub r: f32,
@@ -36,6 +50,28 @@ impl Color {
     };
 }
 
+impl Color {
+    /// The colour as 8-bit sRGB with straight alpha. Components are linear
+    /// light, as the GPU backend's sRGB targets read them.
+    pub fn to_srgb8(self) -> [u8; 4] {
+        let encode = |c: f32| {
+            let c = c.clamp(0.0, 1.0);
+            let srgb = if c <= 0.003_130_8 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            };
+            (srgb * 255.0).round() as u8
+        };
+        [
+            encode(self.r),
+            encode(self.g),
+            encode(self.b),
+            (self.a.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ]
+    }
+}
+
 impl Default for Color {
     fn default() -> Self {
         Self::WHITE
@@ -100,6 +136,7 @@ pub enum Node {
     Line(Line),
     Scatter(Scatter),
     Bar(Bar),
+    Patch(Patch),
     Text(Text),
 }
 
@@ -109,6 +146,59 @@ pub struct Line {
     pub ys: Vec<f64>,
     pub color: Color,
     pub width: f32,
+    pub style: LineStyle,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest miter allowed, as a multiple of the line width.
+    pub miter_limit: f32,
+    /// Name shown in the legend.
+    pub label: Option<String>,
+}
+
+/// Dash pattern of a line. The pattern is laid out in screen pixels along the
+/// whole polyline, so it runs on across vertices and keeps its size at any
+/// zoom level.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum LineStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+    DashDot,
+    /// Alternating on/off lengths in pixels, starting with a dash.
+    Custom(Vec<f32>),
+}
+
+impl LineStyle {
+    /// Most on/off lengths a pattern may have.
+    pub const MAX_DASHES: usize = 8;
+
+    /// On/off lengths in pixels for a line `width` pixels wide, or an empty
+    /// vector for a solid line. Named styles scale with the width so thick
+    /// lines keep their look; custom patterns are used as given. Patterns
+    /// of odd length are repeated once so dashes and gaps alternate.
+    pub fn dashes(&self, width: f32) -> Vec<f32> {
+        let unit = width.max(1.0);
+        let pattern = match self {
+            LineStyle::Solid => return Vec::new(),
+            LineStyle::Dashed => vec![3.7 * unit, 1.6 * unit],
+            LineStyle::Dotted => vec![unit, 1.65 * unit],
+            LineStyle::DashDot => vec![6.4 * unit, 1.6 * unit, unit, 1.6 * unit],
+            LineStyle::Custom(lengths) => {
+                let mut pattern = lengths.clone();
+                if pattern.len() % 2 == 1 {
+                    pattern.extend_from_slice(lengths);
+                }
+                pattern.truncate(Self::MAX_DASHES);
+                pattern
+            }
+        };
+        if pattern.iter().sum::<f32>() > 0.0 && pattern.iter().all(|len| *len >= 0.0) {
+            pattern
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -118,12 +208,94 @@ pub struct Scatter {
     pub color: Color,
     pub size: f32,
     pub marker: Marker,
+    /// Per-point colours used instead of `color`.
+    pub colors: Option<Vec<Color>>,
+    /// Per-point marker sizes in pixels used instead of `size`.
+    pub sizes: Option<Vec<f32>>,
+    pub edge_color: Color,
+    /// Width in pixels of the outline drawn inside each marker; zero draws
+    /// none.
+    pub edge_width: f32,
+    pub label: Option<String>,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Marker {
     Circle,
     Square,
+    TriangleUp,
+    TriangleDown,
+    TriangleLeft,
+    TriangleRight,
+    Diamond,
+    Plus,
+    Cross,
+    Star,
+    Hexagon,
+    Pentagon,
+}
+
+impl Marker {
+    /// Corners of the marker counter-clockwise, y up, centred on the origin
+    /// and fitting a circle of `radius`, matching the GPU backend's shapes.
+    /// Circles come back as a polygon of many sides.
+    pub fn outline(self, radius: f32) -> Vec<[f32; 2]> {
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+        let regular = |sides: usize, start: f32| -> Vec<[f32; 2]> {
+            (0..sides)
+                .map(|i| {
+                    let angle = start + 2.0 * PI * i as f32 / sides as f32;
+                    [radius * angle.cos(), radius * angle.sin()]
+                })
+                .collect()
+        };
+        let plus = |turn: f32| -> Vec<[f32; 2]> {
+            let (r, a) = (radius, radius * 0.3);
+            let (sin, cos) = turn.sin_cos();
+            [
+                [-a, -r],
+                [a, -r],
+                [a, -a],
+                [r, -a],
+                [r, a],
+                [a, a],
+                [a, r],
+                [-a, r],
+                [-a, a],
+                [-r, a],
+                [-r, -a],
+                [-a, -a],
+            ]
+            .map(|[x, y]| [x * cos - y * sin, x * sin + y * cos])
+            .to_vec()
+        };
+        match self {
+            Marker::Circle => regular(48, 0.0),
+            Marker::Square => vec![
+                [-radius, -radius],
+                [radius, -radius],
+                [radius, radius],
+                [-radius, radius],
+            ],
+            Marker::TriangleUp => regular(3, FRAC_PI_2),
+            Marker::TriangleDown => regular(3, -FRAC_PI_2),
+            Marker::TriangleLeft => regular(3, PI),
+            Marker::TriangleRight => regular(3, 0.0),
+            Marker::Diamond => regular(4, FRAC_PI_2),
+            Marker::Plus => plus(0.0),
+            Marker::Cross => plus(FRAC_PI_4),
+            Marker::Star => (0..10)
+                .map(|i| {
+                    let angle = FRAC_PI_2 + PI * i as f32 / 5.0;
+                    let r = if i % 2 == 0 { radius } else { radius * 0.45 };
+                    [r * angle.cos(), r * angle.sin()]
+                })
+                .collect(),
+            Marker::Hexagon => regular(6, 0.0),
+            Marker::Pentagon => regular(5, FRAC_PI_2),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -132,6 +304,18 @@ pub struct Bar {
     pub heights: Vec<f64>,
     pub width: f32,
     pub color: Color,
+    pub label: Option<String>,
+}
+
+/// A filled axis-aligned rectangle spanning `x0..x1` and `y0..y1` in data
+/// coordinates.
+#[derive(Clone, Debug)]
+pub struct Patch {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+    pub color: Color,
 }
 
 #[derive(Clone, Debug)]
@@ -142,3 +326,77 @@ pub struct Text {
     pub color: Color,
     pub size: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_dashes_scale_with_width_and_odd_patterns_repeat() {
+        assert!(LineStyle::Solid.dashes(3.0).is_empty());
+        assert_eq!(LineStyle::Dotted.dashes(2.0), vec![2.0, 3.3]);
+        assert_eq!(LineStyle::Dashed.dashes(0.5), LineStyle::Dashed.dashes(1.0));
+        assert_eq!(
+            LineStyle::Custom(vec![6.0, 2.0, 1.0]).dashes(4.0),
+            vec![6.0, 2.0, 1.0, 6.0, 2.0, 1.0]
+        );
+        assert!(LineStyle::Custom(vec![0.0, 0.0]).dashes(1.0).is_empty());
+    }
+
+    #[test]
+    fn colors_encode_to_srgb() {
+        assert_eq!(Color::WHITE.to_srgb8(), [255, 255, 255, 255]);
+        let grey = Color {
+            r: 0.6,
+            g: 0.0,
+            b: 0.001,
+            a: 0.5,
+        };
+        assert_eq!(grey.to_srgb8(), [203, 0, 3, 128]);
+    }
+
+    #[test]
+    fn marker_outlines_point_up_and_fit_their_radius() {
+        let top = |marker: Marker| {
+            marker
+                .outline(10.0)
+                .into_iter()
+                .fold(f32::MIN, |top, [_, y]| top.max(y))
+        };
+        for marker in [
+            Marker::Circle,
+            Marker::TriangleUp,
+            Marker::Diamond,
+            Marker::Star,
+            Marker::Pentagon,
+        ] {
+            assert!((top(marker) - 10.0).abs() < 1e-4, "{marker:?}");
+        }
+        assert!((top(Marker::TriangleDown) - 5.0).abs() < 1e-4);
+        for marker in [Marker::Plus, Marker::Cross, Marker::Star, Marker::Hexagon] {
+            let outline = marker.outline(10.0);
+            let area: f32 = (0..outline.len())
+                .map(|i| {
+                    let ([x0, y0], [x1, y1]) = (outline[i], outline[(i + 1) % outline.len()]);
+                    x0 * y1 - x1 * y0
+                })
+                .sum();
+            assert!(area > 0.0, "{marker:?} is not counter-clockwise");
+            assert!(outline
+                .iter()
+                .all(|[x, y]| (x * x + y * y).sqrt() <= 10.0 * 2f32.sqrt() + 1e-4));
+        }
+    }
+
+    #[test]
+    fn size_in_device_pixels_follows_dpi() {
+        let size = Size {
+            width: 800,
+            height: 601,
+            dpi: 1.5,
+        };
+        assert_eq!(size.pixels(), (1200, 902));
+        assert_eq!(Size { dpi: 1.0, ..size }.pixels(), (800, 601));
+        assert_eq!(Size { dpi: 0.0, ..size }.pixels(), (1, 1));
+    }
+}
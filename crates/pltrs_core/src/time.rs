@@ -0,0 +1,274 @@
+//! Calendar-aware ticks for time axes.
+//!
+//! Timestamps are seconds since the Unix epoch and are always read as UTC.
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = 3_600.0;
+const DAY: f64 = 86_400.0;
+const AVG_MONTH: f64 = 30.436_875 * DAY;
+const AVG_YEAR: f64 = 365.2425 * DAY;
+const MAX_TICKS: usize = 1_000;
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeUnit {
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+/// Distance between neighbouring ticks, e.g. 15 minutes or 3 months.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeStep {
+    pub unit: TimeUnit,
+    pub count: u32,
+}
+
+impl TimeStep {
+    fn approx_seconds(self) -> f64 {
+        let unit = match self.unit {
+            TimeUnit::Millisecond => 0.001,
+            TimeUnit::Second => 1.0,
+            TimeUnit::Minute => MINUTE,
+            TimeUnit::Hour => HOUR,
+            TimeUnit::Day => DAY,
+            TimeUnit::Month => AVG_MONTH,
+            TimeUnit::Year => AVG_YEAR,
+        };
+        unit * self.count as f64
+    }
+}
+
+const CALENDAR_STEPS: [TimeStep; 24] = [
+    step(TimeUnit::Second, 1),
+    step(TimeUnit::Second, 2),
+    step(TimeUnit::Second, 5),
+    step(TimeUnit::Second, 10),
+    step(TimeUnit::Second, 15),
+    step(TimeUnit::Second, 30),
+    step(TimeUnit::Minute, 1),
+    step(TimeUnit::Minute, 2),
+    step(TimeUnit::Minute, 5),
+    step(TimeUnit::Minute, 10),
+    step(TimeUnit::Minute, 15),
+    step(TimeUnit::Minute, 30),
+    step(TimeUnit::Hour, 1),
+    step(TimeUnit::Hour, 2),
+    step(TimeUnit::Hour, 3),
+    step(TimeUnit::Hour, 6),
+    step(TimeUnit::Hour, 12),
+    step(TimeUnit::Day, 1),
+    step(TimeUnit::Day, 2),
+    step(TimeUnit::Day, 7),
+    step(TimeUnit::Month, 1),
+    step(TimeUnit::Month, 2),
+    step(TimeUnit::Month, 3),
+    step(TimeUnit::Month, 6),
+];
+
+const fn step(unit: TimeUnit, count: u32) -> TimeStep {
+    TimeStep { unit, count }
+}
+
+/// Pick the smallest calendar step that yields at most about `target_count`
+/// ticks across `span` seconds.
+pub fn choose_time_step(span: f64, target_count: usize) -> TimeStep {
+    let rough = span.abs() / target_count.saturating_sub(1).max(1) as f64;
+    if rough < 1.0 {
+        let millis = nice_ceil(rough * 1_000.0).clamp(1.0, 500.0);
+        return step(TimeUnit::Millisecond, millis as u32);
+    }
+
+    if let Some(step) = CALENDAR_STEPS
+        .iter()
+        .find(|step| step.approx_seconds() >= rough)
+    {
+        return *step;
+    }
+
+    let years = nice_ceil(rough / AVG_YEAR).max(1.0);
+    step(TimeUnit::Year, years.min(u32::MAX as f64) as u32)
+}
+
+/// Tick positions for a time axis spanning `limits`, aligned to calendar
+/// boundaries (midnight, month starts, Mondays for weekly steps, ...).
+pub fn time_ticks(limits: (f64, f64), target_count: usize) -> Vec<f64> {
+    let (lo, hi) = (limits.0.min(limits.1), limits.0.max(limits.1));
+    if !(lo.is_finite() && hi.is_finite()) || hi - lo <= f64::EPSILON {
+        return vec![lo];
+    }
+
+    let step = choose_time_step(hi - lo, target_count);
+    match step.unit {
+        TimeUnit::Month => month_ticks(lo, hi, step.count),
+        TimeUnit::Year => year_ticks(lo, hi, step.count),
+        TimeUnit::Day if step.count == 7 => {
+            // 1970-01-05 is the first Monday after the epoch.
+            fixed_ticks(lo, hi, step.approx_seconds(), 4.0 * DAY)
+        }
+        _ => fixed_ticks(lo, hi, step.approx_seconds(), 0.0),
+    }
+}
+
+/// Format a timestamp for a tick on an axis that uses `step`.
+///
+/// Ticks on midnight are labelled with the date on hour and minute axes, so
+/// a view that crosses days stays readable.
+pub fn format_time_tick(value: f64, step: TimeStep) -> String {
+    let total_millis = (value * 1_000.0).round() as i64;
+    let days = total_millis.div_euclid(86_400_000);
+    let millis_of_day = total_millis.rem_euclid(86_400_000);
+    let (year, month, day) = civil_from_days(days);
+    let hour = millis_of_day / 3_600_000;
+    let minute = millis_of_day / 60_000 % 60;
+    let second = millis_of_day / 1_000 % 60;
+    let millis = millis_of_day % 1_000;
+    let month_name = MONTH_NAMES[(month - 1) as usize];
+
+    match step.unit {
+        TimeUnit::Year => format!("{year}"),
+        TimeUnit::Month => format!("{month_name} {year}"),
+        TimeUnit::Day => format!("{month_name} {day}"),
+        TimeUnit::Hour | TimeUnit::Minute if millis_of_day == 0 => {
+            format!("{month_name} {day}")
+        }
+        TimeUnit::Hour | TimeUnit::Minute => format!("{hour:02}:{minute:02}"),
+        TimeUnit::Second => format!("{hour:02}:{minute:02}:{second:02}"),
+        TimeUnit::Millisecond => {
+            format!("{hour:02}:{minute:02}:{second:02}.{millis:03}")
+        }
+    }
+}
+
+fn fixed_ticks(lo: f64, hi: f64, step: f64, origin: f64) -> Vec<f64> {
+    let first = ((lo - origin) / step - 1e-9).ceil();
+    (0..MAX_TICKS)
+        .map(|idx| origin + (first + idx as f64) * step)
+        .take_while(|tick| *tick <= hi + step * 1e-9)
+        .collect()
+}
+
+fn month_ticks(lo: f64, hi: f64, every: u32) -> Vec<f64> {
+    let every = every.max(1) as i64;
+    let (year, month, _) = civil_from_days((lo / DAY).floor() as i64);
+    let mut index = year * 12 + (month as i64 - 1);
+    index += (every - index.rem_euclid(every)) % every;
+
+    let mut ticks = Vec::new();
+    while ticks.len() < MAX_TICKS {
+        let tick = days_from_civil(index.div_euclid(12), (index.rem_euclid(12) + 1) as u32, 1)
+            as f64
+            * DAY;
+        if tick > hi {
+            break;
+        }
+        if tick >= lo {
+            ticks.push(tick);
+        }
+        index += every;
+    }
+    ticks
+}
+
+fn year_ticks(lo: f64, hi: f64, every: u32) -> Vec<f64> {
+    let every = every.max(1) as i64;
+    let (first_year, _, _) = civil_from_days((lo / DAY).floor() as i64);
+    let mut year = first_year + (every - first_year.rem_euclid(every)) % every;
+
+    let mut ticks = Vec::new();
+    while ticks.len() < MAX_TICKS {
+        let tick = days_from_civil(year, 1, 1) as f64 * DAY;
+        if tick > hi {
+            break;
+        }
+        if tick >= lo {
+            ticks.push(tick);
+        }
+        year += every;
+    }
+    ticks
+}
+
+/// Round `value` up to 1, 2 or 5 times a power of ten.
+fn nice_ceil(value: f64) -> f64 {
+    if value <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10_f64.powf(value.log10().floor());
+    let fraction = value / magnitude;
+    let nice = if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// Convert days since 1970-01-01 into a proleptic Gregorian `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Inverse of [`civil_from_days`].
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-03-05T14:30:00Z
+    const SAMPLE: f64 = 1_709_649_000.0;
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_787), (2024, 3, 5));
+        assert_eq!(days_from_civil(2024, 2, 29), 19_782);
+    }
+
+    #[test]
+    fn hour_ticks_align_to_the_clock() {
+        let ticks = time_ticks((SAMPLE, SAMPLE + 10.0 * HOUR), 6);
+        assert_eq!(choose_time_step(10.0 * HOUR, 6), step(TimeUnit::Hour, 2));
+        assert_eq!(ticks.len(), 5);
+        assert_eq!(format_time_tick(ticks[0], step(TimeUnit::Hour, 2)), "16:00");
+        assert_eq!(format_time_tick(ticks[4], step(TimeUnit::Hour, 2)), "Mar 6");
+    }
+
+    #[test]
+    fn month_ticks_land_on_month_starts() {
+        let ticks = time_ticks((SAMPLE, SAMPLE + 200.0 * DAY), 6);
+        let labels: Vec<String> = ticks
+            .iter()
+            .map(|tick| format_time_tick(*tick, step(TimeUnit::Month, 2)))
+            .collect();
+        assert_eq!(labels, ["May 2024", "Jul 2024", "Sep 2024"]);
+    }
+}
@@ -0,0 +1,486 @@
+//! Geometry helpers shared by the render backends.
+//!
+//! Coordinates are screen pixels; the y direction does not matter as long as
+//! both axes use the same unit.
+
+use std::f32::consts::PI;
+
+/// How two segments of a stroked polyline are connected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Extend the outer edges until they meet, falling back to a bevel when
+    /// the point would be further than the miter limit.
+    Miter,
+    #[default]
+    Round,
+    /// Cut the corner off straight.
+    Bevel,
+}
+
+/// How the two ends of a stroked polyline are finished.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// Stop exactly at the end point.
+    #[default]
+    Butt,
+    Round,
+    /// Extend past the end point by half the line width.
+    Square,
+}
+
+/// Miter limit strokes get unless they ask for another: miters longer than
+/// four line widths are beveled.
+pub const DEFAULT_MITER_LIMIT: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest miter allowed, as a multiple of the line width.
+    pub miter_limit: f32,
+    /// When positive, vertex distances are reported modulo this many pixels
+    /// so long lines keep precise dash phases in `f32`.
+    pub period: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: DEFAULT_MITER_LIMIT,
+            period: 0.0,
+        }
+    }
+}
+
+/// A vertex of a stroke mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeVertex {
+    pub position: [f32; 2],
+    /// Distance along the centre line from the first point, in pixels.
+    pub distance: f32,
+}
+
+/// Largest distance in pixels between a round join or cap and the true arc.
+const ARC_TOLERANCE_PX: f32 = 0.25;
+
+/// Stroke a polyline into a triangle list.
+///
+/// Neighbouring segments share their inner corner and the outer gap is
+/// filled once by the join, so the mesh does not overlap itself at vertices
+/// and translucent lines blend evenly. Only a line crossing itself, or a turn
+/// so sharp that the inner corner would fall beyond a neighbouring segment,
+/// still overlaps. Repeated points are skipped, and non-finite points split
+/// the line into separately capped runs.
+pub fn stroke_polyline(points: &[[f32; 2]], style: &StrokeStyle) -> Vec<StrokeVertex> {
+    let mut mesh = Vec::new();
+    let mut travelled = 0.0_f64;
+    for run in points.split(|p| !(p[0].is_finite() && p[1].is_finite())) {
+        stroke_run(run, style, &mut travelled, &mut mesh);
+    }
+    mesh
+}
+
+struct Segment {
+    start: [f32; 2],
+    end: [f32; 2],
+    dir: [f32; 2],
+    normal: [f32; 2],
+    len: f32,
+    /// Distance along the line at `start`.
+    distance: f32,
+}
+
+fn stroke_run(
+    run: &[[f32; 2]],
+    style: &StrokeStyle,
+    travelled: &mut f64,
+    mesh: &mut Vec<StrokeVertex>,
+) {
+    let half = style.width.max(0.0) * 0.5;
+    if half <= 0.0 {
+        return;
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut start = match run.first() {
+        Some(&p) => p,
+        None => return,
+    };
+    for &end in &run[1..] {
+        let delta = sub(end, start);
+        let len = length(delta);
+        if len <= f32::EPSILON {
+            continue;
+        }
+        let dir = scale(delta, 1.0 / len);
+        let distance = if style.period > 0.0 {
+            travelled.rem_euclid(style.period as f64) as f32
+        } else {
+            *travelled as f32
+        };
+        segments.push(Segment {
+            start,
+            end,
+            dir,
+            normal: [-dir[1], dir[0]],
+            len,
+            distance,
+        });
+        *travelled += len as f64;
+        start = end;
+    }
+    if segments.is_empty() {
+        return;
+    }
+
+    // Corners of each segment as (left, right) pairs at its start and end,
+    // where left is along `normal`.
+    let mut starts: Vec<[[f32; 2]; 2]> = segments
+        .iter()
+        .map(|s| {
+            [
+                add(s.start, scale(s.normal, half)),
+                sub(s.start, scale(s.normal, half)),
+            ]
+        })
+        .collect();
+    let mut ends: Vec<[[f32; 2]; 2]> = segments
+        .iter()
+        .map(|s| {
+            [
+                add(s.end, scale(s.normal, half)),
+                sub(s.end, scale(s.normal, half)),
+            ]
+        })
+        .collect();
+
+    for i in 1..segments.len() {
+        let (prev, next) = (&segments[i - 1], &segments[i]);
+        let turn = cross(prev.dir, next.dir);
+        if turn.abs() <= 1e-6 && dot(prev.dir, next.dir) > 0.0 {
+            continue;
+        }
+
+        // A left turn has its outer edge on the right.
+        let outer = if turn > 0.0 { 1 } else { 0 };
+        let inner = 1 - outer;
+        let pivot = next.start;
+        let distance = next.distance;
+
+        // The inner edges meet at the inner miter point; use it as the shared
+        // corner unless it lies beyond either segment.
+        let bisector = add(prev.normal, next.normal);
+        let cos_half = dot(prev.normal, normalize(bisector));
+        let inner_reach = if cos_half > 1e-4 {
+            half * (1.0 - cos_half * cos_half).sqrt() / cos_half
+        } else {
+            f32::INFINITY
+        };
+        let inner_point = if inner_reach <= prev.len.min(next.len) {
+            let point = ends[i - 1][inner];
+            let point = sub(point, scale(prev.dir, inner_reach));
+            ends[i - 1][inner] = point;
+            starts[i][inner] = point;
+            Some(point)
+        } else {
+            None
+        };
+
+        let from = ends[i - 1][outer];
+        let to = starts[i][outer];
+        let at = |position| StrokeVertex { position, distance };
+        if let Some(point) = inner_point {
+            mesh.extend([at(point), at(from), at(pivot)]);
+            mesh.extend([at(point), at(pivot), at(to)]);
+        }
+        match style.join {
+            LineJoin::Bevel => mesh.extend([at(pivot), at(from), at(to)]),
+            LineJoin::Miter => {
+                let miter = if cos_half > 1e-4 {
+                    half / cos_half
+                } else {
+                    f32::INFINITY
+                };
+                if miter <= style.miter_limit * half {
+                    let side = if outer == 0 { 1.0 } else { -1.0 };
+                    let tip = add(pivot, scale(normalize(bisector), side * miter));
+                    mesh.extend([at(pivot), at(from), at(tip)]);
+                    mesh.extend([at(pivot), at(tip), at(to)]);
+                } else {
+                    mesh.extend([at(pivot), at(from), at(to)]);
+                }
+            }
+            LineJoin::Round => {
+                let sweep = cross(sub(from, pivot), sub(to, pivot))
+                    .atan2(dot(sub(from, pivot), sub(to, pivot)));
+                fan(pivot, from, to, sweep, half, distance, mesh);
+            }
+        }
+    }
+
+    let first = &segments[0];
+    let last = &segments[segments.len() - 1];
+    let end_distance = last.distance + last.len;
+    match style.cap {
+        LineCap::Butt => {}
+        LineCap::Square => {
+            let back = scale(first.dir, -half);
+            starts[0] = [add(starts[0][0], back), add(starts[0][1], back)];
+            let ahead = scale(last.dir, half);
+            let n = ends.len() - 1;
+            ends[n] = [add(ends[n][0], ahead), add(ends[n][1], ahead)];
+        }
+        LineCap::Round => {
+            // Counter-clockwise from left to right goes round the back of
+            // the start, and from right to left round the front of the end.
+            fan(
+                first.start,
+                starts[0][0],
+                starts[0][1],
+                PI,
+                half,
+                first.distance,
+                mesh,
+            );
+            let n = ends.len() - 1;
+            fan(
+                last.end,
+                ends[n][1],
+                ends[n][0],
+                PI,
+                half,
+                end_distance,
+                mesh,
+            );
+        }
+    }
+
+    for (i, segment) in segments.iter().enumerate() {
+        // Distances follow the projection onto the centre line so dashes
+        // stay square across moved corners and square caps.
+        let along = |p: [f32; 2]| segment.distance + dot(sub(p, segment.start), segment.dir);
+        let vertex = |position| StrokeVertex {
+            position,
+            distance: along(position),
+        };
+        let [l0, r0] = starts[i];
+        let [l1, r1] = ends[i];
+        mesh.extend([vertex(l0), vertex(r0), vertex(l1)]);
+        mesh.extend([vertex(l1), vertex(r0), vertex(r1)]);
+    }
+}
+
+/// Triangles filling the sector of radius `radius` around `center` that
+/// starts at `from` and turns by `sweep` radians (counter-clockwise when
+/// positive) to end at `to`.
+fn fan(
+    center: [f32; 2],
+    from: [f32; 2],
+    to: [f32; 2],
+    sweep: f32,
+    radius: f32,
+    distance: f32,
+    mesh: &mut Vec<StrokeVertex>,
+) {
+    let a0 = (from[1] - center[1]).atan2(from[0] - center[0]);
+
+    let step = if radius > ARC_TOLERANCE_PX {
+        2.0 * (1.0 - ARC_TOLERANCE_PX / radius).acos()
+    } else {
+        PI
+    };
+    let steps = ((sweep.abs() / step).ceil() as usize).clamp(1, 64);
+    let at = |position| StrokeVertex { position, distance };
+    let mut previous = from;
+    for k in 1..=steps {
+        let point = if k == steps {
+            to
+        } else {
+            let angle = a0 + sweep * k as f32 / steps as f32;
+            [
+                center[0] + radius * angle.cos(),
+                center[1] + radius * angle.sin(),
+            ]
+        };
+        mesh.extend([at(center), at(previous), at(point)]);
+        previous = point;
+    }
+}
+
+/// Move every edge of the closed polygon `points` inwards by `distance`,
+/// keeping corners sharp. Meant for outlines drawn inside a shape, so
+/// `distance` should stay well below the shape's size.
+pub fn inset_polygon(points: &[[f32; 2]], distance: f32) -> Vec<[f32; 2]> {
+    let n = points.len();
+    if n < 3 {
+        return points.to_vec();
+    }
+    let area: f32 = (0..n).map(|i| cross(points[i], points[(i + 1) % n])).sum();
+    // Inward normals point left of the edges of a counter-clockwise polygon.
+    let side = if area >= 0.0 { 1.0 } else { -1.0 };
+    let normal = |a: [f32; 2], b: [f32; 2]| {
+        let [dx, dy] = normalize(sub(b, a));
+        [-dy * side, dx * side]
+    };
+    (0..n)
+        .map(|i| {
+            let prev = points[(i + n - 1) % n];
+            let point = points[i];
+            let next = points[(i + 1) % n];
+            let (a, b) = (normal(prev, point), normal(point, next));
+            // Where the two moved edges meet.
+            let k = 1.0 + dot(a, b);
+            if k <= 1e-6 {
+                return add(point, scale(a, distance));
+            }
+            add(point, scale(add(a, b), distance / k))
+        })
+        .collect()
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn scale(a: [f32; 2], k: f32) -> [f32; 2] {
+    [a[0] * k, a[1] * k]
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+fn cross(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn length(a: [f32; 2]) -> f32 {
+    dot(a, a).sqrt()
+}
+
+fn normalize(a: [f32; 2]) -> [f32; 2] {
+    let len = length(a);
+    if len > 0.0 { scale(a, 1.0 / len) } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Total area of a triangle list, counting overlaps twice.
+    fn area(mesh: &[StrokeVertex]) -> f32 {
+        mesh.chunks(3)
+            .map(|t| {
+                let [a, b, c] = [t[0].position, t[1].position, t[2].position];
+                cross(sub(b, a), sub(c, a)).abs() * 0.5
+            })
+            .sum()
+    }
+
+    fn style(join: LineJoin, cap: LineCap) -> StrokeStyle {
+        StrokeStyle {
+            width: 2.0,
+            join,
+            cap,
+            ..StrokeStyle::default()
+        }
+    }
+
+    #[test]
+    fn right_angle_joins_cover_the_corner_once() {
+        let points = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]];
+        // Two 10x2 arms overlapping in a 1x1 inner square, plus the outer
+        // corner square for a miter or half of it for a bevel.
+        let bevel = area(&stroke_polyline(
+            &points,
+            &style(LineJoin::Bevel, LineCap::Butt),
+        ));
+        assert!((bevel - (40.0 - 1.0 + 0.5)).abs() < 1e-3, "{bevel}");
+        let miter = area(&stroke_polyline(
+            &points,
+            &style(LineJoin::Miter, LineCap::Butt),
+        ));
+        assert!((miter - 40.0).abs() < 1e-3, "{miter}");
+        let round = area(&stroke_polyline(
+            &points,
+            &style(LineJoin::Round, LineCap::Butt),
+        ));
+        assert!(round > 39.7 && round < 39.0 + PI / 4.0, "{round}");
+    }
+
+    #[test]
+    fn sharp_miters_fall_back_to_bevels() {
+        let points = [[0.0, 0.0], [10.0, 0.0], [0.0, 1.0]];
+        let miter = stroke_polyline(&points, &style(LineJoin::Miter, LineCap::Butt));
+        let bevel = stroke_polyline(&points, &style(LineJoin::Bevel, LineCap::Butt));
+        assert_eq!(miter.len(), bevel.len());
+    }
+
+    #[test]
+    fn caps_extend_the_ends() {
+        let points = [[0.0, 0.0], [10.0, 0.0]];
+        let butt = area(&stroke_polyline(
+            &points,
+            &style(LineJoin::Round, LineCap::Butt),
+        ));
+        let square = area(&stroke_polyline(
+            &points,
+            &style(LineJoin::Round, LineCap::Square),
+        ));
+        let round = area(&stroke_polyline(
+            &points,
+            &style(LineJoin::Round, LineCap::Round),
+        ));
+        assert!((butt - 20.0).abs() < 1e-4);
+        assert!((square - 24.0).abs() < 1e-4);
+        // The arcs are polygons, so they cover a little less than a circle.
+        assert!(round > 20.0 + 0.8 * PI && round < 20.0 + PI, "{round}");
+    }
+
+    #[test]
+    fn inset_moves_edges_inwards_in_either_winding() {
+        let square = [[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 4.0]];
+        let expected = vec![[1.0, 1.0], [3.0, 1.0], [3.0, 3.0], [1.0, 3.0]];
+        assert_eq!(inset_polygon(&square, 1.0), expected);
+        let mut reversed = square;
+        reversed.reverse();
+        let mut inset = inset_polygon(&reversed, 1.0);
+        inset.reverse();
+        assert_eq!(inset, expected);
+    }
+
+    #[test]
+    fn distances_run_along_the_line_and_wrap_at_the_period() {
+        let points = [
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 10.0],
+            [f32::NAN, 0.0],
+            [0.0, 5.0],
+            [3.0, 5.0],
+        ];
+        let stroke = StrokeStyle {
+            period: 8.0,
+            ..style(LineJoin::Bevel, LineCap::Butt)
+        };
+        let mesh = stroke_polyline(&points, &stroke);
+        let max = mesh.iter().map(|v| v.distance).fold(0.0, f32::max);
+        assert!(max <= 8.0 + 10.0 + 1e-4);
+        // The second run starts at 20 px, i.e. 4 px into the third period.
+        let last = &mesh[mesh.len() - 6..];
+        let starts: Vec<f32> = last
+            .iter()
+            .filter(|v| v.position[0] == 0.0)
+            .map(|v| v.distance)
+            .collect();
+        assert!(starts.iter().all(|d| (d - 4.0).abs() < 1e-4), "{starts:?}");
+    }
+}
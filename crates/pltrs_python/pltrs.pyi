@@ -1,36 +1,83 @@
-from typing import Iterable, Sequence, TypeAlias
+from datetime import datetime
+from typing import Any, Iterable, Literal, Mapping, Sequence, TypeAlias
 
-Point: TypeAlias = tuple[float, float]
+XValue: TypeAlias = float | datetime | Any  # numpy.datetime64 is accepted too
+Point: TypeAlias = tuple[XValue | str, float | None]  # str x values are categories
 RGB: TypeAlias = tuple[float, float, float]
 Annotation: TypeAlias = tuple[float, float, str]
-SeriesData: TypeAlias = Iterable[float] | Iterable[Point]
+SeriesData: TypeAlias = Iterable[float | None] | Iterable[Point] | Mapping[str, float]  # None / NaN are missing samples
 MultiSeriesData: TypeAlias = Iterable[SeriesData]
+LegendLoc: TypeAlias = Literal["best", "upper right", "upper left", "lower left", "lower right", "outside right"]
+Colormap: TypeAlias = Literal["viridis", "magma", "plasma", "cividis", "coolwarm"] | str  # "_r" reverses
+Marker: TypeAlias = Literal[
+    "circle", "square", "triangle_up", "triangle_down", "triangle_left", "triangle_right",
+    "diamond", "plus", "cross", "star", "hexagon", "pentagon",
+    "o", "s", "^", "v", "<", ">", "D", "+", "x", "*", "h", "p",
+]
+LineStyle: TypeAlias = Literal["solid", "dashed", "dotted", "dashdot", "-", "--", ":", "-."]
+AxisScale: TypeAlias = Literal["linear", "log", "log2", "symlog", "time"] | tuple[Literal["log", "symlog"], float]
 
 class Line:
     def __init__(
         self,
         data: SeriesData | MultiSeriesData,
         *,
-        x: tuple[float, float] | None = ...,
+        x: tuple[XValue, XValue] | None = ...,
         y: tuple[float, float] | None = ...,
+        xscale: AxisScale | None = ...,
+        yscale: AxisScale | None = ...,
+        xinvert: bool = ...,
+        yinvert: bool = ...,
         color: RGB | Sequence[RGB] | None = ...,
         width: float | Sequence[float] | None = ...,
+        linestyle: LineStyle | Sequence[float] | Sequence[LineStyle | Sequence[float]] | None = ...,
+        joinstyle: Literal["round", "miter", "bevel"] | None = ...,
+        capstyle: Literal["butt", "round", "square", "projecting"] | None = ...,
+        miterlimit: float = ...,
         annotations: Sequence[Annotation] | None = ...,
         title: str | None = ...,
         x_label: str | None = ...,
         y_label: str | None = ...,
         grid: bool = ...,
+        label: str | Sequence[str | None] | None = ...,
+        legend: bool | LegendLoc | None = ...,
+        legend_autoscale: bool = ...,
     ) -> None: ...
-    def show(self) -> None: ...
-    def save(self, path: str | None = ...) -> None: ...
+    def twinx(self, other: Line | Bar | Scatter) -> None: ...
+    def show(self, *, samples: Literal[1, 4, 8] = ...) -> None: ...
+    def save(
+        self,
+        path: str | None = ...,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> None: ...
+    def to_png(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> bytes: ...
+    def to_rgba(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> memoryview: ...
 
 class Bar:
     def __init__(
         self,
         data: SeriesData | MultiSeriesData,
         *,
-        x: tuple[float, float] | None = ...,
+        x: tuple[XValue, XValue] | None = ...,
         y: tuple[float, float] | None = ...,
+        yscale: AxisScale | None = ...,
+        xinvert: bool = ...,
+        yinvert: bool = ...,
         color: RGB | Sequence[RGB] | None = ...,
         width: float | Sequence[float] | None = ...,
         annotations: Sequence[Annotation] | None = ...,
@@ -38,29 +85,134 @@ class Bar:
         x_label: str | None = ...,
         y_label: str | None = ...,
         grid: bool = ...,
+        label: str | Sequence[str | None] | None = ...,
+        legend: bool | LegendLoc | None = ...,
+        legend_autoscale: bool = ...,
     ) -> None: ...
-    def show(self) -> None: ...
-    def save(self, path: str | None = ...) -> None: ...
+    def twinx(self, other: Line | Bar | Scatter) -> None: ...
+    def show(self, *, samples: Literal[1, 4, 8] = ...) -> None: ...
+    def save(
+        self,
+        path: str | None = ...,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> None: ...
+    def to_png(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> bytes: ...
+    def to_rgba(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> memoryview: ...
 
 class Scatter:
     def __init__(
         self,
         data: SeriesData | MultiSeriesData,
         *,
-        x: tuple[float, float] | None = ...,
+        x: tuple[XValue, XValue] | None = ...,
         y: tuple[float, float] | None = ...,
+        xscale: AxisScale | None = ...,
+        yscale: AxisScale | None = ...,
+        xinvert: bool = ...,
+        yinvert: bool = ...,
         color: RGB | Sequence[RGB] | None = ...,
         size: float | Sequence[float] | None = ...,
-        marker: str | Sequence[str] | None = ...,
+        marker: Marker | Sequence[Marker] | None = ...,
+        edgecolor: RGB | Sequence[RGB] | None = ...,
+        edgewidth: float | Sequence[float] | None = ...,
+        alpha: float = ...,
+        c: Sequence[float] | Sequence[Sequence[float]] | None = ...,
+        cmap: Colormap | Sequence[RGB] | None = ...,
+        vmin: float | None = ...,
+        vmax: float | None = ...,
+        colorbar: bool | str | None = ...,
+        s: Sequence[float] | Sequence[Sequence[float]] | None = ...,
+        s_range: tuple[float, float] | None = ...,
+        s_area: bool = ...,
+        size_legend: bool | LegendLoc | None = ...,
+        size_label: str | None = ...,
         annotations: Sequence[Annotation] | None = ...,
         title: str | None = ...,
         x_label: str | None = ...,
         y_label: str | None = ...,
         grid: bool = ...,
+        label: str | Sequence[str | None] | None = ...,
+        legend: bool | LegendLoc | None = ...,
+        legend_autoscale: bool = ...,
+    ) -> None: ...
+    def twinx(self, other: Line | Bar | Scatter) -> None: ...
+    def show(self, *, samples: Literal[1, 4, 8] = ...) -> None: ...
+    def save(
+        self,
+        path: str | None = ...,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
     ) -> None: ...
-    def show(self) -> None: ...
-    def save(self, path: str | None = ...) -> None: ...
+    def to_png(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> bytes: ...
+    def to_rgba(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> memoryview: ...
+
+class Figure:
+    def __init__(
+        self,
+        rows: int = ...,
+        cols: int = ...,
+        *,
+        size: tuple[int, int] | None = ...,
+        wspace: float | None = ...,
+        hspace: float | None = ...,
+        sharex: bool | Literal["none", "all", "row", "col"] | None = ...,
+        sharey: bool | Literal["none", "all", "row", "col"] | None = ...,
+    ) -> None: ...
+    def add(self, plot: Line | Bar | Scatter, row: int, col: int) -> None: ...
+    def show(self, *, samples: Literal[1, 4, 8] = ...) -> None: ...
+    def save(
+        self,
+        path: str | None = ...,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> None: ...
+    def to_png(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> bytes: ...
+    def to_rgba(
+        self,
+        *,
+        dpi: float = ...,
+        samples: Literal[1, 4, 8] = ...,
+        renderer: Literal["auto", "gpu", "cpu"] = ...,
+    ) -> memoryview: ...
 
-def show() -> None: ...
+def show(*, samples: Literal[1, 4, 8] = ...) -> None: ...
+def save_pdf(path: str) -> None: ...
 def demo_line() -> None: ...
 def demo_scatter() -> None: ...
@@ -1,33 +1,39 @@
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
-use pyo3::types::PyIterator;
+use pyo3::types::{PyBytes, PyIterator};
 
-use pltrs_backend_wgpu::{run_with_plot, save_figure_png};
-use pltrs_core::{plot::PlotDefinition, Color};
+use pltrs_backend_wgpu::run_with_plot;
+use pltrs_core::{plot::PlotDefinition, scale::ScaleKind, Color, Figure};
 
 use crate::data::{
-    compute_limits, extract_rgb, parse_series_collection, resolve_numeric_arg, try_extract_rgb,
+    default_x_scale, extract_rgb, parse_legend, parse_limits, parse_scale, parse_series_collection,
+    resolve_labels, resolve_limits, resolve_numeric_arg, try_extract_rgb, x_categories, XKind,
 };
-use crate::plot::{bar_series, build_plot_definition, PlotOptions};
+use crate::plot::{attach_twin, bar_series, build_plot_definition, PlotOptions};
 use crate::{
-    map_backend_error, next_figure_id, register_handle, resolve_output_path,
+    check_renderer, check_samples, dpi_scale, map_backend_error, next_figure_id, output_format,
+    png_bytes, register_handle, render_figure, resolve_output_path, rgba_view, save_figure,
     take_registered_handle, PlotHandle,
 };
 
 #[pyclass(name = "Bar")]
 pub struct PyBar {
-    id: u64,
-    plot: PlotDefinition,
+    pub(crate) id: u64,
+    pub(crate) plot: PlotDefinition,
 }
 
 #[pymethods]
 impl PyBar {
     #[new]
-    #[pyo3(signature = (data, *, x=None, y=None, color=None, width=None, annotations=None, title=None, x_label=None, y_label=None, grid=true))]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (data, *, x=None, y=None, yscale=None, xinvert=false, yinvert=false, color=None, width=None, annotations=None, title=None, x_label=None, y_label=None, grid=true, label=None, legend=None, legend_autoscale=false))]
     fn new(
         data: &Bound<'_, PyAny>,
-        x: Option<(f64, f64)>,
+        x: Option<&Bound<'_, PyAny>>,
         y: Option<(f64, f64)>,
+        yscale: Option<&Bound<'_, PyAny>>,
+        xinvert: bool,
+        yinvert: bool,
         color: Option<&Bound<'_, PyAny>>,
         width: Option<&Bound<'_, PyAny>>,
         annotations: Option<Vec<(f64, f64, String)>>,
@@ -35,10 +41,17 @@ impl PyBar {
         x_label: Option<String>,
         y_label: Option<String>,
         grid: bool,
+        label: Option<&Bound<'_, PyAny>>,
+        legend: Option<&Bound<'_, PyAny>>,
+        legend_autoscale: bool,
     ) -> PyResult<Self> {
         let series = parse_series_collection(data)?;
         let series_count = series.len();
+        let x_scale = default_x_scale(&series);
+        let x_categories = x_categories(&series);
         let colors = resolve_bar_colors(color, series_count)?;
+        let labels = resolve_labels(label, series_count)?;
+        let legend = parse_legend(legend, labels.iter().any(Option::is_some), "legend")?;
         let widths = resolve_bar_widths(width, &series, series_count)?;
         let shifted_series = shift_series_for_grouping(series, &widths);
 
@@ -58,53 +71,146 @@ impl PyBar {
             .collect();
         all_ys.push(0.0);
 
-        let xlim = x.unwrap_or_else(|| compute_limits(&all_xs, 0.05));
-        let ylim = y.unwrap_or_else(|| compute_limits(&all_ys, 0.05));
+        let y_scale = parse_scale(yscale, ScaleKind::Linear, "yscale")?;
+        let xlim = resolve_limits(parse_limits(x, "x")?, &all_xs, 0.05, x_scale, xinvert, "x")?;
+        let ylim = resolve_limits(y, &all_ys, 0.05, y_scale, yinvert, "y")?;
         let id = next_figure_id();
 
         let plot = build_plot_definition(
             PlotOptions {
                 xlim,
                 ylim,
+                x_scale,
+                y_scale,
+                x_categories,
                 annotations: annotations.unwrap_or_default(),
                 title,
                 x_label,
                 y_label,
                 grid,
+                legend,
+                legend_autoscale,
+                colorbar: None,
+                size_legend: None,
             },
             shifted_series
                 .into_iter()
                 .zip(colors)
                 .zip(widths.iter().copied())
-                .map(|((series, (r, g, b)), bar_width)| {
-                    bar_series(series.xs, series.ys, Color { r, g, b, a: 0.92 }, bar_width)
+                .zip(labels)
+                .map(|(((series, (r, g, b)), bar_width), label)| {
+                    bar_series(
+                        series.xs,
+                        series.ys,
+                        Color { r, g, b, a: 0.92 },
+                        bar_width,
+                        label,
+                    )
                 })
                 .collect(),
         );
 
-        register_handle(id, PlotHandle::Plot(plot.clone()));
+        register_handle(id, PlotHandle::Plot(Box::new(plot.clone())));
         Ok(Self { id, plot })
     }
 
-    fn show(&self) -> PyResult<()> {
-        let plot = match take_registered_handle(self.id) {
-            Some(PlotHandle::Plot(plot)) => plot,
-            Some(PlotHandle::Figure(_)) => self.plot.clone(),
-            None => self.plot.clone(),
-        };
-        run_with_plot(plot).map_err(map_backend_error)
+    /// Plot the series of another `Line`, `Scatter` or `Bar` against a
+    /// right-hand y axis of this plot, with that plot's y limits, y scale and
+    /// `y_label`. Both plots must use the same x scale; the x limits grow to
+    /// cover both. The other plot is no longer shown on its own.
+    fn twinx(&mut self, other: &Bound<'_, PyAny>) -> PyResult<()> {
+        attach_twin(self.id, &mut self.plot, other)
     }
 
-    #[pyo3(signature = (path=None))]
-    fn save(&self, py: Python<'_>, path: Option<&str>) -> PyResult<()> {
+    /// Render this figure in a window. `samples` is the number of samples
+    /// per pixel used to smooth edges: 1 (no anti-aliasing), 4 or 8.
+    #[pyo3(signature = (*, samples=4))]
+    fn show(&self, samples: u32) -> PyResult<()> {
+        let samples = check_samples(samples)?;
         let plot = match take_registered_handle(self.id) {
-            Some(PlotHandle::Plot(plot)) => plot,
-            Some(PlotHandle::Figure(_)) => self.plot.clone(),
-            None => self.plot.clone(),
+            Some(PlotHandle::Plot(plot)) => *plot,
+            _ => self.plot.clone(),
         };
-        let fig = plot.build_figure(&plot.initial_view());
+        run_with_plot(plot, samples).map_err(map_backend_error)
+    }
+
+    /// Save this figure as a PNG, or as a vector SVG or PDF when `path`
+    /// ends in `.svg` or `.pdf`.
+    ///
+    /// `dpi` sets the resolution of PNGs: sizes are given at 100 dpi, so
+    /// `dpi=200` saves the same layout at twice the width and height in
+    /// pixels. `samples` is the number of samples per pixel used to smooth
+    /// edges: 1 (no anti-aliasing), 4 or 8. `renderer` picks what draws
+    /// PNGs: `"gpu"`, `"cpu"` for the software renderer that needs no GPU,
+    /// or `"auto"` for the GPU when there is one and the CPU otherwise.
+    #[pyo3(signature = (path=None, *, dpi=100.0, samples=4, renderer="auto"))]
+    fn save(
+        &self,
+        py: Python<'_>,
+        path: Option<&str>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<()> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
         let output_path = resolve_output_path(py, path)?;
-        save_figure_png(&fig, &output_path).map_err(|e| PyRuntimeError::new_err(format!("{e}")))
+        output_format(&output_path)?;
+        let fig = self.take_figure(scale);
+        save_figure(&fig, &output_path, samples, renderer)
+    }
+
+    /// Render this figure as the bytes of a PNG file, as `save()` would
+    /// write it, for serving or embedding without a file on disk. Takes the
+    /// same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_png<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale);
+        let image = render_figure(&fig, samples, renderer)?;
+        png_bytes(py, &image)
+    }
+
+    /// Render this figure to 8-bit RGBA pixels, rows top to bottom, as a
+    /// read-only memoryview of shape `(height, width, 4)`. `bytes()` of it
+    /// gives the raw pixels and `numpy.asarray()` an array without a copy.
+    /// Takes the same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_rgba<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale);
+        let image = render_figure(&fig, samples, renderer)?;
+        rgba_view(py, &image)
+    }
+}
+
+impl PyBar {
+    /// Take this plot out of the queue of `show()` and lay it out at
+    /// `scale` device pixels per logical pixel.
+    fn take_figure(&self, scale: f32) -> Figure {
+        let mut plot = match take_registered_handle(self.id) {
+            Some(PlotHandle::Plot(plot)) => *plot,
+            _ => self.plot.clone(),
+        };
+        plot.size.dpi = scale;
+        plot.build_figure(&plot.initial_view())
     }
 }
 
@@ -153,7 +259,11 @@ fn resolve_bar_widths(
     series: &[crate::data::SeriesData],
     series_count: usize,
 ) -> PyResult<Vec<f32>> {
-    let inferred = infer_group_span(series) / series_count.max(1) as f64 * 0.9;
+    let inferred = match series.first().map(|series| series.x_kind) {
+        // A group of bars fills most of its category slot.
+        Some(XKind::Categorical) => 0.8 / series_count.max(1) as f64,
+        _ => infer_group_span(series) / series_count.max(1) as f64 * 0.9,
+    };
     resolve_numeric_arg(width, series_count, inferred as f32, "width")
 }
 
@@ -1,11 +1,25 @@
+use pltrs_core::{colormap::Colormap, legend::LegendLoc, scale::ScaleKind, Color};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyIterator;
+use pyo3::types::{PyDateTime, PyDict, PyIterator, PyString};
 
 #[derive(Debug, Clone)]
 pub struct SeriesData {
     pub xs: Vec<f64>,
     pub ys: Vec<f64>,
+    pub x_kind: XKind,
+    /// Category names for categorical x values; `xs` holds indices into it.
+    pub categories: Vec<String>,
+}
+
+/// What the x values of a series were given as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XKind {
+    Numeric,
+    /// Datetimes, stored as seconds since the Unix epoch.
+    Time,
+    /// Strings, stored as slot indices.
+    Categorical,
 }
 
 /// Parse a Python object into separate x and y vectors.
@@ -13,43 +27,21 @@ pub struct SeriesData {
 /// Accepted formats:
 /// - A list of `(x, y)` tuples or `[x, y]` lists  →  `[(1.0, 2.0), (3.0, 4.0)]`
 /// - A list of scalars (1-D)  →  x is inferred as `[0, 1, 2, ...]`
+/// - A dict mapping x to y  →  `{"api": 3.0, "db": 5.0}`
 ///
-/// Returns `(xs, ys)` where both are `Vec<f64>`.
-pub fn parse_data(obj: &Bound<'_, PyAny>) -> PyResult<(Vec<f64>, Vec<f64>)> {
+/// The x value of a pair may also be a `datetime.datetime` or a numpy
+/// `datetime64`, which is stored as epoch seconds (naive datetimes are read
+/// as UTC), or a string, which becomes a category.
+pub fn parse_data(obj: &Bound<'_, PyAny>) -> PyResult<SeriesData> {
     // Try to iterate — everything we accept is iterable.
-    let iter = PyIterator::from_object(obj)
+    let iter = PyIterator::from_object(&dict_items(obj)?)
         .map_err(|_| PyValueError::new_err("data must be an iterable (list, tuple, or array)"))?;
 
-    let mut xs = Vec::new();
-    let mut ys = Vec::new();
-    let mut is_2d: Option<bool> = None;
-
+    let mut points = PointCollector::default();
     for (i, item) in iter.enumerate() {
-        let item = item?;
-
-        // Probe first element to decide 1-D vs 2-D.
-        if is_2d.is_none() {
-            is_2d = Some(is_pair(&item));
-        }
-
-        if is_2d == Some(true) {
-            let (x, y) = extract_pair(&item)?;
-            xs.push(x);
-            ys.push(y);
-        } else {
-            let y: f64 = item
-                .extract()
-                .map_err(|_| PyValueError::new_err("data elements must be numeric"))?;
-            xs.push(i as f64);
-            ys.push(y);
-        }
-    }
-
-    if xs.is_empty() {
-        return Err(PyValueError::new_err("data must not be empty"));
+        points.push(i, &item?)?;
     }
-
-    Ok((xs, ys))
+    points.finish()
 }
 
 /// Parse either a single data series or an iterable of data series.
@@ -59,7 +51,7 @@ pub fn parse_data(obj: &Bound<'_, PyAny>) -> PyResult<(Vec<f64>, Vec<f64>)> {
 /// - `[[(0, 1), (1, 2)], [(0, 2), (1, 1)]]`
 pub fn parse_series_collection(obj: &Bound<'_, PyAny>) -> PyResult<Vec<SeriesData>> {
     let py = obj.py();
-    let items: Vec<Py<PyAny>> = PyIterator::from_object(obj)
+    let items: Vec<Py<PyAny>> = PyIterator::from_object(&dict_items(obj)?)
         .map_err(|_| PyValueError::new_err("data must be an iterable (list, tuple, or array)"))?
         .map(|item| item.map(Bound::unbind))
         .collect::<PyResult<_>>()?;
@@ -68,19 +60,51 @@ pub fn parse_series_collection(obj: &Bound<'_, PyAny>) -> PyResult<Vec<SeriesDat
         return Err(PyValueError::new_err("data must not be empty"));
     }
 
-    match classify_top_level_item(items[0].bind(py))? {
-        TopLevelItem::Scalar | TopLevelItem::PointPair => {
-            let (xs, ys) = parse_series_items(&items, py)?;
-            Ok(vec![SeriesData { xs, ys }])
-        }
+    let mut series = match classify_top_level_item(items[0].bind(py))? {
+        TopLevelItem::Scalar | TopLevelItem::PointPair => vec![parse_series_items(&items, py)?],
         TopLevelItem::Series => items
             .iter()
-            .map(|item| {
-                let (xs, ys) = parse_data(item.bind(py))?;
-                Ok(SeriesData { xs, ys })
-            })
-            .collect(),
+            .map(|item| parse_data(item.bind(py)))
+            .collect::<PyResult<_>>()?,
+    };
+    unify_categories(&mut series)?;
+    Ok(series)
+}
+
+/// Give every categorical series the same category list, in order of first
+/// appearance, and remap its x indices into that list.
+fn unify_categories(series: &mut [SeriesData]) -> PyResult<()> {
+    let categorical = series
+        .iter()
+        .filter(|series| series.x_kind == XKind::Categorical)
+        .count();
+    if categorical == 0 {
+        return Ok(());
     }
+    if categorical != series.len() {
+        return Err(PyValueError::new_err(
+            "either all series or none must use string x values",
+        ));
+    }
+
+    let mut categories: Vec<String> = Vec::new();
+    for series in series.iter_mut() {
+        for x in &mut series.xs {
+            let name = &series.categories[*x as usize];
+            let idx = match categories.iter().position(|known| known == name) {
+                Some(idx) => idx,
+                None => {
+                    categories.push(name.clone());
+                    categories.len() - 1
+                }
+            };
+            *x = idx as f64;
+        }
+    }
+    for series in series.iter_mut() {
+        series.categories = categories.clone();
+    }
+    Ok(())
 }
 
 pub(crate) fn resolve_numeric_arg(
@@ -119,6 +143,103 @@ pub(crate) fn resolve_numeric_arg(
     }
 }
 
+/// Legend labels per series from a single string or one entry per series;
+/// `None` entries leave a series out of the legend.
+pub(crate) fn resolve_labels(
+    value: Option<&Bound<'_, PyAny>>,
+    series_count: usize,
+) -> PyResult<Vec<Option<String>>> {
+    let Some(obj) = value else {
+        return Ok(vec![None; series_count]);
+    };
+
+    if let Ok(single) = obj.extract::<String>() {
+        if series_count > 1 {
+            return Err(PyValueError::new_err(format!(
+                "label expected {series_count} values, got a single string"
+            )));
+        }
+        return Ok(vec![Some(single)]);
+    }
+
+    let labels = PyIterator::from_object(obj)
+        .map_err(|_| PyValueError::new_err("label must be a string or iterable of strings"))?
+        .map(|item| {
+            item?
+                .extract::<Option<String>>()
+                .map_err(|_| PyValueError::new_err("label values must be strings or None"))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    if labels.len() != series_count {
+        return Err(PyValueError::new_err(format!(
+            "label expected {series_count} values, got {}",
+            labels.len()
+        )));
+    }
+
+    Ok(labels)
+}
+
+/// Parse a colormap given by name, such as `"viridis"` or `"magma_r"`, or as
+/// a list of RGB stops from low to high values. Defaults to viridis.
+pub(crate) fn parse_colormap(value: Option<&Bound<'_, PyAny>>) -> PyResult<Colormap> {
+    let Some(obj) = value else {
+        return Ok(Colormap::default());
+    };
+
+    if let Ok(name) = obj.extract::<String>() {
+        return Colormap::by_name(&name).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "unknown cmap '{name}', expected one of {} (optionally with '_r') or a list of RGB tuples",
+                Colormap::NAMES.join(", ")
+            ))
+        });
+    }
+
+    let stops = PyIterator::from_object(obj)
+        .map_err(|_| PyValueError::new_err("cmap must be a name or iterable of RGB tuples"))?
+        .map(|item| {
+            let (r, g, b) = extract_rgb(&item?)?;
+            Ok(Color { r, g, b, a: 1.0 })
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Colormap::from_colors(stops)
+        .ok_or_else(|| PyValueError::new_err("cmap needs at least one color"))
+}
+
+/// Parse a legend location such as `"best"` or `"upper left"`, or a bool.
+/// Without a value the legend is shown at `"best"` if `shown_by_default`.
+pub(crate) fn parse_legend(
+    value: Option<&Bound<'_, PyAny>>,
+    shown_by_default: bool,
+    name: &str,
+) -> PyResult<Option<LegendLoc>> {
+    let Some(obj) = value else {
+        return Ok(shown_by_default.then_some(LegendLoc::Best));
+    };
+
+    if let Ok(show) = obj.extract::<bool>() {
+        return Ok(show.then_some(LegendLoc::Best));
+    }
+    let loc = obj.extract::<String>().map_err(|_| {
+        PyValueError::new_err(format!("{name} must be a location string or a bool"))
+    })?;
+
+    match loc.as_str() {
+        "best" => Ok(Some(LegendLoc::Best)),
+        "upper right" => Ok(Some(LegendLoc::UpperRight)),
+        "upper left" => Ok(Some(LegendLoc::UpperLeft)),
+        "lower left" => Ok(Some(LegendLoc::LowerLeft)),
+        "lower right" => Ok(Some(LegendLoc::LowerRight)),
+        "outside right" => Ok(Some(LegendLoc::OutsideRight)),
+        other => Err(PyValueError::new_err(format!(
+            "unknown {name} location '{other}', expected 'best', 'upper right', 'upper left', \
+             'lower left', 'lower right' or 'outside right'"
+        ))),
+    }
+}
+
 pub(crate) fn try_extract_rgb(obj: &Bound<'_, PyAny>) -> PyResult<Option<(f32, f32, f32)>> {
     if obj.len().ok() != Some(3) || obj.is_instance_of::<pyo3::types::PyString>() {
         return Ok(None);
@@ -127,7 +248,10 @@ pub(crate) fn try_extract_rgb(obj: &Bound<'_, PyAny>) -> PyResult<Option<(f32, f
     let first = obj.get_item(0)?;
     let second = obj.get_item(1)?;
     let third = obj.get_item(2)?;
-    if first.extract::<f32>().is_err() || second.extract::<f32>().is_err() || third.extract::<f32>().is_err() {
+    if first.extract::<f32>().is_err()
+        || second.extract::<f32>().is_err()
+        || third.extract::<f32>().is_err()
+    {
         return Ok(None);
     }
 
@@ -150,6 +274,184 @@ pub(crate) fn extract_rgb(obj: &Bound<'_, PyAny>) -> PyResult<(f32, f32, f32)> {
     Ok((r, g, b))
 }
 
+/// Parse an axis scale such as `"linear"`, `"log"`, `("log", base)`,
+/// `"symlog"`, `("symlog", linthresh)` or `"time"`, falling back to `default`.
+pub(crate) fn parse_scale(
+    value: Option<&Bound<'_, PyAny>>,
+    default: ScaleKind,
+    name: &str,
+) -> PyResult<ScaleKind> {
+    let Some(obj) = value else {
+        return Ok(default);
+    };
+
+    let (scale, param) = if let Ok(scale) = obj.extract::<String>() {
+        (scale, None)
+    } else if let Ok((scale, param)) = obj.extract::<(String, f64)>() {
+        (scale, Some(param))
+    } else {
+        return Err(PyValueError::new_err(format!(
+            "{name} must be a scale name or a ('log', base) or ('symlog', linthresh) tuple"
+        )));
+    };
+
+    match (scale.as_str(), param) {
+        ("linear", None) => Ok(ScaleKind::Linear),
+        ("log", None) => Ok(ScaleKind::LOG10),
+        ("log", Some(base)) => {
+            if !(base.is_finite() && base > 0.0 && base != 1.0) {
+                return Err(PyValueError::new_err(format!(
+                    "{name} log base must be positive and not 1, got {base}"
+                )));
+            }
+            Ok(ScaleKind::Log { base })
+        }
+        ("log2", None) => Ok(ScaleKind::Log { base: 2.0 }),
+        ("time", None) => Ok(ScaleKind::Time),
+        ("symlog", linthresh) => {
+            let linthresh = linthresh.unwrap_or(1.0);
+            if !(linthresh.is_finite() && linthresh > 0.0) {
+                return Err(PyValueError::new_err(format!(
+                    "{name} symlog threshold must be positive, got {linthresh}"
+                )));
+            }
+            Ok(ScaleKind::SymLog {
+                base: 10.0,
+                linthresh,
+            })
+        }
+        (other, _) => Err(PyValueError::new_err(format!(
+            "unknown {name} '{other}', expected 'linear', 'log', 'log2', 'symlog' or 'time'"
+        ))),
+    }
+}
+
+/// The scale an x axis gets when none is requested: time for datetimes,
+/// categorical for strings and linear otherwise.
+pub(crate) fn default_x_scale(series: &[SeriesData]) -> ScaleKind {
+    match series.first().map(|series| series.x_kind) {
+        Some(XKind::Time) => ScaleKind::Time,
+        Some(XKind::Categorical) => ScaleKind::Categorical,
+        _ => ScaleKind::Linear,
+    }
+}
+
+/// Resolve the `xscale` argument against the parsed data.
+pub(crate) fn resolve_x_scale(
+    value: Option<&Bound<'_, PyAny>>,
+    series: &[SeriesData],
+) -> PyResult<ScaleKind> {
+    let default = default_x_scale(series);
+    if default == ScaleKind::Categorical && value.is_some() {
+        return Err(PyValueError::new_err(
+            "xscale cannot be set when x values are strings",
+        ));
+    }
+    parse_scale(value, default, "xscale")
+}
+
+/// Category names shared by all series, empty unless x values were strings.
+pub(crate) fn x_categories(series: &[SeriesData]) -> Vec<String> {
+    series
+        .first()
+        .map(|series| series.categories.clone())
+        .unwrap_or_default()
+}
+
+/// Parse explicit `(min, max)` axis limits, accepting datetimes as well as
+/// numbers.
+pub(crate) fn parse_limits(
+    value: Option<&Bound<'_, PyAny>>,
+    name: &str,
+) -> PyResult<Option<(f64, f64)>> {
+    let Some(obj) = value else {
+        return Ok(None);
+    };
+    if !is_pair(obj) {
+        return Err(PyValueError::new_err(format!(
+            "{name} must be a (min, max) tuple"
+        )));
+    }
+    let bound = |item: Bound<'_, PyAny>| match extract_x(&item)? {
+        XValue::Number(value) | XValue::Time(value) => Ok(value),
+        XValue::Category(_) => Err(PyValueError::new_err(format!(
+            "{name} limits must be numbers or datetimes"
+        ))),
+    };
+    Ok(Some((bound(obj.get_item(0)?)?, bound(obj.get_item(1)?)?)))
+}
+
+/// Resolve axis limits for `scale`, either from an explicit `(min, max)` or
+/// inferred from the data.
+///
+/// On non-linear scales the padding is applied in the transformed space, and
+/// values that cannot be shown (zero or negative on a log axis) are ignored.
+/// With `invert`, the limits are returned high-to-low so the axis is flipped.
+pub(crate) fn resolve_limits(
+    explicit: Option<(f64, f64)>,
+    vals: &[f64],
+    padding: f64,
+    scale: ScaleKind,
+    invert: bool,
+    name: &str,
+) -> PyResult<(f64, f64)> {
+    let limits = infer_limits(explicit, vals, padding, scale, name)?;
+    if invert {
+        Ok((limits.0.max(limits.1), limits.0.min(limits.1)))
+    } else {
+        Ok(limits)
+    }
+}
+
+fn infer_limits(
+    explicit: Option<(f64, f64)>,
+    vals: &[f64],
+    padding: f64,
+    scale: ScaleKind,
+    name: &str,
+) -> PyResult<(f64, f64)> {
+    if let Some(limits) = explicit {
+        if !scale.accepts(limits.0) || !scale.accepts(limits.1) {
+            let required = match scale {
+                ScaleKind::Log { .. } => "positive on a log axis",
+                ScaleKind::Linear
+                | ScaleKind::SymLog { .. }
+                | ScaleKind::Time
+                | ScaleKind::Categorical => "finite",
+            };
+            return Err(PyValueError::new_err(format!(
+                "{name} limits must be {required}, got {limits:?}"
+            )));
+        }
+        return Ok(limits);
+    }
+
+    if matches!(scale, ScaleKind::Linear | ScaleKind::Time) {
+        return Ok(compute_limits(vals, padding));
+    }
+
+    if scale == ScaleKind::Categorical {
+        // Show whole slots from the first to the last used category.
+        let (lo, hi) = compute_limits(vals, 0.0);
+        return Ok((lo.round() - 0.5, hi.round() + 0.5));
+    }
+
+    let transformed: Vec<f64> = vals
+        .iter()
+        .copied()
+        .filter(|v| scale.accepts(*v))
+        .map(|v| scale.forward(v))
+        .collect();
+    if transformed.is_empty() {
+        return Err(PyValueError::new_err(format!(
+            "{name} data has no positive values to show on a log axis"
+        )));
+    }
+
+    let (lo, hi) = compute_limits(&transformed, padding);
+    Ok((scale.inverse(lo), scale.inverse(hi)))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TopLevelItem {
     Scalar,
@@ -170,18 +472,22 @@ fn is_pair(obj: &Bound<'_, PyAny>) -> bool {
 
 fn is_numeric_pair(obj: &Bound<'_, PyAny>) -> bool {
     is_pair(obj)
-        && obj
-            .get_item(0)
-            .and_then(|item| item.extract::<f64>())
-            .is_ok()
-        && obj
-            .get_item(1)
-            .and_then(|item| item.extract::<f64>())
-            .is_ok()
+        && obj.get_item(0).is_ok_and(|item| {
+            item.extract::<f64>().is_ok() || is_datetime(&item) || item.is_instance_of::<PyString>()
+        })
+        && obj.get_item(1).and_then(|item| extract_y(&item)).is_ok()
+}
+
+/// A y value: a number, or `None` for a missing sample, read as NaN.
+fn extract_y(obj: &Bound<'_, PyAny>) -> PyResult<f64> {
+    if obj.is_none() {
+        return Ok(f64::NAN);
+    }
+    obj.extract()
 }
 
 fn classify_top_level_item(obj: &Bound<'_, PyAny>) -> PyResult<TopLevelItem> {
-    if obj.extract::<f64>().is_ok() {
+    if extract_y(obj).is_ok() {
         return Ok(TopLevelItem::Scalar);
     }
 
@@ -200,7 +506,7 @@ fn classify_top_level_item(obj: &Bound<'_, PyAny>) -> PyResult<TopLevelItem> {
     };
     let first_nested = first_nested?;
 
-    if first_nested.extract::<f64>().is_ok() || is_numeric_pair(&first_nested) {
+    if extract_y(&first_nested).is_ok() || is_numeric_pair(&first_nested) {
         Ok(TopLevelItem::Series)
     } else {
         Err(PyValueError::new_err(
@@ -209,54 +515,173 @@ fn classify_top_level_item(obj: &Bound<'_, PyAny>) -> PyResult<TopLevelItem> {
     }
 }
 
-/// Extract a `(f64, f64)` pair from a 2-element sequence.
-fn extract_pair(obj: &Bound<'_, PyAny>) -> PyResult<(f64, f64)> {
-    let x: f64 = obj
-        .get_item(0)?
-        .extract()
-        .map_err(|_| PyValueError::new_err("pair x-value must be numeric"))?;
-    let y: f64 = obj
-        .get_item(1)?
-        .extract()
-        .map_err(|_| PyValueError::new_err("pair y-value must be numeric"))?;
+/// Extract a pair from a 2-element sequence.
+fn extract_pair(obj: &Bound<'_, PyAny>) -> PyResult<(XValue, f64)> {
+    let x = extract_x(&obj.get_item(0)?)?;
+    let y = extract_y(&obj.get_item(1)?)
+        .map_err(|_| PyValueError::new_err("pair y-value must be numeric or None"))?;
     Ok((x, y))
 }
 
-fn parse_series_items(items: &[Py<PyAny>], py: Python<'_>) -> PyResult<(Vec<f64>, Vec<f64>)> {
-    let mut xs = Vec::new();
-    let mut ys = Vec::new();
-    let mut is_2d: Option<bool> = None;
+/// An x value as given by the caller.
+enum XValue {
+    Number(f64),
+    /// Seconds since the Unix epoch.
+    Time(f64),
+    Category(String),
+}
 
-    for (i, item) in items.iter().enumerate() {
-        let item = item.bind(py);
+impl XValue {
+    fn kind(&self) -> XKind {
+        match self {
+            XValue::Number(_) => XKind::Numeric,
+            XValue::Time(_) => XKind::Time,
+            XValue::Category(_) => XKind::Categorical,
+        }
+    }
+}
+
+/// Extract an x value that is a number, a datetime or a category name.
+fn extract_x(obj: &Bound<'_, PyAny>) -> PyResult<XValue> {
+    if let Ok(x) = obj.extract::<f64>() {
+        return Ok(XValue::Number(x));
+    }
+    if is_datetime(obj) {
+        return Ok(XValue::Time(extract_timestamp(obj)?));
+    }
+    if let Ok(name) = obj.cast::<PyString>() {
+        return Ok(XValue::Category(name.to_str()?.to_owned()));
+    }
+    Err(PyValueError::new_err(
+        "pair x-value must be numeric, a datetime or a string",
+    ))
+}
+
+fn is_datetime(obj: &Bound<'_, PyAny>) -> bool {
+    obj.is_instance_of::<PyDateTime>() || is_numpy_datetime64(obj)
+}
+
+fn is_numpy_datetime64(obj: &Bound<'_, PyAny>) -> bool {
+    obj.get_type().name().is_ok_and(|name| name == "datetime64")
+}
+
+/// Convert a `datetime.datetime` or numpy `datetime64` into epoch seconds.
+fn extract_timestamp(obj: &Bound<'_, PyAny>) -> PyResult<f64> {
+    if is_numpy_datetime64(obj) {
+        let nanos = obj
+            .call_method1("astype", ("datetime64[ns]",))?
+            .call_method1("astype", ("int64",))?
+            .extract::<i64>()?;
+        // NaT is stored as the smallest int64.
+        if nanos == i64::MIN {
+            return Ok(f64::NAN);
+        }
+        return Ok(nanos as f64 / 1e9);
+    }
+
+    let aware = if obj.getattr("tzinfo")?.is_none() {
+        let utc = obj
+            .py()
+            .import("datetime")?
+            .getattr("timezone")?
+            .getattr("utc")?;
+        let kwargs = pyo3::types::PyDict::new(obj.py());
+        kwargs.set_item("tzinfo", utc)?;
+        obj.call_method("replace", (), Some(&kwargs))?
+    } else {
+        obj.clone()
+    };
+    aware.call_method0("timestamp")?.extract()
+}
+
+/// Yields `dict.items()` for a dict so it reads as `(x, y)` pairs, and the
+/// object itself otherwise.
+fn dict_items<'py>(obj: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    match obj.cast::<PyDict>() {
+        Ok(dict) => Ok(dict.items().into_any()),
+        Err(_) => Ok(obj.clone()),
+    }
+}
+
+/// Accumulates the points of one series, tracking what kind of x values it
+/// uses and rejecting a mix.
+#[derive(Default)]
+struct PointCollector {
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    is_2d: Option<bool>,
+    x_kind: Option<XKind>,
+    categories: Vec<String>,
+}
+
+impl PointCollector {
+    fn push(&mut self, index: usize, item: &Bound<'_, PyAny>) -> PyResult<()> {
+        // Probe first element to decide 1-D vs 2-D.
+        let is_2d = *self.is_2d.get_or_insert_with(|| is_pair(item));
+        if !is_2d {
+            let y = extract_y(item)
+                .map_err(|_| PyValueError::new_err("data elements must be numeric or None"))?;
+            self.xs.push(index as f64);
+            self.ys.push(y);
+            return Ok(());
+        }
 
-        if is_2d.is_none() {
-            is_2d = Some(is_pair(item));
+        let (x, y) = extract_pair(item)?;
+        match self.x_kind {
+            Some(kind) if kind != x.kind() => {
+                return Err(PyValueError::new_err(
+                    "x values must be all numeric, all datetimes or all strings",
+                ));
+            }
+            _ => self.x_kind = Some(x.kind()),
         }
+        let x = match x {
+            XValue::Number(x) | XValue::Time(x) => x,
+            XValue::Category(name) => {
+                match self.categories.iter().position(|known| *known == name) {
+                    Some(idx) => idx as f64,
+                    None => {
+                        self.categories.push(name);
+                        (self.categories.len() - 1) as f64
+                    }
+                }
+            }
+        };
+        self.xs.push(x);
+        self.ys.push(y);
+        Ok(())
+    }
 
-        if is_2d == Some(true) {
-            let (x, y) = extract_pair(item)?;
-            xs.push(x);
-            ys.push(y);
-        } else {
-            let y: f64 = item
-                .extract()
-                .map_err(|_| PyValueError::new_err("data elements must be numeric"))?;
-            xs.push(i as f64);
-            ys.push(y);
+    fn finish(self) -> PyResult<SeriesData> {
+        if self.xs.is_empty() {
+            return Err(PyValueError::new_err("data must not be empty"));
         }
+        Ok(SeriesData {
+            xs: self.xs,
+            ys: self.ys,
+            x_kind: self.x_kind.unwrap_or(XKind::Numeric),
+            categories: self.categories,
+        })
     }
+}
 
-    Ok((xs, ys))
+fn parse_series_items(items: &[Py<PyAny>], py: Python<'_>) -> PyResult<SeriesData> {
+    let mut points = PointCollector::default();
+    for (i, item) in items.iter().enumerate() {
+        points.push(i, item.bind(py))?;
+    }
+    points.finish()
 }
 
 /// Compute `(min, max)` limits from a slice of values, with optional padding.
 ///
 /// If `padding` is `0.05`, limits are expanded by 5 % of the range on each side.
 /// If all values are identical, returns `(value - 1.0, value + 1.0)`.
+/// NaN and infinite values are ignored; with no finite values the limits are
+/// `(0.0, 1.0)`.
 pub fn compute_limits(vals: &[f64], padding: f64) -> (f64, f64) {
     let (mut lo, mut hi) = (f64::INFINITY, f64::NEG_INFINITY);
-    for &v in vals {
+    for &v in vals.iter().filter(|v| v.is_finite()) {
         if v < lo {
             lo = v;
         }
@@ -265,6 +690,10 @@ pub fn compute_limits(vals: &[f64], padding: f64) -> (f64, f64) {
         }
     }
 
+    if lo > hi {
+        return (0.0, 1.0);
+    }
+
     let range = hi - lo;
     if range.abs() < f64::EPSILON {
         return (lo - 1.0, hi + 1.0);
@@ -293,11 +722,11 @@ mod tests {
     #[test]
     fn parse_series_collection_accepts_multiple_scalar_series() {
         Python::attach(|py| {
-            let data = PyList::new(py, [vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap();
+            let data = PyList::new(py, [vec![1.0, 2.0, 3.0], vec![3.0, 4.0, 5.0]]).unwrap();
             let series = parse_series_collection(&data.into_any()).unwrap();
             assert_eq!(series.len(), 2);
-            assert_eq!(series[0].xs, vec![0.0, 1.0]);
-            assert_eq!(series[1].ys, vec![3.0, 4.0]);
+            assert_eq!(series[0].xs, vec![0.0, 1.0, 2.0]);
+            assert_eq!(series[1].ys, vec![3.0, 4.0, 5.0]);
         });
     }
 
@@ -313,4 +742,134 @@ mod tests {
             assert_eq!(parsed[1].ys, vec![2.0, 1.0]);
         });
     }
+
+    #[test]
+    fn parse_scale_accepts_a_log_base() {
+        Python::attach(|py| {
+            let scale =
+                |value: Bound<'_, PyAny>| parse_scale(Some(&value), ScaleKind::Linear, "xscale");
+            let log3 = ("log", 3.0).into_pyobject(py).unwrap().into_any();
+            assert_eq!(scale(log3).unwrap(), ScaleKind::Log { base: 3.0 });
+            let log = "log".into_pyobject(py).unwrap().into_any();
+            assert_eq!(scale(log).unwrap(), ScaleKind::LOG10);
+            for base in [0.0, 1.0, -2.0, f64::NAN] {
+                let bad = ("log", base).into_pyobject(py).unwrap().into_any();
+                assert!(scale(bad).is_err());
+            }
+        });
+    }
+
+    #[test]
+    fn resolve_limits_pads_log_axes_in_log_space() {
+        let (lo, hi) =
+            resolve_limits(None, &[-1.0, 1.0, 100.0], 0.5, ScaleKind::LOG10, false, "x").unwrap();
+        assert!((lo - 0.1).abs() < 1e-9);
+        assert!((hi - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejected_limits_are_explained_per_scale() {
+        Python::attach(|py| {
+            let message = |scale| {
+                let limits = Some((0.0, f64::INFINITY));
+                let err = resolve_limits(limits, &[], 0.0, scale, false, "xlim").unwrap_err();
+                err.value(py).to_string()
+            };
+            assert_eq!(
+                message(ScaleKind::Linear),
+                "xlim limits must be finite, got (0.0, inf)"
+            );
+            assert_eq!(
+                message(ScaleKind::LOG10),
+                "xlim limits must be positive on a log axis, got (0.0, inf)"
+            );
+        });
+    }
+
+    #[test]
+    fn parse_series_collection_reads_none_as_a_missing_sample() {
+        Python::attach(|py| {
+            let locals = pyo3::types::PyDict::new(py);
+            py.run(
+                pyo3::ffi::c_str!(
+                    "data = [None, 2.0, float('nan'), 4.0]\npairs = [(0.0, 1.0), (1.0, None)]"
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let data = locals.get_item("data").unwrap().unwrap();
+            let series = parse_series_collection(&data).unwrap();
+            assert!(series[0].ys[0].is_nan() && series[0].ys[2].is_nan());
+            assert_eq!(series[0].ys[3], 4.0);
+            let pairs = locals.get_item("pairs").unwrap().unwrap();
+            let series = parse_series_collection(&pairs).unwrap();
+            assert!(series[0].ys[1].is_nan());
+        });
+    }
+
+    #[test]
+    fn compute_limits_ignores_non_finite_values() {
+        assert_eq!(
+            compute_limits(&[1.0, f64::NAN, 3.0, f64::INFINITY], 0.0),
+            (1.0, 3.0)
+        );
+        assert_eq!(compute_limits(&[f64::NAN], 0.05), (0.0, 1.0));
+    }
+
+    #[test]
+    fn resolve_limits_inverts_explicit_and_inferred_ranges() {
+        let inferred =
+            resolve_limits(None, &[0.0, 10.0], 0.0, ScaleKind::Linear, true, "y").unwrap();
+        assert_eq!(inferred, (10.0, 0.0));
+        let explicit =
+            resolve_limits(Some((0.0, 5.0)), &[], 0.0, ScaleKind::Linear, true, "y").unwrap();
+        assert_eq!(explicit, (5.0, 0.0));
+    }
+
+    #[test]
+    fn parse_data_reads_datetimes_as_utc_epoch_seconds() {
+        Python::attach(|py| {
+            let locals = pyo3::types::PyDict::new(py);
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+import datetime
+data = [(datetime.datetime(2024, 3, 5, 14, 30), 1.0), (datetime.datetime(2024, 3, 5, 15, 30), 2.0)]
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let data = locals.get_item("data").unwrap().unwrap();
+            let series = parse_series_collection(&data).unwrap();
+            assert_eq!(series.len(), 1);
+            assert_eq!(series[0].x_kind, XKind::Time);
+            assert_eq!(series[0].xs, vec![1_709_649_000.0, 1_709_652_600.0]);
+            assert_eq!(default_x_scale(&series), ScaleKind::Time);
+        });
+    }
+
+    #[test]
+    fn parse_series_collection_shares_categories_across_series() {
+        Python::attach(|py| {
+            let locals = pyo3::types::PyDict::new(py);
+            py.run(
+                pyo3::ffi::c_str!(r#"data = [[("b", 1.0), ("a", 2.0)], {"a": 3.0, "c": 4.0}]"#),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let data = locals.get_item("data").unwrap().unwrap();
+            let series = parse_series_collection(&data).unwrap();
+            assert_eq!(x_categories(&series), ["b", "a", "c"]);
+            assert_eq!(series[0].xs, vec![0.0, 1.0]);
+            assert_eq!(series[1].xs, vec![1.0, 2.0]);
+            assert_eq!(default_x_scale(&series), ScaleKind::Categorical);
+            assert!(
+                resolve_x_scale(Some(&pyo3::types::PyString::new(py, "log")), &series).is_err()
+            );
+        });
+    }
 }
@@ -0,0 +1,530 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use pltrs_backend_wgpu::run_with_grid;
+use pltrs_core::{
+    layout::{grid_rects, GridParams},
+    plot::{PlotDefinition, PlotGrid},
+    Color, Figure, Size,
+};
+
+use crate::plot::{extract_plot, fit_plot_rect};
+use crate::{
+    check_renderer, check_samples, dpi_scale, map_backend_error, next_figure_id, output_format,
+    png_bytes, register_handle, render_figure, resolve_output_path, rgba_view, save_figure,
+    take_registered_handle, update_registered_handle, PlotHandle,
+};
+
+/// A grid of independent plots shown in one window.
+///
+/// Plots are created as usual and placed into cells with `.add()`; each
+/// keeps its own data, limits, title, labels and ticks.
+#[pyclass(name = "Figure")]
+pub struct PyFigure {
+    id: u64,
+    rows: usize,
+    cols: usize,
+    size: Size,
+    params: GridParams,
+    share_x: Share,
+    share_y: Share,
+    cells: Vec<Option<PlotDefinition>>,
+}
+
+/// Which cells of a grid share an axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Share {
+    None,
+    All,
+    Row,
+    Col,
+}
+
+#[pymethods]
+impl PyFigure {
+    /// Create an empty subplot grid.
+    ///
+    /// Parameters
+    /// ----------
+    /// rows, cols : int, optional
+    ///     Grid shape. Defaults to a single cell.
+    /// size : tuple(int, int), optional
+    ///     Figure size in pixels. Grows with the grid shape if omitted.
+    /// wspace, hspace : float, optional
+    ///     Horizontal / vertical gap between cells, in pixels.
+    /// sharex, sharey : bool or str, optional
+    ///     Link axis limits across cells: `True` or `"all"` for every cell,
+    ///     `"row"` or `"col"` for cells in the same row / column. Zooming or
+    ///     panning one linked plot moves the others.
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (rows=1, cols=1, *, size=None, wspace=None, hspace=None, sharex=None, sharey=None))]
+    fn new(
+        rows: usize,
+        cols: usize,
+        size: Option<(u32, u32)>,
+        wspace: Option<f32>,
+        hspace: Option<f32>,
+        sharex: Option<&Bound<'_, PyAny>>,
+        sharey: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        if rows == 0 || cols == 0 {
+            return Err(PyValueError::new_err("rows and cols must be at least 1"));
+        }
+        let (width, height) =
+            size.unwrap_or(((520 * cols as u32).max(800), (420 * rows as u32).max(600)));
+        if width == 0 || height == 0 {
+            return Err(PyValueError::new_err("size must be positive"));
+        }
+
+        let mut params = GridParams::default();
+        if let Some(wspace) = wspace {
+            params.wspace = wspace.max(0.0);
+        }
+        if let Some(hspace) = hspace {
+            params.hspace = hspace.max(0.0);
+        }
+
+        let figure = Self {
+            id: next_figure_id(),
+            rows,
+            cols,
+            size: Size {
+                width,
+                height,
+                dpi: 1.0,
+            },
+            params,
+            share_x: parse_share(sharex, "sharex")?,
+            share_y: parse_share(sharey, "sharey")?,
+            cells: vec![None; rows * cols],
+        };
+        register_handle(figure.id, PlotHandle::Grid(Box::new(figure.grid()?)));
+        Ok(figure)
+    }
+
+    /// Place a `Line`, `Scatter` or `Bar` plot into the cell at `(row, col)`.
+    ///
+    /// The plot is no longer shown on its own by `pltrs.show()`.
+    fn add(&mut self, plot: &Bound<'_, PyAny>, row: usize, col: usize) -> PyResult<()> {
+        if row >= self.rows || col >= self.cols {
+            return Err(PyValueError::new_err(format!(
+                "cell ({row}, {col}) is outside the {}x{} grid",
+                self.rows, self.cols
+            )));
+        }
+
+        let (plot_id, plot) = extract_plot(plot, "the plot is in use and cannot be added")?;
+        let cell = row * self.cols + col;
+        let previous = self.cells[cell].replace(plot);
+        let grid = match self.grid() {
+            Ok(grid) => grid,
+            Err(err) => {
+                self.cells[cell] = previous;
+                return Err(err);
+            }
+        };
+        take_registered_handle(plot_id);
+        update_registered_handle(self.id, PlotHandle::Grid(Box::new(grid)));
+        Ok(())
+    }
+
+    /// Render this figure in a window. `samples` is the number of samples
+    /// per pixel used to smooth edges: 1 (no anti-aliasing), 4 or 8.
+    #[pyo3(signature = (*, samples=4))]
+    fn show(&self, samples: u32) -> PyResult<()> {
+        let samples = check_samples(samples)?;
+        let grid = self.grid()?;
+        take_registered_handle(self.id);
+        run_with_grid(grid, samples).map_err(map_backend_error)
+    }
+
+    /// Save this figure as a PNG, or as a vector SVG or PDF when `path`
+    /// ends in `.svg` or `.pdf`.
+    ///
+    /// `dpi` sets the resolution of PNGs: sizes are given at 100 dpi, so
+    /// `dpi=200` saves the same layout at twice the width and height in
+    /// pixels. `samples` is the number of samples per pixel used to smooth
+    /// edges: 1 (no anti-aliasing), 4 or 8. `renderer` picks what draws
+    /// PNGs: `"gpu"`, `"cpu"` for the software renderer that needs no GPU,
+    /// or `"auto"` for the GPU when there is one and the CPU otherwise.
+    #[pyo3(signature = (path=None, *, dpi=100.0, samples=4, renderer="auto"))]
+    fn save(
+        &self,
+        py: Python<'_>,
+        path: Option<&str>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<()> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let output_path = resolve_output_path(py, path)?;
+        output_format(&output_path)?;
+        let fig = self.take_figure(scale)?;
+        save_figure(&fig, &output_path, samples, renderer)
+    }
+
+    /// Render this figure as the bytes of a PNG file, as `save()` would
+    /// write it, for serving or embedding without a file on disk. Takes the
+    /// same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_png<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale)?;
+        let image = render_figure(&fig, samples, renderer)?;
+        png_bytes(py, &image)
+    }
+
+    /// Render this figure to 8-bit RGBA pixels, rows top to bottom, as a
+    /// read-only memoryview of shape `(height, width, 4)`. `bytes()` of it
+    /// gives the raw pixels and `numpy.asarray()` an array without a copy.
+    /// Takes the same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_rgba<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale)?;
+        let image = render_figure(&fig, samples, renderer)?;
+        rgba_view(py, &image)
+    }
+}
+
+impl PyFigure {
+    /// Take this figure out of the queue of `show()` and lay it out at
+    /// `scale` device pixels per logical pixel.
+    fn take_figure(&self, scale: f32) -> PyResult<Figure> {
+        let mut grid = self.grid()?;
+        take_registered_handle(self.id);
+        grid.set_dpi(scale);
+        Ok(grid.build_figure(&grid.initial_views()))
+    }
+
+    /// Lay the filled cells out on the figure. Empty cells stay blank.
+    ///
+    /// Fails when linked cells use different kinds of scale, or different
+    /// categories on a categorical x axis, since shared limits would not
+    /// mean the same thing in each of them.
+    fn grid(&self) -> PyResult<PlotGrid> {
+        let rects = grid_rects(self.size, self.rows, self.cols, &self.params);
+        let plots: Vec<PlotDefinition> = self
+            .cells
+            .iter()
+            .zip(rects)
+            .filter_map(|(cell, rect)| {
+                let mut plot = cell.clone()?;
+                plot.size = self.size;
+                plot.plot_rect = fit_plot_rect(&plot, rect);
+                Some(plot)
+            })
+            .collect();
+
+        let shared_x = self.share_groups(self.share_x);
+        let shared_y = self.share_groups(self.share_y);
+        check_shared(&plots, &shared_x, "sharex", |plot| {
+            (plot.x_scale, &plot.x_categories)
+        })?;
+        check_shared(&plots, &shared_y, "sharey", |plot| plot.y_scale)?;
+
+        Ok(PlotGrid {
+            size: self.size,
+            clear_color: Color::WHITE,
+            shared_x,
+            shared_y,
+            plots,
+        })
+    }
+
+    /// Plot indices (counting filled cells only) that share an axis.
+    fn share_groups(&self, share: Share) -> Vec<Vec<usize>> {
+        let filled: Vec<usize> = (0..self.cells.len())
+            .filter(|&cell| self.cells[cell].is_some())
+            .collect();
+        let key = |cell: usize| match share {
+            Share::None => None,
+            Share::All => Some(0),
+            Share::Row => Some(cell / self.cols),
+            Share::Col => Some(cell % self.cols),
+        };
+
+        let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (plot, &cell) in filled.iter().enumerate() {
+            let Some(key) = key(cell) else {
+                continue;
+            };
+            match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+                Some((_, members)) => members.push(plot),
+                None => groups.push((key, vec![plot])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(_, members)| members)
+            .filter(|members| members.len() > 1)
+            .collect()
+    }
+}
+
+/// Check that every plot of each linked group gives the same `axis` key.
+fn check_shared<'a, K: PartialEq>(
+    plots: &'a [PlotDefinition],
+    groups: &[Vec<usize>],
+    name: &str,
+    axis: impl Fn(&'a PlotDefinition) -> K,
+) -> PyResult<()> {
+    for group in groups {
+        let mut keys = group.iter().map(|&idx| axis(&plots[idx]));
+        let Some(first) = keys.next() else {
+            continue;
+        };
+        if keys.any(|key| key != first) {
+            return Err(PyValueError::new_err(format!(
+                "{name} links plots whose axes use different scales or categories"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn parse_share(value: Option<&Bound<'_, PyAny>>, name: &str) -> PyResult<Share> {
+    let Some(value) = value.filter(|value| !value.is_none()) else {
+        return Ok(Share::None);
+    };
+    if let Ok(flag) = value.extract::<bool>() {
+        return Ok(if flag { Share::All } else { Share::None });
+    }
+    match value.extract::<String>().as_deref() {
+        Ok("none") => Ok(Share::None),
+        Ok("all") => Ok(Share::All),
+        Ok("row") => Ok(Share::Row),
+        Ok("col") => Ok(Share::Col),
+        _ => Err(PyValueError::new_err(format!(
+            "{name} must be a bool or one of 'none', 'all', 'row', 'col'"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bar::PyBar;
+    use crate::line::PyLine;
+    use pyo3::types::IntoPyDict;
+
+    #[test]
+    fn figure_lays_out_added_plots_in_separate_cells() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<PyFigure>().unwrap();
+            module.add_class::<PyLine>().unwrap();
+            module.add_class::<PyBar>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+fig = pltrs_test.Figure(rows=2, cols=2)
+cpu = pltrs_test.Line([1.0, 3.0, 2.0], title="cpu")
+fig.add(cpu, row=0, col=0)
+fig.add(pltrs_test.Bar([4.0, 5.0], title="memory"), row=1, col=1)
+try:
+    fig.add(cpu, row=2, col=0)
+    out_of_range_accepted = True
+except ValueError:
+    out_of_range_accepted = False
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let fig = locals.get_item("fig").unwrap().unwrap();
+            let fig = fig.cast::<PyFigure>().unwrap().borrow();
+            let Some(PlotHandle::Grid(grid)) = take_registered_handle(fig.id) else {
+                panic!("expected a registered grid");
+            };
+            assert_eq!(grid.plots.len(), 2);
+            let (first, second) = (grid.plots[0].plot_rect, grid.plots[1].plot_rect);
+            assert!(first.x + first.w < second.x);
+            assert!(second.y + second.h < first.y);
+            assert_eq!(grid.plots[0].title.as_deref(), Some("cpu"));
+
+            let figure = grid.build_figure(&grid.initial_views());
+            assert_eq!(figure.axes.len(), 4);
+            let out_of_range_accepted = locals.get_item("out_of_range_accepted").unwrap().unwrap();
+            assert!(!out_of_range_accepted.extract::<bool>().unwrap());
+        });
+    }
+
+    #[test]
+    fn figure_links_axes_by_row_and_column() {
+        Python::attach(|py| {
+            let mut figure = PyFigure::new(
+                2,
+                2,
+                None,
+                None,
+                None,
+                Some(&pyo3::types::PyString::new(py, "col")),
+                Some(&pyo3::types::PyBool::new(py, true).to_owned().into_any()),
+            )
+            .unwrap();
+            take_registered_handle(figure.id);
+            let plot = crate::plot::build_plot_definition(
+                crate::plot::PlotOptions {
+                    xlim: (0.0, 1.0),
+                    ylim: (0.0, 1.0),
+                    x_scale: Default::default(),
+                    y_scale: Default::default(),
+                    x_categories: Vec::new(),
+                    annotations: Vec::new(),
+                    title: None,
+                    x_label: None,
+                    y_label: None,
+                    grid: true,
+                    legend: None,
+                    legend_autoscale: false,
+                    colorbar: None,
+                    size_legend: None,
+                },
+                Vec::new(),
+            );
+            // Cells (0, 0), (0, 1) and (1, 1) are filled.
+            figure.cells = vec![Some(plot.clone()), Some(plot.clone()), None, Some(plot)];
+
+            let grid = figure.grid().unwrap();
+            assert_eq!(grid.shared_x, vec![vec![1, 2]]);
+            assert_eq!(grid.shared_y, vec![vec![0, 1, 2]]);
+            assert!(
+                parse_share(Some(&pyo3::types::PyString::new(py, "diagonal")), "sharex").is_err()
+            );
+        });
+    }
+
+    #[test]
+    fn figure_rejects_linking_axes_of_different_kinds() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<PyFigure>().unwrap();
+            module.add_class::<PyLine>().unwrap();
+            module.add_class::<PyBar>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+fig = pltrs_test.Figure(rows=1, cols=3, sharex=True, sharey=True)
+fig.add(pltrs_test.Line([1.0, 2.0]), row=0, col=0)
+rejected = []
+mismatched = [
+    pltrs_test.Line([1.0, 2.0], yscale="log"),
+    pltrs_test.Bar({"a": 1.0, "b": 2.0}),
+]
+for cell, plot in enumerate(mismatched):
+    try:
+        fig.add(plot, row=0, col=cell + 1)
+        rejected.append(False)
+    except ValueError:
+        rejected.append(True)
+fig.add(pltrs_test.Line([3.0, 4.0]), row=0, col=1)
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let rejected = locals.get_item("rejected").unwrap().unwrap();
+            assert_eq!(rejected.extract::<Vec<bool>>().unwrap(), vec![true, true]);
+            let fig = locals.get_item("fig").unwrap().unwrap();
+            let fig = fig.cast::<PyFigure>().unwrap().borrow();
+            assert_eq!(fig.cells.iter().flatten().count(), 2);
+            assert!(fig.cells[2].is_none());
+
+            // Rejected plots are still shown on their own.
+            take_registered_handle(fig.id);
+            let mismatched = locals.get_item("mismatched").unwrap().unwrap();
+            for plot in mismatched.try_iter().unwrap() {
+                let (plot_id, _) = extract_plot(&plot.unwrap(), "busy").unwrap();
+                assert!(take_registered_handle(plot_id).is_some());
+            }
+        });
+    }
+
+    #[test]
+    fn figures_render_to_png_bytes_and_rgba_pixels() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<PyFigure>().unwrap();
+            module.add_class::<PyLine>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+fig = pltrs_test.Figure(size=(120, 80))
+fig.add(pltrs_test.Line([1.0, 3.0, 2.0]), row=0, col=0)
+png = fig.to_png(dpi=50, renderer="cpu")
+png_size = (int.from_bytes(png[16:20], "big"), int.from_bytes(png[20:24], "big"))
+line = pltrs_test.Line([1.0, 3.0, 2.0])
+line_png = line.to_png(renderer="cpu")
+line_size = (int.from_bytes(line_png[16:20], "big"), int.from_bytes(line_png[20:24], "big"))
+pixels = line.to_rgba(dpi=200, samples=1, renderer="cpu")
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let png = locals.get_item("png").unwrap().unwrap();
+            assert!(png.extract::<Vec<u8>>().unwrap().starts_with(b"\x89PNG"));
+            let png_size = locals.get_item("png_size").unwrap().unwrap();
+            assert_eq!(png_size.extract::<(u32, u32)>().unwrap(), (60, 40));
+
+            let (width, height) = locals
+                .get_item("line_size")
+                .unwrap()
+                .unwrap()
+                .extract::<(u32, u32)>()
+                .unwrap();
+            let pixels = locals.get_item("pixels").unwrap().unwrap();
+            let shape = pixels.getattr("shape").unwrap();
+            assert_eq!(
+                shape.extract::<(u32, u32, u32)>().unwrap(),
+                (2 * height, 2 * width, 4)
+            );
+            assert!(pixels
+                .getattr("readonly")
+                .unwrap()
+                .extract::<bool>()
+                .unwrap());
+
+            // Rendering takes the plots out of the queue of `show()`.
+            let fig = locals.get_item("fig").unwrap().unwrap();
+            let fig = fig.cast::<PyFigure>().unwrap().borrow();
+            assert!(take_registered_handle(fig.id).is_none());
+            let line = locals.get_item("line").unwrap().unwrap();
+            let line = line.cast::<PyLine>().unwrap().borrow();
+            assert!(take_registered_handle(line.id).is_none());
+        });
+    }
+}
@@ -4,18 +4,25 @@ use std::sync::{
     Mutex,
 };
 
-use pltrs_backend_wgpu::{run_with_figure, run_with_plot, KEYBOARD_INTERRUPT_ERROR};
+use pltrs_backend_pdf::{save_figure_pdf, save_figures_pdf};
+use pltrs_backend_svg::save_figure_svg;
+use pltrs_backend_wgpu::{
+    render_image_with, run_with_figure, run_with_grid, run_with_plot, save_figure_png_with,
+    Renderer, DEFAULT_SAMPLES, KEYBOARD_INTERRUPT_ERROR,
+};
 use pltrs_core::{
-    plot::PlotDefinition,
+    plot::{PlotDefinition, PlotGrid},
     scale::Scale,
-    scene::{Axes, Line, Node, Rect},
-    Color, Figure, Size,
+    scene::{Axes, Line, LineCap, LineJoin, LineStyle, Node, Rect, DEFAULT_MITER_LIMIT},
+    Color, Figure, Image, Size,
 };
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyMemoryView};
 
 mod bar;
 mod data;
+mod figure;
 mod line;
 mod plot;
 mod scatter;
@@ -25,7 +32,8 @@ mod scatter;
 #[derive(Clone)]
 pub enum PlotHandle {
     Figure(Figure),
-    Plot(PlotDefinition),
+    Plot(Box<PlotDefinition>),
+    Grid(Box<PlotGrid>),
 }
 
 pub struct RegisteredFigure {
@@ -51,6 +59,15 @@ pub fn take_registered_handle(id: u64) -> Option<PlotHandle> {
     Some(reg.swap_remove(idx).handle)
 }
 
+/// Replace the handle registered under `id`, keeping its place in the queue.
+pub fn update_registered_handle(id: u64, handle: PlotHandle) {
+    let mut reg = FIGURE_REGISTRY.lock().unwrap();
+    match reg.iter_mut().find(|entry| entry.id == id) {
+        Some(entry) => entry.handle = handle,
+        None => reg.push(RegisteredFigure { id, handle }),
+    }
+}
+
 pub fn drain_registered_handles() -> Vec<PlotHandle> {
     let mut reg = FIGURE_REGISTRY.lock().unwrap();
     reg.drain(..).map(|entry| entry.handle).collect()
@@ -85,9 +102,7 @@ fn resolve_output_path_from_base(
     script_path: Option<&Path>,
     cwd: &Path,
 ) -> PathBuf {
-    let base_dir = script_path
-        .and_then(|path| path.parent())
-        .unwrap_or(cwd);
+    let base_dir = script_path.and_then(|path| path.parent()).unwrap_or(cwd);
     let requested = path.and_then(|value| {
         let trimmed = value.trim();
         (!trimmed.is_empty()).then_some(trimmed)
@@ -110,8 +125,9 @@ pub fn resolve_output_path(py: Python<'_>, path: Option<&str>) -> PyResult<PathB
         .ok()
         .and_then(|value| value.extract::<String>().ok())
         .map(PathBuf::from);
-    let cwd = std::env::current_dir()
-        .map_err(|err| PyRuntimeError::new_err(format!("failed to resolve current directory: {err}")))?;
+    let cwd = std::env::current_dir().map_err(|err| {
+        PyRuntimeError::new_err(format!("failed to resolve current directory: {err}"))
+    })?;
 
     Ok(resolve_output_path_from_base(
         path,
@@ -128,28 +144,156 @@ pub fn map_backend_error(err: anyhow::Error) -> PyErr {
     }
 }
 
+/// Resolution figure sizes are given at; `save(dpi=...)` scales from it.
+pub const BASE_DPI: f32 = 100.0;
+
+/// Turn the `dpi` argument of `save()` into device pixels per logical pixel.
+pub fn dpi_scale(dpi: f32) -> PyResult<f32> {
+    if dpi.is_finite() && dpi > 0.0 {
+        Ok(dpi / BASE_DPI)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "dpi must be positive, got {dpi}"
+        )))
+    }
+}
+
+/// A file format `save()` can write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Png,
+    Svg,
+    Pdf,
+}
+
+/// The format the extension of `path` names, so a bad name is rejected
+/// before anything is rendered.
+pub fn output_format(path: &Path) -> PyResult<OutputFormat> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("png") => Ok(OutputFormat::Png),
+        Some("svg") => Ok(OutputFormat::Svg),
+        Some("pdf") => Ok(OutputFormat::Pdf),
+        _ => Err(PyValueError::new_err(format!(
+            "cannot save {path:?}: use a .png, .svg or .pdf file name"
+        ))),
+    }
+}
+
+/// Write `fig` to `path` in the format its extension names, with PNGs
+/// drawn by `renderer`.
+pub fn save_figure(fig: &Figure, path: &Path, samples: u32, renderer: Renderer) -> PyResult<()> {
+    let saved = match output_format(path)? {
+        OutputFormat::Png => save_figure_png_with(fig, path, samples, renderer),
+        OutputFormat::Svg => save_figure_svg(fig, path),
+        OutputFormat::Pdf => save_figure_pdf(fig, path),
+    };
+    saved.map_err(|e| PyRuntimeError::new_err(format!("{e}")))
+}
+
+/// Render `fig` to pixels in memory, drawn by `renderer`.
+pub fn render_figure(fig: &Figure, samples: u32, renderer: Renderer) -> PyResult<Image> {
+    render_image_with(fig, samples, renderer).map_err(|e| PyRuntimeError::new_err(format!("{e}")))
+}
+
+/// `image` encoded as the bytes of a PNG file.
+pub fn png_bytes<'py>(py: Python<'py>, image: &Image) -> PyResult<Bound<'py, PyBytes>> {
+    let png = image
+        .encode_png()
+        .map_err(|e| PyRuntimeError::new_err(format!("{e}")))?;
+    Ok(PyBytes::new(py, &png))
+}
+
+/// The pixels of `image` as a read-only memoryview of shape
+/// `(height, width, 4)`.
+pub fn rgba_view<'py>(py: Python<'py>, image: &Image) -> PyResult<Bound<'py, PyAny>> {
+    let bytes = PyBytes::new(py, &image.pixels);
+    let shape = (image.height, image.width, 4);
+    PyMemoryView::from(bytes.as_any())?.call_method1("cast", ("B", shape))
+}
+
+/// Validate the `samples` argument of `save()` and `show()`.
+pub fn check_samples(samples: u32) -> PyResult<u32> {
+    match samples {
+        1 | 4 | 8 => Ok(samples),
+        _ => Err(PyValueError::new_err(format!(
+            "samples must be 1, 4 or 8, got {samples}"
+        ))),
+    }
+}
+
+/// Validate the `renderer` argument of `save()`.
+pub fn check_renderer(renderer: &str) -> PyResult<Renderer> {
+    match renderer {
+        "auto" => Ok(Renderer::Auto),
+        "gpu" => Ok(Renderer::Gpu),
+        "cpu" => Ok(Renderer::Cpu),
+        _ => Err(PyValueError::new_err(format!(
+            "renderer must be one of 'auto', 'gpu', 'cpu', got {renderer:?}"
+        ))),
+    }
+}
+
 /// Render all queued figures in sequence, then clear the registry.
 ///
 /// Each figure is displayed in its own window. Close the window (or press
-/// Escape) to proceed to the next figure.
+/// Escape) to proceed to the next figure. `samples` is the number of samples
+/// per pixel used to smooth edges: 1, 4 or 8.
 #[pyfunction]
-fn show() -> PyResult<()> {
+#[pyo3(signature = (*, samples=4))]
+fn show(samples: u32) -> PyResult<()> {
+    let samples = check_samples(samples)?;
     let figures = drain_registered_handles();
 
     if figures.is_empty() {
         // Nothing queued — open an empty window (original behaviour).
-        return run_with_figure(None).map_err(map_backend_error);
+        return run_with_figure(None, samples).map_err(map_backend_error);
     }
 
     for fig in figures {
         match fig {
-            PlotHandle::Figure(fig) => run_with_figure(Some(fig)).map_err(map_backend_error)?,
-            PlotHandle::Plot(plot) => run_with_plot(plot).map_err(map_backend_error)?,
+            PlotHandle::Figure(fig) => {
+                run_with_figure(Some(fig), samples).map_err(map_backend_error)?
+            }
+            PlotHandle::Plot(plot) => run_with_plot(*plot, samples).map_err(map_backend_error)?,
+            PlotHandle::Grid(grid) => run_with_grid(*grid, samples).map_err(map_backend_error)?,
         }
     }
     Ok(())
 }
 
+/// Write all queued figures to one PDF, a page each, then clear the
+/// registry. The figures stay queued if the PDF cannot be written.
+#[pyfunction]
+fn save_pdf(py: Python<'_>, path: &str) -> PyResult<()> {
+    let output_path = resolve_output_path(py, Some(path))?;
+    if output_format(&output_path)? != OutputFormat::Pdf {
+        return Err(PyValueError::new_err(format!(
+            "cannot save {output_path:?}: save_pdf() needs a .pdf file name"
+        )));
+    }
+    let figures: Vec<Figure> = FIGURE_REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|entry| match &entry.handle {
+            PlotHandle::Figure(fig) => fig.clone(),
+            PlotHandle::Plot(plot) => plot.build_figure(&plot.initial_view()),
+            PlotHandle::Grid(grid) => grid.build_figure(&grid.initial_views()),
+        })
+        .collect();
+    if figures.is_empty() {
+        return Err(PyValueError::new_err("no figures to save"));
+    }
+    save_figures_pdf(&figures, &output_path)
+        .map_err(|e| PyRuntimeError::new_err(format!("{e}")))?;
+    drain_registered_handles();
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Legacy demo helpers (kept for backward compatibility)
 // ---------------------------------------------------------------------------
@@ -185,14 +329,20 @@ fn demo_line() -> PyResult<()> {
             a: 1.0,
         },
         width: 2.0,
+        style: LineStyle::Solid,
+        join: LineJoin::Round,
+        cap: LineCap::Butt,
+        miter_limit: DEFAULT_MITER_LIMIT,
+        label: None,
     };
     ax.add(Node::Line(line));
     fig.add_axes(ax);
 
-    run_with_figure(Some(fig)).map_err(map_backend_error)
+    run_with_figure(Some(fig), DEFAULT_SAMPLES).map_err(map_backend_error)
 }
 
 #[pyfunction]
+#[allow(clippy::approx_constant)]
 fn demo_scatter() -> PyResult<()> {
     let size = Size {
         width: 800,
@@ -226,6 +376,11 @@ fn demo_scatter() -> PyResult<()> {
         },
         size: 20.0,
         marker: Marker::Circle,
+        colors: None,
+        sizes: None,
+        edge_color: Color::BLACK,
+        edge_width: 0.0,
+        label: None,
     };
     ax.add(Node::Scatter(scatter));
 
@@ -242,12 +397,17 @@ fn demo_scatter() -> PyResult<()> {
         },
         size: 15.0,
         marker: Marker::Square,
+        colors: None,
+        sizes: None,
+        edge_color: Color::BLACK,
+        edge_width: 0.0,
+        label: None,
     };
     ax.add(Node::Scatter(scatter2));
 
     fig.add_axes(ax);
 
-    run_with_figure(Some(fig)).map_err(map_backend_error)
+    run_with_figure(Some(fig), DEFAULT_SAMPLES).map_err(map_backend_error)
 }
 
 // ---------------------------------------------------------------------------
@@ -258,11 +418,13 @@ fn demo_scatter() -> PyResult<()> {
 fn pltrs(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // New API classes
     m.add_class::<bar::PyBar>()?;
+    m.add_class::<figure::PyFigure>()?;
     m.add_class::<line::PyLine>()?;
     m.add_class::<scatter::PyScatter>()?;
 
     // Functions
     m.add_function(wrap_pyfunction!(show, m)?)?;
+    m.add_function(wrap_pyfunction!(save_pdf, m)?)?;
 
     // Legacy demos
     m.add_function(wrap_pyfunction!(demo_line, m)?)?;
@@ -273,7 +435,7 @@ fn pltrs(m: &Bound<'_, PyModule>) -> PyResult<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use pltrs_core::Size;
+    use pltrs_core::{Marker, PlotSeries, Size};
     use pyo3::types::IntoPyDict;
     use std::path::Path;
 
@@ -301,14 +463,14 @@ mod tests {
         let taken = take_registered_handle(id_a).unwrap();
         match taken {
             PlotHandle::Figure(fig) => assert_eq!(fig.size.width, 1),
-            PlotHandle::Plot(_) => panic!("expected a static figure"),
+            _ => panic!("expected a static figure"),
         }
 
         let remaining = drain_registered_handles();
         assert_eq!(remaining.len(), 1);
         match &remaining[0] {
             PlotHandle::Figure(fig) => assert_eq!(fig.size.width, 2),
-            PlotHandle::Plot(_) => panic!("expected a static figure"),
+            _ => panic!("expected a static figure"),
         }
     }
 
@@ -352,7 +514,7 @@ fig = pltrs_test.Line(
                             .any(|node| matches!(node, pltrs_core::Node::Text(text) if text.content == "Multi-series"))
                     );
                 }
-                PlotHandle::Figure(_) => panic!("expected an interactive plot"),
+                _ => panic!("expected an interactive plot"),
             }
         });
     }
@@ -393,11 +555,389 @@ fig = pltrs_test.Bar(
                         .count();
                     assert_eq!(bar_count, 2);
                 }
-                PlotHandle::Figure(_) => panic!("expected an interactive plot"),
+                _ => panic!("expected an interactive plot"),
+            }
+        });
+    }
+
+    #[test]
+    fn bar_with_string_x_labels_ticks_with_categories() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<bar::PyBar>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+fig = pltrs_test.Bar([("api", 3.0), ("db", 5.0), ("cache", 1.0)])
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let figures = drain_registered_handles();
+            match &figures[0] {
+                PlotHandle::Plot(plot) => {
+                    let fig = plot.build_figure(&plot.initial_view());
+                    let labels: Vec<&str> = fig.axes[1]
+                        .children
+                        .iter()
+                        .filter_map(|node| match node {
+                            pltrs_core::Node::Text(text) => Some(text.content.as_str()),
+                            _ => None,
+                        })
+                        .collect();
+                    for name in ["api", "db", "cache"] {
+                        assert!(labels.contains(&name), "missing tick {name}");
+                    }
+                }
+                _ => panic!("expected an interactive plot"),
             }
         });
     }
 
+    #[test]
+    fn twinx_moves_series_onto_a_secondary_axis() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<line::PyLine>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+rate = pltrs_test.Line([120.0, 180.0, 150.0], y_label="req/s")
+errors = pltrs_test.Line([0.5, 2.0, 1.0], x=(-1.0, 4.0), y=(0.0, 5.0), y_label="error %")
+rate.twinx(errors)
+try:
+    rate.twinx(rate)
+    self_twin_accepted = True
+except ValueError:
+    self_twin_accepted = False
+logx = pltrs_test.Line([1.0, 2.0], xscale="log")
+try:
+    rate.twinx(logx)
+    log_twin_accepted = True
+except ValueError:
+    log_twin_accepted = False
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let rate = locals.get_item("rate").unwrap().unwrap();
+            let rate = rate.cast::<line::PyLine>().unwrap().borrow();
+            let errors = locals.get_item("errors").unwrap().unwrap();
+            let errors = errors.cast::<line::PyLine>().unwrap().borrow();
+            assert!(take_registered_handle(errors.id).is_none());
+            let Some(PlotHandle::Plot(plot)) = take_registered_handle(rate.id) else {
+                panic!("expected the twinned plot to stay registered");
+            };
+            let y2 = plot.y2.as_ref().unwrap();
+            assert_eq!(y2.base_lim, (0.0, 5.0));
+            assert_eq!(y2.label.as_deref(), Some("error %"));
+            assert_eq!(plot.base_xlim.0, -1.0);
+            assert_eq!(plot.base_xlim.1, 4.0);
+            assert_eq!(plot.y_label.as_deref(), Some("req/s"));
+            let self_twin_accepted = locals.get_item("self_twin_accepted").unwrap().unwrap();
+            assert!(!self_twin_accepted.extract::<bool>().unwrap());
+            let log_twin_accepted = locals.get_item("log_twin_accepted").unwrap().unwrap();
+            assert!(!log_twin_accepted.extract::<bool>().unwrap());
+            let logx = locals.get_item("logx").unwrap().unwrap();
+            let logx = logx.cast::<line::PyLine>().unwrap().borrow();
+            assert!(take_registered_handle(logx.id).is_some());
+        });
+    }
+
+    #[test]
+    fn labelled_series_get_a_legend() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<line::PyLine>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+both = pltrs_test.Line([[1.0, 2.0, 3.0], [3.0, 2.0, 1.0]], label=["cpu", None])
+outside = pltrs_test.Line([1.0, 2.0], label="cpu", legend="outside right")
+try:
+    pltrs_test.Line([[1.0], [2.0]], label="cpu")
+    single_label_accepted = True
+except ValueError:
+    single_label_accepted = False
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let both = locals.get_item("both").unwrap().unwrap();
+            let both = both.cast::<line::PyLine>().unwrap().borrow();
+            take_registered_handle(both.id);
+            let view = both.plot.initial_view();
+            let layout = both.plot.legend_layout(&view).unwrap();
+            assert_eq!(layout.entries, vec![0]);
+
+            let outside = locals.get_item("outside").unwrap().unwrap();
+            let outside = outside.cast::<line::PyLine>().unwrap().borrow();
+            take_registered_handle(outside.id);
+            let rect = outside.plot.plot_rect;
+            let legend = outside.plot.legend_layout(&view).unwrap().rect;
+            assert!(rect.w < plot::plot_rect().w);
+            assert!(legend.x > rect.x + rect.w && legend.x + legend.w <= 1.0);
+
+            let single_label_accepted = locals.get_item("single_label_accepted").unwrap().unwrap();
+            assert!(!single_label_accepted.extract::<bool>().unwrap());
+        });
+    }
+
+    #[test]
+    fn line_styles_accept_names_and_dash_patterns() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<line::PyLine>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+shared = pltrs_test.Line([[1.0, 2.0, 3.0], [3.0, 2.0, 1.0]], linestyle=[8, 4])
+mixed = pltrs_test.Line([[1.0, 2.0, 3.0], [3.0, 2.0, 1.0]], linestyle=["--", [1, 2, 3, 4]])
+joined = pltrs_test.Line([1.0, 3.0, 1.0], joinstyle="miter", capstyle="projecting", miterlimit=10)
+try:
+    pltrs_test.Line([1.0, 2.0], linestyle=[4, 2, 1])
+    odd_pattern_accepted = True
+except ValueError:
+    odd_pattern_accepted = False
+try:
+    pltrs_test.Line([1.0, 2.0], miterlimit=0.5)
+    short_miter_accepted = True
+except ValueError:
+    short_miter_accepted = False
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let styles = |name: &str| {
+                let plot = locals.get_item(name).unwrap().unwrap();
+                let plot = plot.cast::<line::PyLine>().unwrap().borrow();
+                take_registered_handle(plot.id);
+                plot.plot
+                    .series
+                    .iter()
+                    .map(|series| match series {
+                        PlotSeries::Line(line) => line.style.clone(),
+                        _ => panic!("expected line series"),
+                    })
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(styles("shared"), vec![LineStyle::Custom(vec![8.0, 4.0]); 2]);
+            assert_eq!(
+                styles("mixed"),
+                vec![
+                    LineStyle::Dashed,
+                    LineStyle::Custom(vec![1.0, 2.0, 3.0, 4.0])
+                ]
+            );
+            let joined = locals.get_item("joined").unwrap().unwrap();
+            let joined = joined.cast::<line::PyLine>().unwrap().borrow();
+            take_registered_handle(joined.id);
+            let Some(PlotSeries::Line(line)) = joined.plot.series.first() else {
+                panic!("expected a line series");
+            };
+            assert_eq!((line.join, line.cap), (LineJoin::Miter, LineCap::Square));
+            assert_eq!(line.miter_limit, 10.0);
+
+            let odd_pattern_accepted = locals.get_item("odd_pattern_accepted").unwrap().unwrap();
+            assert!(!odd_pattern_accepted.extract::<bool>().unwrap());
+            let short_miter_accepted = locals.get_item("short_miter_accepted").unwrap().unwrap();
+            assert!(!short_miter_accepted.extract::<bool>().unwrap());
+        });
+    }
+
+    #[test]
+    fn scatter_colors_points_through_a_colormap() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<scatter::PyScatter>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+points = pltrs_test.Scatter(
+    [1.0, 2.0, 3.0],
+    c=[0.0, 5.0, float("nan")],
+    cmap=[(0.0, 0.0, 0.0), (1.0, 1.0, 1.0)],
+    vmax=10.0,
+    colorbar="load",
+    alpha=0.5,
+)
+try:
+    pltrs_test.Scatter([1.0, 2.0], c=[1.0])
+    short_c_accepted = True
+except ValueError:
+    short_c_accepted = False
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let points = locals.get_item("points").unwrap().unwrap();
+            let points = points.cast::<scatter::PyScatter>().unwrap().borrow();
+            take_registered_handle(points.id);
+            let Some(PlotSeries::Scatter(scatter)) = points.plot.series.first() else {
+                panic!("expected a scatter series");
+            };
+            let colors = scatter.colors.as_ref().unwrap();
+            assert_eq!((colors[0].r, colors[1].r), (0.0, 0.5));
+            assert_eq!(colors[2].a, 0.0);
+            assert_eq!((colors[0].a, scatter.color.a), (0.5, 0.5));
+            let colorbar = points.plot.colorbar.as_ref().unwrap();
+            assert_eq!(colorbar.mapping.range, (0.0, 10.0));
+            assert_eq!(colorbar.label.as_deref(), Some("load"));
+
+            let short_c_accepted = locals.get_item("short_c_accepted").unwrap().unwrap();
+            assert!(!short_c_accepted.extract::<bool>().unwrap());
+        });
+    }
+
+    #[test]
+    fn scatter_sizes_points_by_value() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<scatter::PyScatter>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+raw = pltrs_test.Scatter([1.0, 2.0], s=[6.0, 12.0], size_legend=False)
+bubbles = pltrs_test.Scatter(
+    [1.0, 2.0, 3.0],
+    s=[0.0, 25.0, 100.0],
+    s_range=(5.0, 11.0),
+    s_area=True,
+    size_legend="outside right",
+    size_label="population",
+)
+try:
+    pltrs_test.Scatter([1.0, 2.0], s=[-3.0, 12.0])
+    negative_s_accepted = True
+except ValueError:
+    negative_s_accepted = False
+bad_ranges = 0
+for s_range in [(0.0, 40.0), (-4.0, 40.0), (4.0, float("inf")), (40.0, 4.0)]:
+    try:
+        pltrs_test.Scatter([1.0, 2.0], s=[1.0, 2.0], s_range=s_range)
+    except ValueError:
+        bad_ranges += 1
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let raw = locals.get_item("raw").unwrap().unwrap();
+            let raw = raw.cast::<scatter::PyScatter>().unwrap().borrow();
+            take_registered_handle(raw.id);
+            let Some(PlotSeries::Scatter(scatter)) = raw.plot.series.first() else {
+                panic!("expected a scatter series");
+            };
+            assert_eq!(scatter.sizes, Some(vec![6.0, 12.0]));
+            assert!(raw.plot.size_legend.is_none());
+
+            let bubbles = locals.get_item("bubbles").unwrap().unwrap();
+            let bubbles = bubbles.cast::<scatter::PyScatter>().unwrap().borrow();
+            take_registered_handle(bubbles.id);
+            let Some(PlotSeries::Scatter(scatter)) = bubbles.plot.series.first() else {
+                panic!("expected a scatter series");
+            };
+            assert_eq!(scatter.sizes, Some(vec![5.0, 7.0, 11.0]));
+            let size_legend = bubbles.plot.size_legend.as_ref().unwrap();
+            assert_eq!(size_legend.label.as_deref(), Some("population"));
+            assert!(bubbles.plot.plot_rect.w < plot::plot_rect().w);
+            let negative_s_accepted = locals.get_item("negative_s_accepted").unwrap().unwrap();
+            assert!(!negative_s_accepted.extract::<bool>().unwrap());
+            let bad_ranges = locals.get_item("bad_ranges").unwrap().unwrap();
+            assert_eq!(bad_ranges.extract::<usize>().unwrap(), 4);
+        });
+    }
+
+    #[test]
+    fn scatter_markers_accept_names_shorthands_and_outlines() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<scatter::PyScatter>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+plain = pltrs_test.Scatter([[1.0], [2.0]], marker=["star", "^"])
+outlined = pltrs_test.Scatter([1.0, 2.0], marker="D", edgecolor=(0.0, 0.0, 1.0))
+try:
+    pltrs_test.Scatter([1.0], marker="blob")
+    bad_marker = None
+except ValueError as err:
+    bad_marker = str(err)
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let plain = locals.get_item("plain").unwrap().unwrap();
+            let plain = plain.cast::<scatter::PyScatter>().unwrap().borrow();
+            take_registered_handle(plain.id);
+            let markers: Vec<_> = plain
+                .plot
+                .series
+                .iter()
+                .map(|series| match series {
+                    PlotSeries::Scatter(scatter) => (scatter.marker, scatter.edge_width),
+                    _ => panic!("expected scatter series"),
+                })
+                .collect();
+            assert_eq!(
+                markers,
+                vec![(Marker::Star, 0.0), (Marker::TriangleUp, 0.0)]
+            );
+
+            let outlined = locals.get_item("outlined").unwrap().unwrap();
+            let outlined = outlined.cast::<scatter::PyScatter>().unwrap().borrow();
+            take_registered_handle(outlined.id);
+            let Some(PlotSeries::Scatter(scatter)) = outlined.plot.series.first() else {
+                panic!("expected a scatter series");
+            };
+            assert_eq!(scatter.marker, Marker::Diamond);
+            assert_eq!(scatter.edge_width, 1.0);
+            assert_eq!(scatter.edge_color.b, 1.0);
+
+            let bad_marker: Option<String> = locals
+                .get_item("bad_marker")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(bad_marker.unwrap().contains("unknown marker 'blob'"));
+        });
+    }
+
     #[test]
     fn resolve_output_path_uses_script_directory_for_relative_targets() {
         let resolved = resolve_output_path_from_base(
@@ -426,4 +966,184 @@ fig = pltrs_test.Bar(
 
         assert_eq!(resolved, PathBuf::from("/tmp/current/plot.png"));
     }
+
+    #[test]
+    fn save_picks_the_format_from_the_extension() {
+        Python::attach(|_| {
+            let fig = Figure::new(Size {
+                width: 40,
+                height: 30,
+                dpi: 1.0,
+            });
+            let path = std::env::temp_dir().join("pltrs_save_format_test.SVG");
+            save_figure(&fig, &path, 4, Renderer::Auto).unwrap();
+            let svg = std::fs::read_to_string(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert!(svg.contains(r#"viewBox="0 0 40 30""#));
+
+            let path = std::env::temp_dir().join("pltrs_save_format_test.pdf");
+            save_figure(&fig, &path, 4, Renderer::Auto).unwrap();
+            let pdf = std::fs::read(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert!(pdf.starts_with(b"%PDF-"));
+
+            let path = std::env::temp_dir().join("pltrs_save_format_test.png");
+            save_figure(&fig, &path, 4, check_renderer("cpu").unwrap()).unwrap();
+            let png = std::fs::read(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert!(png.starts_with(b"\x89PNG"));
+            assert!(check_renderer("opengl").is_err());
+            assert!(save_figure(&fig, Path::new("plot.eps"), 4, Renderer::Auto).is_err());
+            assert!(save_figure(&fig, Path::new("plot"), 4, Renderer::Auto).is_err());
+        });
+    }
+
+    #[test]
+    fn rejected_save_arguments_leave_the_plot_queued() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<line::PyLine>().unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+line = pltrs_test.Line([1.0, 2.0, 3.0])
+rejected = 0
+for method, args, kwargs in [
+    ("save", ("out.png",), {"dpi": 0}),
+    ("save", ("out.png",), {"samples": 3}),
+    ("save", ("out.png",), {"renderer": "gpux"}),
+    ("save", ("out.eps",), {}),
+    ("to_png", (), {"dpi": -1}),
+    ("to_rgba", (), {"renderer": "gpux"}),
+]:
+    try:
+        getattr(line, method)(*args, **kwargs)
+    except ValueError:
+        rejected += 1
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let rejected: usize = locals
+                .get_item("rejected")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(rejected, 6);
+            let line = locals.get_item("line").unwrap().unwrap();
+            let line = line.cast::<line::PyLine>().unwrap().borrow();
+            assert!(take_registered_handle(line.id).is_some());
+        });
+    }
+
+    #[test]
+    fn save_pdf_keeps_the_queue_until_the_file_is_written() {
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module.add_class::<line::PyLine>().unwrap();
+            module
+                .add_function(wrap_pyfunction!(save_pdf, &module).unwrap())
+                .unwrap();
+
+            let path = std::env::temp_dir().join("pltrs_save_pdf_queue_test.pdf");
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            locals.set_item("path", path.to_str().unwrap()).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+line = pltrs_test.Line([1.0, 2.0, 3.0])
+errors = []
+for target in ["report.png", "/nonexistent-pltrs-dir/report.pdf"]:
+    try:
+        pltrs_test.save_pdf(target)
+    except (ValueError, RuntimeError) as err:
+        errors.append(type(err).__name__)
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+
+            let errors: Vec<String> = locals
+                .get_item("errors")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(errors, ["ValueError", "RuntimeError"]);
+            let line = locals.get_item("line").unwrap().unwrap();
+            let id = line.cast::<line::PyLine>().unwrap().borrow().id;
+            assert!(FIGURE_REGISTRY
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|entry| entry.id == id));
+
+            py.run(
+                pyo3::ffi::c_str!("pltrs_test.save_pdf(path)"),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let pdf = std::fs::read(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            assert!(pdf.starts_with(b"%PDF-"));
+            assert!(take_registered_handle(id).is_none());
+        });
+    }
+
+    #[test]
+    fn samples_must_be_one_four_or_eight() {
+        for samples in [1, 4, 8] {
+            assert_eq!(check_samples(samples).unwrap(), samples);
+        }
+        for samples in [0, 2, 16] {
+            assert!(check_samples(samples).is_err());
+        }
+
+        Python::attach(|py| {
+            let module = PyModule::new(py, "pltrs_test").unwrap();
+            module
+                .add_function(wrap_pyfunction!(show, &module).unwrap())
+                .unwrap();
+
+            let locals = [("pltrs_test", module)].into_py_dict(py).unwrap();
+            py.run(
+                pyo3::ffi::c_str!(
+                    r#"
+try:
+    pltrs_test.show(samples=2)
+    rejected = False
+except ValueError:
+    rejected = True
+"#
+                ),
+                None,
+                Some(&locals),
+            )
+            .unwrap();
+            let rejected: bool = locals
+                .get_item("rejected")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(rejected);
+        });
+    }
+
+    #[test]
+    fn save_dpi_scales_from_base_dpi() {
+        assert_eq!(dpi_scale(200.0).unwrap(), 2.0);
+        assert_eq!(dpi_scale(BASE_DPI).unwrap(), 1.0);
+        assert!(dpi_scale(0.0).is_err());
+        assert!(dpi_scale(f32::NAN).is_err());
+    }
 }
@@ -1,16 +1,21 @@
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyIterator;
+use pyo3::types::{PyBytes, PyIterator, PyString};
 
-use pltrs_backend_wgpu::{run_with_plot, save_figure_png};
-use pltrs_core::{plot::PlotDefinition, Color};
+use pltrs_backend_wgpu::run_with_plot;
+use pltrs_core::{
+    plot::PlotDefinition, scale::ScaleKind, Color, Figure, LineCap, LineJoin, LineStyle,
+    DEFAULT_MITER_LIMIT,
+};
 
 use crate::data::{
-    compute_limits, extract_rgb, parse_series_collection, resolve_numeric_arg, try_extract_rgb,
+    extract_rgb, parse_legend, parse_limits, parse_scale, parse_series_collection, resolve_labels,
+    resolve_limits, resolve_numeric_arg, resolve_x_scale, try_extract_rgb, x_categories,
 };
-use crate::plot::{build_plot_definition, line_series, PlotOptions};
+use crate::plot::{attach_twin, build_plot_definition, line_series, LineStroke, PlotOptions};
 use crate::{
-    map_backend_error, next_figure_id, register_handle, resolve_output_path,
+    check_renderer, check_samples, dpi_scale, map_backend_error, next_figure_id, output_format,
+    png_bytes, register_handle, render_figure, resolve_output_path, rgba_view, save_figure,
     take_registered_handle, PlotHandle,
 };
 
@@ -20,8 +25,8 @@ use crate::{
 /// No rendering happens until `.show()` is called.
 #[pyclass(name = "Line")]
 pub struct PyLine {
-    id: u64,
-    plot: PlotDefinition,
+    pub(crate) id: u64,
+    pub(crate) plot: PlotDefinition,
 }
 
 #[pymethods]
@@ -35,31 +40,75 @@ impl PyLine {
     ///     a list of such series.
     /// x : tuple(float, float), optional
     ///     Explicit x-axis range `(min, max)`. Inferred across all series if omitted.
+    ///     Datetimes are accepted on time axes.
     /// y : tuple(float, float), optional
     ///     Explicit y-axis range `(min, max)`. Inferred across all series if omitted.
+    /// xscale, yscale : str or tuple(str, float), optional
+    ///     Axis scale: `"linear"`, `"log"`, `"log2"`, `"symlog"`,
+    ///     `("symlog", linthresh)` or `"time"`. The x axis defaults to `"time"`
+    ///     when x values are `datetime` or numpy `datetime64`, else `"linear"`.
+    /// xinvert, yinvert : bool, optional
+    ///     Flip the axis so values grow leftwards / downwards.
     /// color : tuple(float, float, float) or list[tuple(float, float, float)], optional
     ///     One RGB color or one per series.
     /// width : float or list[float], optional
     ///     One line width or one per series.
+    /// joinstyle : str, optional
+    ///     How segments meet at data points: `"round"` (default), `"miter"`
+    ///     or `"bevel"`.
+    /// miterlimit : float, optional
+    ///     Longest miter join, as a multiple of the line width; longer ones
+    ///     are beveled. Defaults to 4 and must be at least 1.
+    /// capstyle : str, optional
+    ///     How line ends are drawn: `"butt"` (default), `"round"` or
+    ///     `"square"` (also `"projecting"`).
+    /// linestyle : str or list[float] or list, optional
+    ///     `"solid"`, `"dashed"`, `"dotted"` or `"dashdot"` (also `"-"`,
+    ///     `"--"`, `":"` and `"-."`), or a custom pattern of alternating dash
+    ///     and gap lengths in pixels such as `[12, 4, 2, 4]`. Give a list of
+    ///     these for one style per series. Named patterns scale with the line
+    ///     width. Dashes run on across data points and keep their length when
+    ///     zooming.
     /// annotations : list[tuple(float, float, str)], optional
     ///     Text labels given as `(x, y, label)` in data coordinates.
     /// title, x_label, y_label : str, optional
     ///     Plot title and axis labels.
     /// grid : bool, optional
     ///     Draw background grid lines and labeled axes. Enabled by default.
+    /// label : str or list[str | None], optional
+    ///     Legend label for the series, or one per series.
+    /// legend : bool or str, optional
+    ///     Legend location: `"best"`, `"upper right"`, `"upper left"`,
+    ///     `"lower left"`, `"lower right"` or `"outside right"`. Shown at
+    ///     `"best"` when any series is labelled; `False` hides it.
+    /// legend_autoscale : bool, optional
+    ///     Refit the y range to the visible series when a legend entry is
+    ///     clicked in the window.
     #[new]
-    #[pyo3(signature = (data, *, x=None, y=None, color=None, width=None, annotations=None, title=None, x_label=None, y_label=None, grid=true))]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (data, *, x=None, y=None, xscale=None, yscale=None, xinvert=false, yinvert=false, color=None, width=None, linestyle=None, joinstyle=None, capstyle=None, miterlimit=DEFAULT_MITER_LIMIT, annotations=None, title=None, x_label=None, y_label=None, grid=true, label=None, legend=None, legend_autoscale=false))]
     fn new(
         data: &Bound<'_, PyAny>,
-        x: Option<(f64, f64)>,
+        x: Option<&Bound<'_, PyAny>>,
         y: Option<(f64, f64)>,
+        xscale: Option<&Bound<'_, PyAny>>,
+        yscale: Option<&Bound<'_, PyAny>>,
+        xinvert: bool,
+        yinvert: bool,
         color: Option<&Bound<'_, PyAny>>,
         width: Option<&Bound<'_, PyAny>>,
+        linestyle: Option<&Bound<'_, PyAny>>,
+        joinstyle: Option<&str>,
+        capstyle: Option<&str>,
+        miterlimit: f32,
         annotations: Option<Vec<(f64, f64, String)>>,
         title: Option<String>,
         x_label: Option<String>,
         y_label: Option<String>,
         grid: bool,
+        label: Option<&Bound<'_, PyAny>>,
+        legend: Option<&Bound<'_, PyAny>>,
+        legend_autoscale: bool,
     ) -> PyResult<Self> {
         let series = parse_series_collection(data)?;
         let all_xs: Vec<f64> = series
@@ -71,59 +120,165 @@ impl PyLine {
             .flat_map(|series| series.ys.iter().copied())
             .collect();
 
-        let xlim = x.unwrap_or_else(|| compute_limits(&all_xs, 0.05));
-        let ylim = y.unwrap_or_else(|| compute_limits(&all_ys, 0.05));
+        let x_scale = resolve_x_scale(xscale, &series)?;
+        let x_categories = x_categories(&series);
+        let y_scale = parse_scale(yscale, ScaleKind::Linear, "yscale")?;
+        let xlim = resolve_limits(parse_limits(x, "x")?, &all_xs, 0.05, x_scale, xinvert, "x")?;
+        let ylim = resolve_limits(y, &all_ys, 0.05, y_scale, yinvert, "y")?;
 
         let colors = resolve_line_colors(color, series.len())?;
         let widths = resolve_numeric_arg(width, series.len(), 9.0, "width")?;
+        let styles = resolve_line_styles(linestyle, series.len())?;
+        let join = joinstyle.map(parse_join).transpose()?.unwrap_or_default();
+        let cap = capstyle.map(parse_cap).transpose()?.unwrap_or_default();
+        if !(miterlimit.is_finite() && miterlimit >= 1.0) {
+            return Err(PyValueError::new_err(format!(
+                "miterlimit must be at least 1, got {miterlimit}"
+            )));
+        }
+        let labels = resolve_labels(label, series.len())?;
+        let legend = parse_legend(legend, labels.iter().any(Option::is_some), "legend")?;
 
         let plot = build_plot_definition(
             PlotOptions {
                 xlim,
                 ylim,
+                x_scale,
+                y_scale,
+                x_categories,
                 annotations: annotations.unwrap_or_default(),
                 title,
                 x_label,
                 y_label,
                 grid,
+                legend,
+                legend_autoscale,
+                colorbar: None,
+                size_legend: None,
             },
             series
                 .into_iter()
                 .zip(colors)
                 .zip(widths)
-                .map(|((series, (r, g, b)), line_width)| {
-                    line_series(series.xs, series.ys, Color { r, g, b, a: 1.0 }, line_width)
+                .zip(styles)
+                .zip(labels)
+                .map(|((((series, (r, g, b)), line_width), style), label)| {
+                    let stroke = LineStroke {
+                        color: Color { r, g, b, a: 1.0 },
+                        width: line_width,
+                        style,
+                        join,
+                        cap,
+                        miter_limit: miterlimit,
+                    };
+                    line_series(series.xs, series.ys, stroke, label)
                 })
                 .collect(),
         );
         let id = next_figure_id();
 
-        register_handle(id, PlotHandle::Plot(plot.clone()));
+        register_handle(id, PlotHandle::Plot(Box::new(plot.clone())));
 
         Ok(Self { id, plot })
     }
 
-    /// Render this figure in a window.
-    fn show(&self) -> PyResult<()> {
-        let plot = match take_registered_handle(self.id) {
-            Some(PlotHandle::Plot(plot)) => plot,
-            Some(PlotHandle::Figure(_)) => self.plot.clone(),
-            None => self.plot.clone(),
-        };
-        run_with_plot(plot).map_err(map_backend_error)
+    /// Plot the series of another `Line`, `Scatter` or `Bar` against a
+    /// right-hand y axis of this plot, with that plot's y limits, y scale and
+    /// `y_label`. Both plots must use the same x scale; the x limits grow to
+    /// cover both. The other plot is no longer shown on its own.
+    fn twinx(&mut self, other: &Bound<'_, PyAny>) -> PyResult<()> {
+        attach_twin(self.id, &mut self.plot, other)
     }
 
-    /// Render this figure offscreen and save it as a PNG.
-    #[pyo3(signature = (path=None))]
-    fn save(&self, py: Python<'_>, path: Option<&str>) -> PyResult<()> {
+    /// Render this figure in a window. `samples` is the number of samples
+    /// per pixel used to smooth edges: 1 (no anti-aliasing), 4 or 8.
+    #[pyo3(signature = (*, samples=4))]
+    fn show(&self, samples: u32) -> PyResult<()> {
+        let samples = check_samples(samples)?;
         let plot = match take_registered_handle(self.id) {
-            Some(PlotHandle::Plot(plot)) => plot,
-            Some(PlotHandle::Figure(_)) => self.plot.clone(),
-            None => self.plot.clone(),
+            Some(PlotHandle::Plot(plot)) => *plot,
+            _ => self.plot.clone(),
         };
-        let fig = plot.build_figure(&plot.initial_view());
+        run_with_plot(plot, samples).map_err(map_backend_error)
+    }
+
+    /// Save this figure as a PNG, or as a vector SVG or PDF when `path`
+    /// ends in `.svg` or `.pdf`.
+    ///
+    /// `dpi` sets the resolution of PNGs: sizes are given at 100 dpi, so
+    /// `dpi=200` saves the same layout at twice the width and height in
+    /// pixels. `samples` is the number of samples per pixel used to smooth
+    /// edges: 1 (no anti-aliasing), 4 or 8. `renderer` picks what draws
+    /// PNGs: `"gpu"`, `"cpu"` for the software renderer that needs no GPU,
+    /// or `"auto"` for the GPU when there is one and the CPU otherwise.
+    #[pyo3(signature = (path=None, *, dpi=100.0, samples=4, renderer="auto"))]
+    fn save(
+        &self,
+        py: Python<'_>,
+        path: Option<&str>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<()> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
         let output_path = resolve_output_path(py, path)?;
-        save_figure_png(&fig, &output_path).map_err(|e| PyRuntimeError::new_err(format!("{e}")))
+        output_format(&output_path)?;
+        let fig = self.take_figure(scale);
+        save_figure(&fig, &output_path, samples, renderer)
+    }
+
+    /// Render this figure as the bytes of a PNG file, as `save()` would
+    /// write it, for serving or embedding without a file on disk. Takes the
+    /// same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_png<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale);
+        let image = render_figure(&fig, samples, renderer)?;
+        png_bytes(py, &image)
+    }
+
+    /// Render this figure to 8-bit RGBA pixels, rows top to bottom, as a
+    /// read-only memoryview of shape `(height, width, 4)`. `bytes()` of it
+    /// gives the raw pixels and `numpy.asarray()` an array without a copy.
+    /// Takes the same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_rgba<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale);
+        let image = render_figure(&fig, samples, renderer)?;
+        rgba_view(py, &image)
+    }
+}
+
+impl PyLine {
+    /// Take this plot out of the queue of `show()` and lay it out at
+    /// `scale` device pixels per logical pixel.
+    fn take_figure(&self, scale: f32) -> Figure {
+        let mut plot = match take_registered_handle(self.id) {
+            Some(PlotHandle::Plot(plot)) => *plot,
+            _ => self.plot.clone(),
+        };
+        plot.size.dpi = scale;
+        plot.build_figure(&plot.initial_view())
     }
 }
 
@@ -166,3 +321,88 @@ fn resolve_line_colors(
         }
     }
 }
+
+fn resolve_line_styles(
+    linestyle: Option<&Bound<'_, PyAny>>,
+    series_count: usize,
+) -> PyResult<Vec<LineStyle>> {
+    let Some(obj) = linestyle else {
+        return Ok(vec![LineStyle::Solid; series_count]);
+    };
+
+    if obj.is_instance_of::<PyString>() || obj.extract::<Vec<f32>>().is_ok() {
+        let style = parse_line_style(obj)?;
+        return Ok(vec![style; series_count]);
+    }
+
+    let styles = PyIterator::from_object(obj)
+        .map_err(|_| {
+            PyValueError::new_err("linestyle must be a string, a dash pattern or a list of these")
+        })?
+        .map(|item| parse_line_style(&item?))
+        .collect::<PyResult<Vec<_>>>()?;
+
+    if styles.len() != series_count {
+        return Err(PyValueError::new_err(format!(
+            "linestyle expected 1 value or {series_count} values, got {}",
+            styles.len()
+        )));
+    }
+
+    Ok(styles)
+}
+
+fn parse_line_style(obj: &Bound<'_, PyAny>) -> PyResult<LineStyle> {
+    if let Ok(name) = obj.extract::<String>() {
+        return match name.as_str() {
+            "solid" | "-" => Ok(LineStyle::Solid),
+            "dashed" | "--" => Ok(LineStyle::Dashed),
+            "dotted" | ":" => Ok(LineStyle::Dotted),
+            "dashdot" | "-." => Ok(LineStyle::DashDot),
+            other => Err(PyValueError::new_err(format!(
+                "unknown linestyle '{other}', expected 'solid', 'dashed', 'dotted', \
+                 'dashdot' or a list of dash and gap lengths"
+            ))),
+        };
+    }
+
+    let pattern = obj.extract::<Vec<f32>>().map_err(|_| {
+        PyValueError::new_err("linestyle values must be strings or lists of numbers")
+    })?;
+    if pattern.is_empty() || pattern.len() % 2 == 1 || pattern.len() > LineStyle::MAX_DASHES {
+        return Err(PyValueError::new_err(format!(
+            "a dash pattern needs an even number of dash and gap lengths, at most {}",
+            LineStyle::MAX_DASHES
+        )));
+    }
+    if pattern.iter().any(|len| !len.is_finite() || *len < 0.0)
+        || pattern.iter().sum::<f32>() <= 0.0
+    {
+        return Err(PyValueError::new_err(
+            "dash pattern lengths must be non-negative and not all zero",
+        ));
+    }
+    Ok(LineStyle::Custom(pattern))
+}
+
+fn parse_join(name: &str) -> PyResult<LineJoin> {
+    match name {
+        "round" => Ok(LineJoin::Round),
+        "miter" => Ok(LineJoin::Miter),
+        "bevel" => Ok(LineJoin::Bevel),
+        other => Err(PyValueError::new_err(format!(
+            "unknown joinstyle '{other}', expected 'round', 'miter' or 'bevel'"
+        ))),
+    }
+}
+
+fn parse_cap(name: &str) -> PyResult<LineCap> {
+    match name {
+        "butt" => Ok(LineCap::Butt),
+        "round" => Ok(LineCap::Round),
+        "square" | "projecting" => Ok(LineCap::Square),
+        other => Err(PyValueError::new_err(format!(
+            "unknown capstyle '{other}', expected 'butt', 'round' or 'square'"
+        ))),
+    }
+}
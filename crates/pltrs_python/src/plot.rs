@@ -1,16 +1,35 @@
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+
 use pltrs_core::{
-    plot::{PlotDefinition, PlotSeries},
-    scene::{Bar, Color, Line, Marker, Rect, Scatter, Size, Text},
+    colormap::Colorbar,
+    legend::{legend_size_px, LegendLoc, SizeLegend},
+    plot::{union_limits, PlotDefinition, PlotSeries, SecondaryAxis},
+    scale::ScaleKind,
+    scene::{Bar, Color, Line, LineCap, LineJoin, LineStyle, Marker, Rect, Scatter, Size, Text},
 };
 
+use crate::bar::PyBar;
+use crate::line::PyLine;
+use crate::scatter::PyScatter;
+use crate::{take_registered_handle, update_registered_handle, PlotHandle};
+
 pub struct PlotOptions {
     pub xlim: (f64, f64),
     pub ylim: (f64, f64),
+    pub x_scale: ScaleKind,
+    pub y_scale: ScaleKind,
+    /// Names for the slots of a categorical x axis.
+    pub x_categories: Vec<String>,
     pub annotations: Vec<(f64, f64, String)>,
     pub title: Option<String>,
     pub x_label: Option<String>,
     pub y_label: Option<String>,
     pub grid: bool,
+    pub legend: Option<LegendLoc>,
+    pub legend_autoscale: bool,
+    pub colorbar: Option<Colorbar>,
+    pub size_legend: Option<SizeLegend>,
 }
 
 pub fn default_figure_size() -> Size {
@@ -31,12 +50,15 @@ pub fn plot_rect() -> Rect {
 }
 
 pub fn build_plot_definition(options: PlotOptions, series: Vec<PlotSeries>) -> PlotDefinition {
-    PlotDefinition {
+    let mut plot = PlotDefinition {
         size: default_figure_size(),
         clear_color: Color::WHITE,
         plot_rect: plot_rect(),
         base_xlim: options.xlim,
         base_ylim: options.ylim,
+        x_scale: options.x_scale,
+        y_scale: options.y_scale,
+        x_categories: options.x_categories,
         title: options.title,
         x_label: options.x_label,
         y_label: options.y_label,
@@ -53,39 +75,175 @@ pub fn build_plot_definition(options: PlotOptions, series: Vec<PlotSeries>) -> P
             })
             .collect(),
         series,
+        y2: None,
+        legend: options.legend,
+        legend_autoscale: options.legend_autoscale,
+        colorbar: options.colorbar,
+        size_legend: options.size_legend,
+    };
+    plot.plot_rect = fit_plot_rect(&plot, plot.plot_rect);
+    plot
+}
+
+/// Spacing between the plot area and a legend placed outside it.
+const OUTSIDE_LEGEND_GAP_PX: f32 = 20.0;
+
+/// Shrink `rect` so a secondary y axis, a colorbar and an outside legend fit
+/// to its right.
+pub fn fit_plot_rect(plot: &PlotDefinition, rect: Rect) -> Rect {
+    let mut margin_px = plot.right_decorations_px();
+    let labels: Vec<&str> = plot.all_series().filter_map(PlotSeries::label).collect();
+    let mut outside_px: f32 = 0.0;
+    if plot.legend == Some(LegendLoc::OutsideRight) && !labels.is_empty() {
+        outside_px = legend_size_px(labels).0;
+    }
+    if let Some(size_legend) = plot
+        .size_legend
+        .as_ref()
+        .filter(|legend| legend.loc == LegendLoc::OutsideRight)
+    {
+        outside_px = outside_px.max(size_legend.size_px().0);
+    }
+    if outside_px > 0.0 {
+        margin_px += outside_px + OUTSIDE_LEGEND_GAP_PX;
+    }
+    Rect {
+        w: (rect.w - margin_px / plot.size.width.max(1) as f32).max(0.0),
+        ..rect
     }
 }
 
-pub fn line_series(xs: Vec<f64>, ys: Vec<f64>, color: Color, width: f32) -> PlotSeries {
+/// Move the series of `other` onto a right-hand y axis of `plot`, keeping
+/// their y limits, scale and `y_label`, and widen the x limits of `plot` to
+/// cover both. `other` is no longer shown on its own.
+pub fn attach_twin(id: u64, plot: &mut PlotDefinition, other: &Bound<'_, PyAny>) -> PyResult<()> {
+    let (other_id, other) = extract_plot(other, "a plot cannot be its own twin")?;
+    if other_id == id {
+        return Err(PyValueError::new_err("a plot cannot be its own twin"));
+    }
+    if other.y2.is_some() {
+        return Err(PyValueError::new_err(
+            "the twin plot already has a secondary axis",
+        ));
+    }
+    if other.x_scale != plot.x_scale || other.x_categories != plot.x_categories {
+        return Err(PyValueError::new_err(
+            "the twin plot must use the same x scale and categories",
+        ));
+    }
+
+    take_registered_handle(other_id);
+    if let Some(xlim) = union_limits([plot.base_xlim, other.base_xlim].into_iter()) {
+        plot.base_xlim = xlim;
+    }
+    plot.y2 = Some(SecondaryAxis {
+        base_lim: other.base_ylim,
+        scale: other.y_scale,
+        label: other.y_label,
+        series: other.series,
+    });
+    plot.legend = plot.legend.or(other.legend);
+    plot.colorbar = plot.colorbar.take().or(other.colorbar);
+    plot.size_legend = plot.size_legend.take().or(other.size_legend);
+    plot.plot_rect = fit_plot_rect(plot, plot_rect());
+    update_registered_handle(id, PlotHandle::Plot(Box::new(plot.clone())));
+    Ok(())
+}
+
+/// The registry id and definition behind a `Line`, `Scatter` or `Bar`.
+/// `busy` is the error message used when the plot is already borrowed.
+pub fn extract_plot(obj: &Bound<'_, PyAny>, busy: &str) -> PyResult<(u64, PlotDefinition)> {
+    let busy = |_| PyValueError::new_err(busy.to_string());
+    if let Ok(line) = obj.cast::<PyLine>() {
+        let line = line.try_borrow().map_err(busy)?;
+        return Ok((line.id, line.plot.clone()));
+    }
+    if let Ok(scatter) = obj.cast::<PyScatter>() {
+        let scatter = scatter.try_borrow().map_err(busy)?;
+        return Ok((scatter.id, scatter.plot.clone()));
+    }
+    if let Ok(bar) = obj.cast::<PyBar>() {
+        let bar = bar.try_borrow().map_err(busy)?;
+        return Ok((bar.id, bar.plot.clone()));
+    }
+    Err(PyTypeError::new_err("plot must be a Line, Scatter or Bar"))
+}
+
+/// How one line series is stroked.
+pub struct LineStroke {
+    pub color: Color,
+    pub width: f32,
+    pub style: LineStyle,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+}
+
+pub fn line_series(
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    stroke: LineStroke,
+    label: Option<String>,
+) -> PlotSeries {
     PlotSeries::Line(Line {
         xs,
         ys,
-        color,
-        width,
+        color: stroke.color,
+        width: stroke.width,
+        style: stroke.style,
+        join: stroke.join,
+        cap: stroke.cap,
+        miter_limit: stroke.miter_limit,
+        label,
     })
 }
 
+/// How the markers of one scatter series are drawn.
+pub struct ScatterStyle {
+    pub color: Color,
+    pub size: f32,
+    pub marker: Marker,
+    /// Per-point colours used instead of `color`.
+    pub colors: Option<Vec<Color>>,
+    /// Per-point sizes used instead of `size`.
+    pub sizes: Option<Vec<f32>>,
+    /// Outline drawn inside each marker; no outline when `edge_width` is 0.
+    pub edge_color: Color,
+    pub edge_width: f32,
+}
+
 pub fn scatter_series(
     xs: Vec<f64>,
     ys: Vec<f64>,
-    color: Color,
-    size: f32,
-    marker: Marker,
+    style: ScatterStyle,
+    label: Option<String>,
 ) -> PlotSeries {
     PlotSeries::Scatter(Scatter {
         xs,
         ys,
-        color,
-        size,
-        marker,
+        color: style.color,
+        size: style.size,
+        marker: style.marker,
+        colors: style.colors,
+        sizes: style.sizes,
+        edge_color: style.edge_color,
+        edge_width: style.edge_width,
+        label,
     })
 }
 
-pub fn bar_series(xs: Vec<f64>, heights: Vec<f64>, color: Color, width: f32) -> PlotSeries {
+pub fn bar_series(
+    xs: Vec<f64>,
+    heights: Vec<f64>,
+    color: Color,
+    width: f32,
+    label: Option<String>,
+) -> PlotSeries {
     PlotSeries::Bar(Bar {
         xs,
         heights,
         width,
         color,
+        label,
     })
 }
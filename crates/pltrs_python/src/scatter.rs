@@ -1,16 +1,26 @@
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyIterator;
+use pyo3::types::{PyBytes, PyIterator};
 
-use pltrs_backend_wgpu::{run_with_plot, save_figure_png};
-use pltrs_core::{plot::PlotDefinition, scene::Marker, Color};
+use pltrs_backend_wgpu::run_with_plot;
+use pltrs_core::{
+    colormap::{ColorMapping, Colorbar},
+    legend::{SizeLegend, SizeMapping},
+    plot::PlotDefinition,
+    scale::ScaleKind,
+    scene::Marker,
+    Color, Figure,
+};
 
 use crate::data::{
-    compute_limits, extract_rgb, parse_series_collection, resolve_numeric_arg, try_extract_rgb,
+    extract_rgb, parse_colormap, parse_legend, parse_limits, parse_scale, parse_series_collection,
+    resolve_labels, resolve_limits, resolve_numeric_arg, resolve_x_scale, try_extract_rgb,
+    x_categories, SeriesData,
 };
-use crate::plot::{build_plot_definition, scatter_series, PlotOptions};
+use crate::plot::{attach_twin, build_plot_definition, scatter_series, PlotOptions, ScatterStyle};
 use crate::{
-    map_backend_error, next_figure_id, register_handle, resolve_output_path,
+    check_renderer, check_samples, dpi_scale, map_backend_error, next_figure_id, output_format,
+    png_bytes, register_handle, render_figure, resolve_output_path, rgba_view, save_figure,
     take_registered_handle, PlotHandle,
 };
 
@@ -20,8 +30,8 @@ use crate::{
 /// No rendering happens until `.show()` is called.
 #[pyclass(name = "Scatter")]
 pub struct PyScatter {
-    id: u64,
-    plot: PlotDefinition,
+    pub(crate) id: u64,
+    pub(crate) plot: PlotDefinition,
 }
 
 #[pymethods]
@@ -35,35 +45,116 @@ impl PyScatter {
     ///     a list of such series.
     /// x : tuple(float, float), optional
     ///     Explicit x-axis range `(min, max)`. Inferred across all series if omitted.
+    ///     Datetimes are accepted on time axes.
     /// y : tuple(float, float), optional
     ///     Explicit y-axis range `(min, max)`. Inferred across all series if omitted.
+    /// xscale, yscale : str or tuple(str, float), optional
+    ///     Axis scale: `"linear"`, `"log"`, `"log2"`, `"symlog"`,
+    ///     `("symlog", linthresh)` or `"time"`. The x axis defaults to `"time"`
+    ///     when x values are `datetime` or numpy `datetime64`, else `"linear"`.
+    /// xinvert, yinvert : bool, optional
+    ///     Flip the axis so values grow leftwards / downwards.
     /// color : tuple(float, float, float) or list[tuple(float, float, float)], optional
     ///     One RGB color or one per series.
     /// size : float or list[float], optional
     ///     One marker size or one per series.
     /// marker : str or list[str], optional
-    ///     One marker shape or one per series.
+    ///     One marker shape or one per series: `"circle"`, `"square"`,
+    ///     `"triangle_up"`, `"triangle_down"`, `"triangle_left"`,
+    ///     `"triangle_right"`, `"diamond"`, `"plus"`, `"cross"`, `"star"`,
+    ///     `"hexagon"` or `"pentagon"`, or the shorthands `"o"`, `"s"`, `"^"`,
+    ///     `"v"`, `"<"`, `">"`, `"D"`, `"+"`, `"x"`, `"*"`, `"h"` and `"p"`.
+    /// edgecolor : tuple(float, float, float) or list[tuple(float, float, float)], optional
+    ///     Marker outline color, one for all series or one per series.
+    /// edgewidth : float or list[float], optional
+    ///     Marker outline width in pixels, one or one per series. Defaults to
+    ///     1 when `edgecolor` is given, else no outline.
+    /// alpha : float, optional
+    ///     Marker opacity from 0 to 1, for colour-mapped points too.
+    ///     Defaults to 0.9.
+    /// c : list[float] or list[list[float]], optional
+    ///     One value per point, or one list per series, mapped through `cmap`
+    ///     in place of `color`. NaN values hide their point.
+    /// cmap : str or list[tuple(float, float, float)], optional
+    ///     `"viridis"` (default), `"magma"`, `"plasma"`, `"cividis"`,
+    ///     `"coolwarm"`, any of these with `"_r"` to reverse it, or a list of
+    ///     RGB colors from low to high.
+    /// vmin, vmax : float, optional
+    ///     Values mapped to the ends of the colormap. Inferred from `c` if omitted.
+    /// colorbar : bool or str, optional
+    ///     Draw a colorbar when `c` is given; a string labels it. Enabled by default.
+    /// s : list[float] or list[list[float]], optional
+    ///     One marker size in pixels per point, or one list per series, in
+    ///     place of `size`. NaN values hide their point. Values used as sizes
+    ///     directly, without `s_range` or `s_area`, must be positive.
+    /// s_range : tuple(float, float), optional
+    ///     Map `s` linearly onto this `(min, max)` pixel range instead of
+    ///     using the values as sizes. Both bounds must be positive, with
+    ///     `min <= max`.
+    /// s_area : bool, optional
+    ///     Make marker area rather than diameter grow linearly with `s`, so
+    ///     bubble area encodes the value. Uses `s_range=(4, 40)` unless given.
+    /// size_legend : bool or str, optional
+    ///     Location of a legend of sample sizes when `s` is given, like
+    ///     `legend`. Shown at `"best"` by default; `False` hides it.
+    /// size_label : str, optional
+    ///     Title of the size legend.
     /// annotations : list[tuple(float, float, str)], optional
     ///     Text labels given as `(x, y, label)` in data coordinates.
     /// title, x_label, y_label : str, optional
     ///     Plot title and axis labels.
     /// grid : bool, optional
     ///     Draw background grid lines and labeled axes. Enabled by default.
+    /// label : str or list[str | None], optional
+    ///     Legend label for the series, or one per series.
+    /// legend : bool or str, optional
+    ///     Legend location: `"best"`, `"upper right"`, `"upper left"`,
+    ///     `"lower left"`, `"lower right"` or `"outside right"`. Shown at
+    ///     `"best"` when any series is labelled; `False` hides it.
+    /// legend_autoscale : bool, optional
+    ///     Refit the y range to the visible series when a legend entry is
+    ///     clicked in the window.
     #[new]
-    #[pyo3(signature = (data, *, x=None, y=None, color=None, size=None, marker=None, annotations=None, title=None, x_label=None, y_label=None, grid=true))]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (data, *, x=None, y=None, xscale=None, yscale=None, xinvert=false, yinvert=false, color=None, size=None, marker=None, edgecolor=None, edgewidth=None, alpha=0.9, c=None, cmap=None, vmin=None, vmax=None, colorbar=None, s=None, s_range=None, s_area=false, size_legend=None, size_label=None, annotations=None, title=None, x_label=None, y_label=None, grid=true, label=None, legend=None, legend_autoscale=false))]
     fn new(
         data: &Bound<'_, PyAny>,
-        x: Option<(f64, f64)>,
+        x: Option<&Bound<'_, PyAny>>,
         y: Option<(f64, f64)>,
+        xscale: Option<&Bound<'_, PyAny>>,
+        yscale: Option<&Bound<'_, PyAny>>,
+        xinvert: bool,
+        yinvert: bool,
         color: Option<&Bound<'_, PyAny>>,
         size: Option<&Bound<'_, PyAny>>,
         marker: Option<&Bound<'_, PyAny>>,
+        edgecolor: Option<&Bound<'_, PyAny>>,
+        edgewidth: Option<&Bound<'_, PyAny>>,
+        alpha: f32,
+        c: Option<&Bound<'_, PyAny>>,
+        cmap: Option<&Bound<'_, PyAny>>,
+        vmin: Option<f64>,
+        vmax: Option<f64>,
+        colorbar: Option<&Bound<'_, PyAny>>,
+        s: Option<&Bound<'_, PyAny>>,
+        s_range: Option<(f32, f32)>,
+        s_area: bool,
+        size_legend: Option<&Bound<'_, PyAny>>,
+        size_label: Option<String>,
         annotations: Option<Vec<(f64, f64, String)>>,
         title: Option<String>,
         x_label: Option<String>,
         y_label: Option<String>,
         grid: bool,
+        label: Option<&Bound<'_, PyAny>>,
+        legend: Option<&Bound<'_, PyAny>>,
+        legend_autoscale: bool,
     ) -> PyResult<Self> {
+        if !(0.0..=1.0).contains(&alpha) {
+            return Err(PyValueError::new_err(format!(
+                "alpha must be between 0 and 1, got {alpha}"
+            )));
+        }
         let series = parse_series_collection(data)?;
         let all_xs: Vec<f64> = series
             .iter()
@@ -74,68 +165,351 @@ impl PyScatter {
             .flat_map(|series| series.ys.iter().copied())
             .collect();
 
-        let xlim = x.unwrap_or_else(|| compute_limits(&all_xs, 0.05));
-        let ylim = y.unwrap_or_else(|| compute_limits(&all_ys, 0.05));
+        let x_scale = resolve_x_scale(xscale, &series)?;
+        let x_categories = x_categories(&series);
+        let y_scale = parse_scale(yscale, ScaleKind::Linear, "yscale")?;
+        let xlim = resolve_limits(parse_limits(x, "x")?, &all_xs, 0.05, x_scale, xinvert, "x")?;
+        let ylim = resolve_limits(y, &all_ys, 0.05, y_scale, yinvert, "y")?;
 
         let colors = resolve_scatter_colors(color, series.len())?;
         let sizes = resolve_numeric_arg(size, series.len(), 15.0, "size")?;
         let markers = resolve_markers(marker, series.len())?;
+        let edge_colors = match edgecolor {
+            Some(obj) => resolve_rgb_values(obj, series.len(), "edgecolor")?,
+            None => vec![(0.0, 0.0, 0.0); series.len()],
+        };
+        let default_edge_width = if edgecolor.is_some() { 1.0 } else { 0.0 };
+        let edge_widths =
+            resolve_numeric_arg(edgewidth, series.len(), default_edge_width, "edgewidth")?;
+        let labels = resolve_labels(label, series.len())?;
+        let legend = parse_legend(legend, labels.iter().any(Option::is_some), "legend")?;
+        let values = resolve_point_values(c, &series, "c")?;
+        let mapping = match &values {
+            Some(values) => Some(ColorMapping {
+                cmap: parse_colormap(cmap)?,
+                range: value_range(values.iter().flatten().copied(), vmin, vmax),
+            }),
+            None => None,
+        };
+        let colorbar = match (&mapping, colorbar) {
+            (Some(mapping), colorbar) => parse_colorbar(colorbar)?.map(|label| Colorbar {
+                mapping: mapping.clone(),
+                label,
+            }),
+            (None, _) => None,
+        };
+        let point_colors: Vec<Option<Vec<Color>>> = match (&mapping, values) {
+            (Some(mapping), Some(values)) => values
+                .into_iter()
+                .map(|values| {
+                    Some(
+                        values
+                            .into_iter()
+                            .map(|value| {
+                                let color = mapping.color(value);
+                                Color {
+                                    a: color.a * alpha,
+                                    ..color
+                                }
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+            _ => vec![None; series.len()],
+        };
+
+        if let Some((lo, hi)) = s_range {
+            if !(lo.is_finite() && hi.is_finite() && lo > 0.0 && lo <= hi) {
+                return Err(PyValueError::new_err(format!(
+                    "s_range must be positive (min, max) sizes with min <= max, got ({lo}, {hi})"
+                )));
+            }
+        }
+        let size_values = resolve_point_values(s, &series, "s")?;
+        let raw_sizes = s_range.is_none() && !s_area;
+        if let Some(values) = size_values.as_ref().filter(|_| raw_sizes) {
+            if values.iter().flatten().any(|&value| value <= 0.0) {
+                return Err(PyValueError::new_err(
+                    "s values used as marker sizes must be positive; pass s_range to map them",
+                ));
+            }
+        }
+        let size_mapping = size_values.as_ref().map(|values| {
+            let (lo, hi) = value_range(values.iter().flatten().copied(), None, None);
+            let px = match (s_range, s_area) {
+                (Some(px), _) => px,
+                (None, true) => (4.0, 40.0),
+                // Values are sizes already.
+                (None, false) => (lo as f32, hi as f32),
+            };
+            SizeMapping {
+                range: (lo, hi),
+                px,
+                area: s_area,
+            }
+        });
+        // Sample markers take the series colour unless points are colour-mapped.
+        let size_legend_color = match (&mapping, colors.first()) {
+            (None, Some(&(r, g, b))) => Color { r, g, b, a: alpha },
+            _ => Color {
+                r: 0.45,
+                g: 0.47,
+                b: 0.52,
+                a: alpha,
+            },
+        };
+        let size_legend = match size_mapping {
+            Some(mapping) => {
+                parse_legend(size_legend, true, "size_legend")?.map(|loc| SizeLegend {
+                    mapping,
+                    loc,
+                    label: size_label,
+                    color: size_legend_color,
+                })
+            }
+            None => None,
+        };
+        let point_sizes: Vec<Option<Vec<f32>>> = match (size_mapping, size_values) {
+            (Some(mapping), Some(values)) => values
+                .into_iter()
+                .map(|values| {
+                    Some(
+                        values
+                            .into_iter()
+                            .map(|value| mapping.size(value))
+                            .collect(),
+                    )
+                })
+                .collect(),
+            _ => vec![None; series.len()],
+        };
         let id = next_figure_id();
 
         let plot = build_plot_definition(
             PlotOptions {
                 xlim,
                 ylim,
+                x_scale,
+                y_scale,
+                x_categories,
                 annotations: annotations.unwrap_or_default(),
                 title,
                 x_label,
                 y_label,
                 grid,
+                legend,
+                legend_autoscale,
+                colorbar,
+                size_legend,
             },
             series
                 .into_iter()
                 .zip(colors)
                 .zip(sizes)
                 .zip(markers)
-                .map(|(((series, (r, g, b)), marker_size), marker_shape)| {
-                    scatter_series(
-                        series.xs,
-                        series.ys,
-                        Color { r, g, b, a: 0.9 },
-                        marker_size,
-                        marker_shape,
-                    )
-                })
+                .zip(labels)
+                .zip(point_colors.into_iter().zip(point_sizes))
+                .zip(edge_colors.into_iter().zip(edge_widths))
+                .map(
+                    |(
+                        (
+                            ((((series, (r, g, b)), marker_size), marker_shape), label),
+                            (point_colors, point_sizes),
+                        ),
+                        ((er, eg, eb), edge_width),
+                    )| {
+                        let style = ScatterStyle {
+                            color: Color { r, g, b, a: alpha },
+                            size: marker_size,
+                            marker: marker_shape,
+                            colors: point_colors,
+                            sizes: point_sizes,
+                            edge_color: Color {
+                                r: er,
+                                g: eg,
+                                b: eb,
+                                a: 1.0,
+                            },
+                            edge_width,
+                        };
+                        scatter_series(series.xs, series.ys, style, label)
+                    },
+                )
                 .collect(),
         );
 
-        register_handle(id, PlotHandle::Plot(plot.clone()));
+        register_handle(id, PlotHandle::Plot(Box::new(plot.clone())));
 
         Ok(Self { id, plot })
     }
 
-    /// Render this figure in a window.
-    fn show(&self) -> PyResult<()> {
-        let plot = match take_registered_handle(self.id) {
-            Some(PlotHandle::Plot(plot)) => plot,
-            Some(PlotHandle::Figure(_)) => self.plot.clone(),
-            None => self.plot.clone(),
-        };
-        run_with_plot(plot).map_err(map_backend_error)
+    /// Plot the series of another `Line`, `Scatter` or `Bar` against a
+    /// right-hand y axis of this plot, with that plot's y limits, y scale and
+    /// `y_label`. Both plots must use the same x scale; the x limits grow to
+    /// cover both. The other plot is no longer shown on its own.
+    fn twinx(&mut self, other: &Bound<'_, PyAny>) -> PyResult<()> {
+        attach_twin(self.id, &mut self.plot, other)
     }
 
-    /// Render this figure offscreen and save it as a PNG.
-    #[pyo3(signature = (path=None))]
-    fn save(&self, py: Python<'_>, path: Option<&str>) -> PyResult<()> {
+    /// Render this figure in a window. `samples` is the number of samples
+    /// per pixel used to smooth edges: 1 (no anti-aliasing), 4 or 8.
+    #[pyo3(signature = (*, samples=4))]
+    fn show(&self, samples: u32) -> PyResult<()> {
+        let samples = check_samples(samples)?;
         let plot = match take_registered_handle(self.id) {
-            Some(PlotHandle::Plot(plot)) => plot,
-            Some(PlotHandle::Figure(_)) => self.plot.clone(),
-            None => self.plot.clone(),
+            Some(PlotHandle::Plot(plot)) => *plot,
+            _ => self.plot.clone(),
         };
-        let fig = plot.build_figure(&plot.initial_view());
+        run_with_plot(plot, samples).map_err(map_backend_error)
+    }
+
+    /// Save this figure as a PNG, or as a vector SVG or PDF when `path`
+    /// ends in `.svg` or `.pdf`.
+    ///
+    /// `dpi` sets the resolution of PNGs: sizes are given at 100 dpi, so
+    /// `dpi=200` saves the same layout at twice the width and height in
+    /// pixels. `samples` is the number of samples per pixel used to smooth
+    /// edges: 1 (no anti-aliasing), 4 or 8. `renderer` picks what draws
+    /// PNGs: `"gpu"`, `"cpu"` for the software renderer that needs no GPU,
+    /// or `"auto"` for the GPU when there is one and the CPU otherwise.
+    #[pyo3(signature = (path=None, *, dpi=100.0, samples=4, renderer="auto"))]
+    fn save(
+        &self,
+        py: Python<'_>,
+        path: Option<&str>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<()> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
         let output_path = resolve_output_path(py, path)?;
-        save_figure_png(&fig, &output_path).map_err(|e| PyRuntimeError::new_err(format!("{e}")))
+        output_format(&output_path)?;
+        let fig = self.take_figure(scale);
+        save_figure(&fig, &output_path, samples, renderer)
     }
+
+    /// Render this figure as the bytes of a PNG file, as `save()` would
+    /// write it, for serving or embedding without a file on disk. Takes the
+    /// same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_png<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyBytes>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale);
+        let image = render_figure(&fig, samples, renderer)?;
+        png_bytes(py, &image)
+    }
+
+    /// Render this figure to 8-bit RGBA pixels, rows top to bottom, as a
+    /// read-only memoryview of shape `(height, width, 4)`. `bytes()` of it
+    /// gives the raw pixels and `numpy.asarray()` an array without a copy.
+    /// Takes the same `dpi`, `samples` and `renderer` as `save()`.
+    #[pyo3(signature = (*, dpi=100.0, samples=4, renderer="auto"))]
+    fn to_rgba<'py>(
+        &self,
+        py: Python<'py>,
+        dpi: f32,
+        samples: u32,
+        renderer: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let scale = dpi_scale(dpi)?;
+        let samples = check_samples(samples)?;
+        let renderer = check_renderer(renderer)?;
+        let fig = self.take_figure(scale);
+        let image = render_figure(&fig, samples, renderer)?;
+        rgba_view(py, &image)
+    }
+}
+
+impl PyScatter {
+    /// Take this plot out of the queue of `show()` and lay it out at
+    /// `scale` device pixels per logical pixel.
+    fn take_figure(&self, scale: f32) -> Figure {
+        let mut plot = match take_registered_handle(self.id) {
+            Some(PlotHandle::Plot(plot)) => *plot,
+            _ => self.plot.clone(),
+        };
+        plot.size.dpi = scale;
+        plot.build_figure(&plot.initial_view())
+    }
+}
+
+/// Per-point values such as colours or sizes for each series: a flat list for a single series
+/// or one list per series, each as long as its series.
+fn resolve_point_values(
+    value: Option<&Bound<'_, PyAny>>,
+    series: &[SeriesData],
+    name: &str,
+) -> PyResult<Option<Vec<Vec<f64>>>> {
+    let Some(obj) = value else {
+        return Ok(None);
+    };
+
+    let values = match (series.len(), obj.extract::<Vec<f64>>()) {
+        (1, Ok(flat)) => vec![flat],
+        _ => obj.extract::<Vec<Vec<f64>>>().map_err(|_| {
+            PyValueError::new_err(format!(
+                "{name} must be a list of numbers, or one list per series for several series"
+            ))
+        })?,
+    };
+
+    if values.len() != series.len() {
+        return Err(PyValueError::new_err(format!(
+            "{name} expected {} lists of values, got {}",
+            series.len(),
+            values.len()
+        )));
+    }
+    for (idx, (values, series)) in values.iter().zip(series).enumerate() {
+        if values.len() != series.xs.len() {
+            return Err(PyValueError::new_err(format!(
+                "{name} expected {} values for series {idx}, got {}",
+                series.xs.len(),
+                values.len()
+            )));
+        }
+    }
+    Ok(Some(values))
+}
+
+/// The `(vmin, vmax)` range for colour mapping, filling gaps from the finite
+/// values.
+fn value_range(
+    values: impl Iterator<Item = f64>,
+    vmin: Option<f64>,
+    vmax: Option<f64>,
+) -> (f64, f64) {
+    let (lo, hi) = values
+        .filter(|value| value.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), value| {
+            (lo.min(value), hi.max(value))
+        });
+    let (lo, hi) = if lo.is_finite() { (lo, hi) } else { (0.0, 1.0) };
+    (vmin.unwrap_or(lo), vmax.unwrap_or(hi))
+}
+
+/// `None` hides the colorbar; otherwise the inner value is its label.
+fn parse_colorbar(value: Option<&Bound<'_, PyAny>>) -> PyResult<Option<Option<String>>> {
+    let Some(obj) = value else {
+        return Ok(Some(None));
+    };
+    if let Ok(show) = obj.extract::<bool>() {
+        return Ok(show.then_some(None));
+    }
+    obj.extract::<String>()
+        .map(|label| Some(Some(label)))
+        .map_err(|_| PyValueError::new_err("colorbar must be a bool or a label string"))
 }
 
 fn resolve_scatter_colors(
@@ -154,28 +528,37 @@ fn resolve_scatter_colors(
         None => Ok((0..series_count)
             .map(|idx| defaults[idx % defaults.len()])
             .collect()),
-        Some(obj) => {
-            if let Some(rgb) = try_extract_rgb(obj)? {
-                return Ok(vec![rgb; series_count]);
-            }
+        Some(obj) => resolve_rgb_values(obj, series_count, "color"),
+    }
+}
 
-            let colors = PyIterator::from_object(obj)
-                .map_err(|_| {
-                    PyRuntimeError::new_err("color must be an RGB tuple or iterable of RGB tuples")
-                })?
-                .map(|item| item.and_then(|item| extract_rgb(&item)))
-                .collect::<PyResult<Vec<_>>>()?;
+/// One RGB tuple for every series, or one per series.
+fn resolve_rgb_values(
+    obj: &Bound<'_, PyAny>,
+    series_count: usize,
+    name: &str,
+) -> PyResult<Vec<(f32, f32, f32)>> {
+    if let Some(rgb) = try_extract_rgb(obj)? {
+        return Ok(vec![rgb; series_count]);
+    }
 
-            if colors.len() != series_count {
-                return Err(PyRuntimeError::new_err(format!(
-                    "color expected 1 value or {series_count} values, got {}",
-                    colors.len()
-                )));
-            }
+    let colors = PyIterator::from_object(obj)
+        .map_err(|_| {
+            PyRuntimeError::new_err(format!(
+                "{name} must be an RGB tuple or iterable of RGB tuples"
+            ))
+        })?
+        .map(|item| item.and_then(|item| extract_rgb(&item)))
+        .collect::<PyResult<Vec<_>>>()?;
 
-            Ok(colors)
-        }
+    if colors.len() != series_count {
+        return Err(PyRuntimeError::new_err(format!(
+            "{name} expected 1 value or {series_count} values, got {}",
+            colors.len()
+        )));
     }
+
+    Ok(colors)
 }
 
 fn resolve_markers(
@@ -216,10 +599,23 @@ fn resolve_markers(
 
 fn parse_marker(value: &str) -> PyResult<Marker> {
     match value {
-        "circle" => Ok(Marker::Circle),
-        "square" => Ok(Marker::Square),
+        "circle" | "o" => Ok(Marker::Circle),
+        "square" | "s" => Ok(Marker::Square),
+        "triangle_up" | "^" => Ok(Marker::TriangleUp),
+        "triangle_down" | "v" => Ok(Marker::TriangleDown),
+        "triangle_left" | "<" => Ok(Marker::TriangleLeft),
+        "triangle_right" | ">" => Ok(Marker::TriangleRight),
+        "diamond" | "D" => Ok(Marker::Diamond),
+        "plus" | "+" => Ok(Marker::Plus),
+        "cross" | "x" => Ok(Marker::Cross),
+        "star" | "*" => Ok(Marker::Star),
+        "hexagon" | "h" => Ok(Marker::Hexagon),
+        "pentagon" | "p" => Ok(Marker::Pentagon),
         other => Err(PyValueError::new_err(format!(
-            "unknown marker '{other}', expected 'circle' or 'square'"
+            "unknown marker '{other}', expected 'circle', 'square', 'triangle_up', \
+             'triangle_down', 'triangle_left', 'triangle_right', 'diamond', 'plus', \
+             'cross', 'star', 'hexagon' or 'pentagon' (or a matplotlib-style \
+             shorthand such as 'o', '^' or 'D')"
         ))),
     }
 }
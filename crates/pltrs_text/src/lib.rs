@@ -1,9 +1,12 @@
 use anyhow::{Context, anyhow};
-use pltrs_core::TextBatch;
-use std::{fs, path::PathBuf};
+use pltrs_core::{Color, TextBatch};
+use std::{borrow::Cow, fs, path::PathBuf};
 use wgpu_text::{
     BrushBuilder, TextBrush,
-    glyph_brush::{Section as TextSection, Text, ab_glyph::FontArc},
+    glyph_brush::{
+        GlyphPositioner, Section as TextSection, SectionGeometry, Text,
+        ab_glyph::{Font, FontArc},
+    },
 };
 
 pub struct TextRenderer {
@@ -28,33 +31,14 @@ impl TextRenderer {
         queue: &wgpu::Queue,
         width: u32,
         height: u32,
+        dpi: f32,
         batches: &[TextBatch],
     ) {
         if batches.is_empty() {
             return;
         }
 
-        let sections: Vec<TextSection> = batches
-            .iter()
-            .map(|batch| {
-                let x_px = batch.position[0] * width as f32;
-                let y_px = (1.0 - batch.position[1]) * height as f32;
-
-                TextSection::default()
-                    .with_screen_position((x_px, y_px))
-                    .add_text(
-                        Text::new(&batch.content)
-                            .with_scale(batch.size)
-                            .with_color([
-                                batch.color.r,
-                                batch.color.g,
-                                batch.color.b,
-                                batch.color.a,
-                            ]),
-                    )
-            })
-            .collect();
-
+        let sections = sections(width, height, dpi, batches);
         if let Err(err) = self.brush.queue(device, queue, sections) {
             eprintln!("Text queue error: {:?}", err);
         }
@@ -93,15 +77,140 @@ impl TextRenderer {
     }
 }
 
+/// Lays text out and rasterizes it on the CPU, with glyphs where
+/// `TextRenderer` places them.
+pub struct TextRasterizer {
+    font: FontArc,
+}
+
+impl TextRasterizer {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self { font: load_font()? })
+    }
+
+    /// Call `draw` with the pixel, the share of it covered and the colour
+    /// for every pixel the text of `batches` touches on a target of `width`
+    /// by `height` device pixels.
+    pub fn rasterize(
+        &self,
+        width: u32,
+        height: u32,
+        dpi: f32,
+        batches: &[TextBatch],
+        mut draw: impl FnMut(u32, u32, f32, Color),
+    ) {
+        let fonts = [&self.font];
+        for section in sections(width, height, dpi, batches) {
+            let geometry = SectionGeometry {
+                screen_position: section.screen_position,
+                bounds: section.bounds,
+            };
+            for glyph in section
+                .layout
+                .calculate_glyphs(&fonts, &geometry, &section.text)
+            {
+                let [r, g, b, a] = section.text[glyph.section_index].extra.color;
+                let color = Color { r, g, b, a };
+                let Some(outline) = self.font.outline_glyph(glyph.glyph) else {
+                    continue;
+                };
+                let bounds = outline.px_bounds();
+                outline.draw(|x, y, coverage| {
+                    let x = bounds.min.x as i64 + x as i64;
+                    let y = bounds.min.y as i64 + y as i64;
+                    if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                        draw(x as u32, y as u32, coverage, color);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// One section per batch, placed in device pixels on a target of `width`
+/// by `height`.
+fn sections(width: u32, height: u32, dpi: f32, batches: &[TextBatch]) -> Vec<TextSection<'_>> {
+    batches
+        .iter()
+        .map(|batch| {
+            let x_px = batch.position[0] * width as f32;
+            let y_px = (1.0 - batch.position[1]) * height as f32;
+
+            TextSection::default()
+                .with_screen_position((x_px, y_px))
+                .add_text(
+                    Text::new(&batch.content)
+                        .with_scale(batch.size * dpi)
+                        .with_color([batch.color.r, batch.color.g, batch.color.b, batch.color.a]),
+                )
+        })
+        .collect()
+}
+
 fn load_font() -> anyhow::Result<FontArc> {
+    match font_data()? {
+        Cow::Borrowed(bytes) => FontArc::try_from_slice(bytes),
+        Cow::Owned(bytes) => FontArc::try_from_vec(bytes),
+    }
+    .map_err(|_| anyhow!("failed to parse font"))
+}
+
+/// The font text is drawn with: the file named by `PLTRS_FONT_PATH`, or the
+/// bundled Noto Sans.
+pub fn font_data() -> anyhow::Result<Cow<'static, [u8]>> {
     if let Some(font_path) = std::env::var_os("PLTRS_FONT_PATH") {
         let path = PathBuf::from(font_path);
         let font_bytes =
             fs::read(&path).with_context(|| format!("failed to read font from {:?}", path))?;
-        return FontArc::try_from_vec(font_bytes)
-            .map_err(|_| anyhow!("failed to parse font at {:?}", path));
+        return Ok(Cow::Owned(font_bytes));
+    }
+
+    Ok(Cow::Borrowed(include_bytes!(
+        "../assets/NotoSans[wght].ttf"
+    )))
+}
+
+/// How the text renderer sizes and places text, for backends that lay it
+/// out themselves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontMetrics {
+    pub family: String,
+    /// Distance from the top of a line to the baseline, per pixel of text
+    /// size.
+    pub ascent: f32,
+    /// Font size (em) per pixel of text size. Text sizes span the font's
+    /// ascent to descent, so this is below 1 for most fonts.
+    pub em: f32,
+}
+
+impl FontMetrics {
+    pub fn new(data: &[u8]) -> anyhow::Result<Self> {
+        let face = ttf_parser::Face::parse(data, 0)
+            .map_err(|err| anyhow!("failed to parse font: {err}"))?;
+        let height = (face.ascender() as f32 - face.descender() as f32).max(1.0);
+        let family = face
+            .names()
+            .into_iter()
+            .filter(|name| name.name_id == ttf_parser::name_id::FAMILY)
+            .find_map(|name| name.to_string())
+            .unwrap_or_else(|| "sans-serif".to_string());
+        Ok(Self {
+            family,
+            ascent: face.ascender() as f32 / height,
+            em: face.units_per_em() as f32 / height,
+        })
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    FontArc::try_from_slice(include_bytes!("../assets/NotoSans[wght].ttf"))
-        .map_err(|_| anyhow!("failed to parse bundled default font"))
+    #[test]
+    fn bundled_font_metrics() {
+        let metrics = FontMetrics::new(&font_data().unwrap()).unwrap();
+        assert_eq!(metrics.family, "Noto Sans");
+        assert!(metrics.ascent > 0.5 && metrics.ascent < 1.0);
+        assert!(metrics.em > 0.5 && metrics.em < 1.0);
+    }
 }